@@ -0,0 +1,35 @@
+//! Polygon-polygon intersects under each index.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tg_sys::{tg_point, Geom};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+const POINTS: usize = 4096;
+
+fn bench(c: &mut Criterion) {
+    // Overlapping edges, so the answer needs the segments, not just the
+    // rectangles; a small shape wholly inside; and one in a corner of the
+    // bounding box but outside the ring.
+    let cases = [
+        ("overlapping", tg_point { x: 150.0, y: 0.0 }, 100.0),
+        ("inside", tg_point { x: 10.0, y: 10.0 }, 20.0),
+        ("outside", tg_point { x: 95.0, y: 95.0 }, 5.0),
+    ];
+    for (name, ix) in common::INDEXES {
+        let a = common::polygon(POINTS, ix);
+        let mut group = c.benchmark_group(format!("intersects/{}", name));
+        for (case, center, radius) in cases {
+            let wkt = common::polygon_wkt_at(POINTS, center, radius);
+            let b_geom = Geom::parse_wkt_ix(&wkt, ix).unwrap();
+            group.bench_function(case, |b| {
+                b.iter(|| black_box(&a).intersects(black_box(&b_geom)))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,101 @@
+//! kNN over the segments of a ring with `tg_ring_nearest_segment`, and of a
+//! line with `tg_line_nearest_segment`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tg_sys::{
+    tg_point, tg_rect, tg_segment, Line, LineFuncs::tg_line_nearest_segment,
+    RingFuncs::tg_ring_nearest_segment,
+};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+struct Search {
+    point: tg_point,
+    k: usize,
+    found: Vec<(usize, f64)>,
+}
+
+extern "C" fn rect_dist(rect: tg_rect, _more: *mut libc::c_int, udata: *mut libc::c_void) -> f64 {
+    let search = unsafe { &*(udata as *const Search) };
+    rect.distance_to_point(search.point)
+}
+
+extern "C" fn seg_dist(seg: tg_segment, _more: *mut libc::c_int, udata: *mut libc::c_void) -> f64 {
+    let search = unsafe { &*(udata as *const Search) };
+    let p = search.point;
+    let (dx, dy) = (seg.b.x - seg.a.x, seg.b.y - seg.a.y);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 > 0.0 {
+        (((p.x - seg.a.x) * dx + (p.y - seg.a.y) * dy) / len2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (seg.a.x + t * dx - p.x).hypot(seg.a.y + t * dy - p.y)
+}
+
+extern "C" fn iter(
+    _seg: tg_segment,
+    dist: f64,
+    index: libc::c_int,
+    udata: *mut libc::c_void,
+) -> bool {
+    let search = unsafe { &mut *(udata as *mut Search) };
+    search.found.push((index as usize, dist));
+    search.found.len() < search.k
+}
+
+/// Finds the `k` nearest segments to each point with `nearest`, which is
+/// handed the search as `udata`.
+fn knn(
+    c: &mut Criterion,
+    group: String,
+    points: &[tg_point],
+    nearest: impl Fn(*mut libc::c_void) -> bool,
+) {
+    let mut group = c.benchmark_group(group);
+    for k in [1, 10, 100] {
+        group.bench_with_input(BenchmarkId::from_parameter(k), &k, |b, &k| {
+            b.iter(|| {
+                let mut total = 0;
+                for &point in points {
+                    let mut search = Search {
+                        point,
+                        k,
+                        found: Vec::with_capacity(k),
+                    };
+                    assert!(nearest(&mut search as *mut Search as *mut libc::c_void));
+                    total += search.found.len();
+                }
+                total
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    let points = common::points(1000);
+    for (name, ix) in common::INDEXES {
+        let geom = common::polygon(4096, ix);
+        let ring = geom.poly().unwrap().exterior();
+        knn(
+            c,
+            format!("nearest_segment/{}", name),
+            &points,
+            |udata| unsafe { tg_ring_nearest_segment(ring, rect_dist, seg_dist, iter, udata) },
+        );
+
+        // The same wobbly circle as a LineString.
+        let line = Line::new_ix(ring.points(), ix);
+        knn(
+            c,
+            format!("line_nearest_segment/{}", name),
+            &points,
+            |udata| unsafe { tg_line_nearest_segment(&*line, rect_dist, seg_dist, iter, udata) },
+        );
+    }
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
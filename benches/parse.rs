@@ -0,0 +1,55 @@
+//! Parsing and writing each supported format.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use tg_sys::{tg_index, Geom};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+const POINTS: usize = 4096;
+
+fn bench_parse(c: &mut Criterion) {
+    let geom = common::polygon(POINTS, tg_index::TG_DEFAULT);
+    let wkt = geom.to_wkt();
+    let geojson = geom.to_geojson();
+    let wkb = geom.to_wkb();
+    let hex = geom.to_hex();
+    // Parsing builds the index too, so compare with and without one.
+    for (name, ix) in common::INDEXES {
+        let mut group = c.benchmark_group(format!("parse/{}", name));
+        group.bench_with_input(BenchmarkId::new("wkt", POINTS), &wkt, |b, s| {
+            b.iter(|| Geom::parse_wkt_ix(black_box(s), ix).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("geojson", POINTS), &geojson, |b, s| {
+            b.iter(|| Geom::parse_geojson_ix(black_box(s), ix).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("wkb", POINTS), &wkb, |b, s| {
+            b.iter(|| Geom::parse_wkb_ix(black_box(s), ix).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("hex", POINTS), &hex, |b, s| {
+            b.iter(|| Geom::parse_hex_ix(black_box(s), ix).unwrap())
+        });
+        group.finish();
+    }
+}
+
+fn bench_write(c: &mut Criterion) {
+    let geom = common::polygon(POINTS, tg_index::TG_DEFAULT);
+    let mut group = c.benchmark_group("write");
+    group.bench_function(BenchmarkId::new("wkt", POINTS), |b| {
+        b.iter(|| black_box(&geom).to_wkt())
+    });
+    group.bench_function(BenchmarkId::new("geojson", POINTS), |b| {
+        b.iter(|| black_box(&geom).to_geojson())
+    });
+    group.bench_function(BenchmarkId::new("wkb", POINTS), |b| {
+        b.iter(|| black_box(&geom).to_wkb())
+    });
+    group.bench_function(BenchmarkId::new("hex", POINTS), |b| {
+        b.iter(|| black_box(&geom).to_hex())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_write);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use tg_sys::GeometryPredicates::tg_geom_intersects_xy;
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+fn bench(c: &mut Criterion) {
+    let points = common::points(100_000);
+    for (name, ix) in common::INDEXES {
+        let geom = common::polygon(4096, ix);
+        let mut group = c.benchmark_group(format!("point_in_polygon/{}", name));
+        group.bench_function(BenchmarkId::new("loop", points.len()), |b| {
+            b.iter(|| {
+                points
+                    .iter()
+                    .map(|p| unsafe { tg_geom_intersects_xy(geom.as_ptr(), p.x, p.y) })
+                    .collect::<Vec<bool>>()
+            })
+        });
+        group.bench_function(BenchmarkId::new("contains_points", points.len()), |b| {
+            b.iter(|| geom.contains_points(black_box(&points)))
+        });
+        group.bench_function(BenchmarkId::new("intersects_xy_batch", points.len()), |b| {
+            b.iter(|| geom.intersects_xy_batch(black_box(&points)))
+        });
+        group.finish();
+    }
+}
+
+/// Single point queries against rings of growing size, under each index.
+fn bench_ring_size(c: &mut Criterion) {
+    let points = common::points(1000);
+    for (name, ix) in common::INDEXES {
+        let mut group = c.benchmark_group(format!("point_in_polygon/size/{}", name));
+        for n in [256, 4096, 65536] {
+            let geom = common::polygon(n, ix);
+            group.bench_with_input(BenchmarkId::from_parameter(n), &geom, |b, geom| {
+                b.iter(|| {
+                    points
+                        .iter()
+                        .filter(|p| geom.intersects_xy(p.x, p.y))
+                        .count()
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench, bench_ring_size);
+criterion_main!(benches);
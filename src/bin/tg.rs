@@ -0,0 +1,405 @@
+//! `tg` command-line tool.
+//!
+//! Converts geometries between formats, evaluates spatial predicates, and
+//! reports information about geometries.
+//!
+//! Every input operand is either a literal geometry, `@path` to read a file
+//! with one geometry per line, or `-` to read stdin one geometry per line.
+//! When a command is given no inputs at all, stdin is read.
+
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::ExitCode;
+
+use tg_sys::{
+    tg_geom, tg_geom_type, tg_poly, Geom, GeometryAccessors::tg_geom_type_string, Predicate,
+};
+
+const USAGE: &str = "\
+Usage:
+    tg convert [--from FORMAT] --to FORMAT [INPUT...]
+    tg predicate [--from FORMAT] NAME A B
+    tg info [--from FORMAT] [INPUT...]
+    tg validate [--from FORMAT] [INPUT...]
+
+Formats:
+    wkt, geojson, hex (input also accepts `auto`, the default)
+
+Predicates:
+    equals, intersects, disjoint, contains, within, covers, coveredby
+
+Inputs:
+    GEOMETRY    a literal geometry
+    @PATH       a file with one geometry per line
+    -           stdin, one geometry per line";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Auto,
+    Wkt,
+    GeoJson,
+    Hex,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Result<Format, String> {
+        match name {
+            "auto" => Ok(Format::Auto),
+            "wkt" => Ok(Format::Wkt),
+            "geojson" | "json" => Ok(Format::GeoJson),
+            "hex" => Ok(Format::Hex),
+            "wkb" => Err("binary WKB isn't supported; use hex for hex-encoded WKB".to_string()),
+            _ => Err(format!("unknown format {:?}", name)),
+        }
+    }
+
+    /// Guesses the format of `text` when the format is `Auto`.
+    fn detect(self, text: &str) -> Format {
+        if self != Format::Auto {
+            return self;
+        }
+        if text.starts_with('{') {
+            Format::GeoJson
+        } else if text.bytes().all(|b| b.is_ascii_hexdigit()) {
+            Format::Hex
+        } else {
+            Format::Wkt
+        }
+    }
+}
+
+fn parse(text: &str, format: Format) -> Result<Geom, String> {
+    let geom = match format.detect(text) {
+        Format::GeoJson => Geom::parse_geojson(text),
+        Format::Hex => Geom::parse_hex(text),
+        Format::Wkt | Format::Auto => Geom::parse_wkt(text),
+    };
+    geom.map_err(|err| err.to_string())
+}
+
+fn write(geom: &Geom, format: Format) -> String {
+    match format {
+        Format::GeoJson => geom.to_geojson(),
+        Format::Hex => geom.to_hex(),
+        Format::Wkt | Format::Auto => geom.to_wkt(),
+    }
+}
+
+/// A single line of input, along with where it came from for error reporting.
+struct Input {
+    origin: String,
+    text: String,
+}
+
+/// Expands an operand into the geometries text it refers to.
+fn read_operand(operand: &str) -> Box<dyn Iterator<Item = io::Result<Input>>> {
+    if operand == "-" {
+        read_lines("stdin".to_string(), BufReader::new(io::stdin()))
+    } else if let Some(path) = operand.strip_prefix('@') {
+        match File::open(path) {
+            Ok(file) => read_lines(path.to_string(), BufReader::new(file)),
+            Err(err) => Box::new(std::iter::once(Err(io::Error::new(
+                err.kind(),
+                format!("{}: {}", path, err),
+            )))),
+        }
+    } else {
+        Box::new(std::iter::once(Ok(Input {
+            origin: "argument".to_string(),
+            text: operand.to_string(),
+        })))
+    }
+}
+
+fn read_lines(
+    name: String,
+    reader: impl BufRead + 'static,
+) -> Box<dyn Iterator<Item = io::Result<Input>>> {
+    Box::new(
+        reader
+            .lines()
+            .enumerate()
+            .filter_map(move |(i, line)| match line {
+                Ok(line) if line.trim().is_empty() => None,
+                Ok(line) => Some(Ok(Input {
+                    origin: format!("{}:{}", name, i + 1),
+                    text: line.trim().to_string(),
+                })),
+                Err(err) => Some(Err(err)),
+            }),
+    )
+}
+
+/// Rejects `-` given more than once, since stdin can only be read once.
+fn stdin_once<'a>(operands: impl IntoIterator<Item = &'a String>) -> Result<(), String> {
+    if operands
+        .into_iter()
+        .filter(|operand| *operand == "-")
+        .count()
+        > 1
+    {
+        return Err("only one operand can read stdin".to_string());
+    }
+    Ok(())
+}
+
+fn read_operands(
+    operands: &[String],
+) -> Result<Box<dyn Iterator<Item = io::Result<Input>>>, String> {
+    if operands.is_empty() {
+        return Ok(read_operand("-"));
+    }
+    stdin_once(operands)?;
+    let operands = operands.to_vec();
+    Ok(Box::new(
+        operands
+            .into_iter()
+            .flat_map(|operand| read_operand(&operand)),
+    ))
+}
+
+/// Options shared by all commands, and the remaining positional arguments.
+struct Args {
+    from: Format,
+    to: Option<Format>,
+    positional: Vec<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args {
+        from: Format::Auto,
+        to: None,
+        positional: Vec::new(),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--from" | "--to" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("{} requires a format", arg))?;
+                let format = Format::from_name(value)?;
+                if arg == "--from" {
+                    parsed.from = format;
+                } else if format == Format::Auto {
+                    return Err("--to requires a concrete format".to_string());
+                } else {
+                    parsed.to = Some(format);
+                }
+            }
+            "--" => {
+                parsed.positional.extend(args.by_ref().cloned());
+            }
+            _ => parsed.positional.push(arg.clone()),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Tracks whether any input failed, so the exit status can report it after
+/// every input has been processed.
+struct Status {
+    failed: bool,
+}
+
+impl Status {
+    fn error(&mut self, origin: &str, err: impl std::fmt::Display) {
+        eprintln!("tg: {}: {}", origin, err);
+        self.failed = true;
+    }
+
+    /// Parses every input, reporting failures and yielding the rest.
+    fn each_geom(
+        &mut self,
+        inputs: impl Iterator<Item = io::Result<Input>>,
+        format: Format,
+        mut f: impl FnMut(Geom) -> io::Result<()>,
+    ) -> io::Result<()> {
+        for input in inputs {
+            let input = match input {
+                Ok(input) => input,
+                Err(err) => {
+                    self.error("read", err);
+                    continue;
+                }
+            };
+            match parse(&input.text, format) {
+                Ok(geom) => f(geom)?,
+                Err(err) => self.error(&input.origin, err),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn convert(args: Args, out: &mut impl Write, status: &mut Status) -> Result<(), String> {
+    let to = args.to.ok_or("convert requires --to")?;
+    status
+        .each_geom(read_operands(&args.positional)?, args.from, |geom| {
+            writeln!(out, "{}", write(&geom, to))
+        })
+        .map_err(|err| err.to_string())
+}
+
+fn predicate(args: Args, out: &mut impl Write, status: &mut Status) -> Result<(), String> {
+    let [name, a, b] = &args.positional[..] else {
+        return Err("predicate requires NAME A B".to_string());
+    };
+    let predicate = match name.as_str() {
+        "equals" => Predicate::Equals,
+        "intersects" => Predicate::Intersects,
+        "disjoint" => Predicate::Disjoint,
+        "contains" => Predicate::Contains,
+        "within" => Predicate::Within,
+        "covers" => Predicate::Covers,
+        "coveredby" => Predicate::CoveredBy,
+        _ => return Err(format!("unknown predicate {:?}", name)),
+    };
+    stdin_once([a, b])?;
+    // The right-hand side is held in memory so the left-hand side can stream.
+    let mut right = Vec::new();
+    status
+        .each_geom(read_operand(b), args.from, |geom| {
+            right.push(geom);
+            Ok(())
+        })
+        .map_err(|err| err.to_string())?;
+    status
+        .each_geom(read_operand(a), args.from, |left| {
+            for right in &right {
+                writeln!(out, "{}", predicate.eval(&left, right))?;
+            }
+            Ok(())
+        })
+        .map_err(|err| err.to_string())
+}
+
+fn info(args: Args, out: &mut impl Write, status: &mut Status) -> Result<(), String> {
+    status
+        .each_geom(read_operands(&args.positional)?, args.from, |geom| {
+            let kind = unsafe { CStr::from_ptr(tg_geom_type_string(geom.geom_type())) };
+            let rect = geom.rect();
+            let dims = geom.dims();
+            write!(
+                out,
+                "type={} dims={} z={} m={} empty={} feature={} rect={},{},{},{} points={} memsize={}",
+                kind.to_string_lossy(),
+                2 + dims.extra(),
+                dims.has_z(),
+                dims.has_m(),
+                geom.is_empty(),
+                geom.is_feature(),
+                rect.min.x,
+                rect.min.y,
+                rect.max.x,
+                rect.max.y,
+                num_points(&geom),
+                geom.memsize(),
+            )?;
+            if let Some(levels) = index_levels(&geom) {
+                write!(out, " levels={}", levels)?;
+            }
+            writeln!(out)
+        })
+        .map_err(|err| err.to_string())
+}
+
+fn validate(args: Args, out: &mut impl Write, status: &mut Status) -> Result<(), String> {
+    for input in read_operands(&args.positional)? {
+        let input = match input {
+            Ok(input) => input,
+            Err(err) => {
+                status.error("read", err);
+                continue;
+            }
+        };
+        let result = parse(&input.text, args.from).and_then(|geom| {
+            geom.validate().map_err(|errors| {
+                let errors: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+                errors.join("; ")
+            })
+        });
+        match result {
+            Ok(()) => writeln!(out, "{}: valid", input.origin),
+            Err(err) => {
+                status.failed = true;
+                writeln!(out, "{}: invalid: {}", input.origin, err)
+            }
+        }
+        .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+/// Counts every vertex in a geometry, including ring closing points.
+fn num_points(geom: &tg_geom) -> usize {
+    match geom.geom_type() {
+        tg_geom_type::TG_POINT => usize::from(!geom.is_empty()),
+        tg_geom_type::TG_LINESTRING => geom.line().map_or(0, |line| line.points().len()),
+        tg_geom_type::TG_POLYGON => geom.poly().map_or(0, poly_num_points),
+        tg_geom_type::TG_MULTIPOINT => geom.points().len(),
+        tg_geom_type::TG_MULTILINESTRING => geom.lines().map(|line| line.points().len()).sum(),
+        tg_geom_type::TG_MULTIPOLYGON => geom.polys().map(poly_num_points).sum(),
+        tg_geom_type::TG_GEOMETRYCOLLECTION => geom.geometries().map(num_points).sum(),
+    }
+}
+
+fn poly_num_points(poly: &tg_poly) -> usize {
+    poly.exterior().points().len() + poly.holes().map(|hole| hole.points().len()).sum::<usize>()
+}
+
+/// Returns the number of index levels of a LineString, or of a Polygon's
+/// exterior ring. Other geometry types have no single index to report.
+fn index_levels(geom: &tg_geom) -> Option<usize> {
+    match geom.geom_type() {
+        tg_geom_type::TG_LINESTRING => geom.line().map(|line| line.index_num_levels()),
+        tg_geom_type::TG_POLYGON => geom.poly().map(|poly| poly.exterior().index_num_levels()),
+        _ => None,
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+    if command == "-h" || command == "--help" {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    let args = match parse_args(rest) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("tg: {}\n\n{}", err, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let mut status = Status { failed: false };
+    let result = match command.as_str() {
+        "convert" => convert(args, &mut out, &mut status),
+        "predicate" => predicate(args, &mut out, &mut status),
+        "info" => info(args, &mut out, &mut status),
+        "validate" => validate(args, &mut out, &mut status),
+        _ => Err(format!("unknown command {:?}\n\n{}", command, USAGE)),
+    };
+    let flushed = out.flush();
+
+    if let Err(err) = result {
+        eprintln!("tg: {}", err);
+        return ExitCode::FAILURE;
+    }
+    if let Err(err) = flushed {
+        eprintln!("tg: {}", err);
+        return ExitCode::FAILURE;
+    }
+    if status.failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
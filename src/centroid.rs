@@ -0,0 +1,326 @@
+//! Centroids and points guaranteed to lie on a geometry, such as anchors for
+//! map labels.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::{tg_geom, tg_geom_type, tg_point, tg_poly, tg_rect, Geom};
+
+/// Running sums for a centroid, kept per dimension so that the highest
+/// dimension present wins: any area outweighs every line, and any length
+/// outweighs every point.
+#[derive(Default)]
+struct Centroid {
+    area: f64,
+    area_x: f64,
+    area_y: f64,
+    length: f64,
+    length_x: f64,
+    length_y: f64,
+    points: f64,
+    points_x: f64,
+    points_y: f64,
+}
+
+impl Centroid {
+    fn point(&mut self, p: tg_point) {
+        self.points += 1.0;
+        self.points_x += p.x;
+        self.points_y += p.y;
+    }
+
+    /// Adds a path, closing it back to its first point if `closed`.
+    fn path(&mut self, points: &[tg_point], closed: bool) {
+        for &p in points {
+            self.point(p);
+        }
+        let closing = points.first().filter(|_| closed && points.len() > 1);
+        let ends = points.iter().skip(1).chain(closing);
+        for (&a, &b) in points.iter().zip(ends) {
+            let length = (b.x - a.x).hypot(b.y - a.y);
+            self.length += length;
+            self.length_x += length * (a.x + b.x) / 2.0;
+            self.length_y += length * (a.y + b.y) / 2.0;
+        }
+    }
+
+    fn poly(&mut self, poly: &tg_poly) {
+        let exterior = poly.exterior();
+        self.path(exterior.points(), true);
+        let sign = signed_area(exterior.points()).0.signum();
+        self.ring(exterior.points(), sign);
+        for hole in poly.holes() {
+            self.path(hole.points(), true);
+            let sign = -signed_area(hole.points()).0.signum();
+            self.ring(hole.points(), sign);
+        }
+    }
+
+    /// Adds the area of a ring, counted positive for exteriors and negative
+    /// for holes whatever their winding.
+    fn ring(&mut self, points: &[tg_point], sign: f64) {
+        let (area, x, y) = signed_area(points);
+        self.area += sign * area;
+        self.area_x += sign * x;
+        self.area_y += sign * y;
+    }
+
+    fn geom(&mut self, geom: &tg_geom) {
+        match geom.geom_type() {
+            _ if geom.is_empty() => {}
+            tg_geom_type::TG_POINT => self.point(geom.point()),
+            tg_geom_type::TG_MULTIPOINT => geom.points().for_each(|p| self.point(p)),
+            tg_geom_type::TG_LINESTRING | tg_geom_type::TG_MULTILINESTRING => {
+                for line in geom.line().into_iter().chain(geom.lines()) {
+                    self.path(line.points(), false);
+                }
+            }
+            tg_geom_type::TG_POLYGON | tg_geom_type::TG_MULTIPOLYGON => {
+                for poly in geom.poly().into_iter().chain(geom.polys()) {
+                    self.poly(poly);
+                }
+            }
+            tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                for member in geom.geometries() {
+                    self.geom(member);
+                }
+            }
+        }
+    }
+
+    fn finish(&self) -> Option<tg_point> {
+        let (weight, x, y) = if self.area > 0.0 {
+            (self.area, self.area_x, self.area_y)
+        } else if self.length > 0.0 {
+            (self.length, self.length_x, self.length_y)
+        } else if self.points > 0.0 {
+            (self.points, self.points_x, self.points_y)
+        } else {
+            return None;
+        };
+        Some(tg_point {
+            x: x / weight,
+            y: y / weight,
+        })
+    }
+}
+
+/// Returns the signed area of a ring by the shoelace formula, positive when
+/// counterclockwise, along with its first moments, the area-weighted sums
+/// of the centroid's coordinates.
+fn signed_area(points: &[tg_point]) -> (f64, f64, f64) {
+    let (mut area, mut x, mut y) = (0.0, 0.0, 0.0);
+    let Some(&origin) = points.first() else {
+        return (area, x, y);
+    };
+    // Working relative to a vertex keeps the cross products small.
+    let ends = points.iter().skip(1).chain(Some(&origin));
+    for (a, b) in points.iter().zip(ends) {
+        let (ax, ay) = (a.x - origin.x, a.y - origin.y);
+        let (bx, by) = (b.x - origin.x, b.y - origin.y);
+        let cross = ax * by - bx * ay;
+        area += cross;
+        x += (ax + bx) * cross;
+        y += (ay + by) * cross;
+    }
+    area /= 2.0;
+    (area, x / 6.0 + origin.x * area, y / 6.0 + origin.y * area)
+}
+
+/// A square of the polylabel search, ordered by the best distance any point
+/// inside it could reach.
+#[derive(Clone, Copy)]
+struct Cell {
+    center: tg_point,
+    half: f64,
+    dist: f64,
+    max: f64,
+}
+
+impl Cell {
+    fn new(center: tg_point, half: f64, polys: &[&tg_poly]) -> Cell {
+        let dist = surface_distance(center, polys);
+        Cell {
+            center,
+            half,
+            dist,
+            max: dist + half * std::f64::consts::SQRT_2,
+        }
+    }
+}
+
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.max.total_cmp(&other.max)
+    }
+}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Cell {}
+
+/// Returns the distance from `p` to the nearest ring of the polygons,
+/// positive inside them and negative outside.
+fn surface_distance(p: tg_point, polys: &[&tg_poly]) -> f64 {
+    let mut nearest = f64::INFINITY;
+    for poly in polys {
+        for ring in std::iter::once(poly.exterior()).chain(poly.holes()) {
+            if let Some(q) = ring.closest_point(p) {
+                nearest = nearest.min((q.x - p.x).hypot(q.y - p.y));
+            }
+        }
+    }
+    let inside = polys
+        .iter()
+        .any(|poly| poly.as_geom().intersects_xy(p.x, p.y));
+    if inside {
+        nearest
+    } else {
+        -nearest
+    }
+}
+
+/// The most cells the polylabel search will measure, so that slivers and
+/// hostile input can't keep it splitting forever. Each one costs a
+/// nearest-segment search of every ring.
+const MAX_CELLS: usize = 10_000;
+
+/// The most cells along the longer side of the bounds to start with.
+const MAX_GRID: f64 = 100.0;
+
+/// Finds the point inside the polygons farthest from their boundaries, to
+/// within a thousandth of the larger side of `rect`, with the polylabel
+/// search: cover the bounds with
+/// square cells and keep splitting the cells that could still hold a
+/// better point, most promising first.
+fn polylabel(polys: &[&tg_poly], rect: tg_rect, start: tg_point) -> Option<tg_point> {
+    let (width, height) = (rect.max.x - rect.min.x, rect.max.y - rect.min.y);
+    if width.min(height) <= 0.0 {
+        return None;
+    }
+    let precision = width.max(height) * 1e-3;
+    // Long, thin bounds start with fewer, larger cells than their short side.
+    let size = width.min(height).max(width.max(height) / MAX_GRID);
+
+    let mut queue = BinaryHeap::new();
+    let mut y = rect.min.y;
+    while y < rect.max.y {
+        let mut x = rect.min.x;
+        while x < rect.max.x {
+            let center = tg_point {
+                x: x + size / 2.0,
+                y: y + size / 2.0,
+            };
+            queue.push(Cell::new(center, size / 2.0, polys));
+            x += size;
+        }
+        y += size;
+    }
+    let mut best = Cell::new(start, 0.0, polys);
+    let middle = Cell::new(rect.center(), 0.0, polys);
+    if middle.dist > best.dist {
+        best = middle;
+    }
+    let mut cells = queue.len() + 2;
+    while let Some(cell) = queue.pop() {
+        if cell.dist > best.dist {
+            best = cell;
+        }
+        // Keep splitting until some point inside has been found, however
+        // thin the polygon, or the search runs out of cells.
+        let done = best.dist > 0.0 && cell.max - best.dist <= precision;
+        if done || cell.max <= 0.0 || cells >= MAX_CELLS {
+            continue;
+        }
+        cells += 4;
+        let half = cell.half / 2.0;
+        for (dx, dy) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+            let center = tg_point {
+                x: cell.center.x + dx * half,
+                y: cell.center.y + dy * half,
+            };
+            queue.push(Cell::new(center, half, polys));
+        }
+    }
+    (best.dist > 0.0).then_some(best.center)
+}
+
+/// Collects the polygons of the geometry, including those in collections.
+fn polys<'a>(geom: &'a tg_geom, out: &mut Vec<&'a tg_poly>) {
+    match geom.geom_type() {
+        _ if geom.is_empty() => {}
+        tg_geom_type::TG_POLYGON | tg_geom_type::TG_MULTIPOLYGON => {
+            out.extend(geom.poly().into_iter().chain(geom.polys()));
+        }
+        tg_geom_type::TG_GEOMETRYCOLLECTION => {
+            for member in geom.geometries() {
+                polys(member, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl tg_geom {
+    /// Returns the centroid of the geometry as a Point, or an empty Point
+    /// if the geometry is empty.
+    ///
+    /// The centroid is that of the highest dimension present: the
+    /// area-weighted centroid of the polygons, less their holes; otherwise
+    /// the length-weighted centroid of the lines; otherwise the mean of the
+    /// points. Polygons with no area count as their boundaries, and lines
+    /// with no length as their points. The centroid of a concave polygon
+    /// may lie outside it; see [`point_on_surface()`][tg_geom::point_on_surface].
+    pub fn centroid(&self) -> Geom {
+        let mut centroid = Centroid::default();
+        centroid.geom(self);
+        match centroid.finish() {
+            Some(p) => Geom::new_point(p),
+            None => Geom::new_empty(tg_geom_type::TG_POINT),
+        }
+    }
+
+    /// Returns a Point guaranteed to intersect the geometry, also known as a
+    /// representative point, or an empty Point if the geometry is empty.
+    ///
+    /// For geometries with polygons this is the point inside them farthest
+    /// from any edge, the pole of inaccessibility, found to within a
+    /// thousandth of the size of their bounding rectangle. That makes a good
+    /// anchor for a label. The search gives up after a fixed number of
+    /// steps, so for slivers too thin to find a point inside it falls back
+    /// to the point of the boundary nearest the centroid, as it does for
+    /// lines and points.
+    pub fn point_on_surface(&self) -> Geom {
+        let mut centroid = Centroid::default();
+        centroid.geom(self);
+        let Some(center) = centroid.finish() else {
+            return Geom::new_empty(tg_geom_type::TG_POINT);
+        };
+        if centroid.area > 0.0 {
+            let mut found = Vec::new();
+            polys(self, &mut found);
+            let rect = found
+                .iter()
+                .map(|poly| poly.rect())
+                .reduce(tg_rect::expand)
+                .expect("geometries with area have polygons");
+            if let Some(p) = polylabel(&found, rect, center) {
+                return Geom::new_point(p);
+            }
+        }
+        match self.closest_point(center) {
+            Some(p) => Geom::new_point(p),
+            None => Geom::new_empty(tg_geom_type::TG_POINT),
+        }
+    }
+}
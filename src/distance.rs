@@ -0,0 +1,392 @@
+//! Planar distances between geometries and the closest points that realize
+//! them.
+//!
+//! Lines and rings are searched through their segment index with
+//! [`tg_line_nearest_segment()`] and [`tg_ring_nearest_segment()`], which
+//! visit segments nearest first, so only the first segment of each path is
+//! ever measured exactly.
+//!
+//! [`hausdorff_distance()`][tg_line::hausdorff_distance] and
+//! [`frechet_distance()`][tg_line::frechet_distance] compare two whole paths,
+//! such as a recorded track and the road it should follow.
+//!
+//! ```no_run
+//! use tg_sys::{tg_point, Geom};
+//!
+//! let fence = Geom::parse_wkt("POLYGON((0 0,10 0,10 10,0 10,0 0))").unwrap();
+//! let truck = tg_point { x: 13.0, y: 4.0 };
+//! assert_eq!(fence.closest_point(truck), Some(tg_point { x: 10.0, y: 4.0 }));
+//! assert_eq!(fence.distance_to(&Geom::new_point(truck)), 3.0);
+//! ```
+
+use crate::LineFuncs::tg_line_nearest_segment;
+use crate::RingFuncs::tg_ring_nearest_segment;
+use crate::{tg_geom, tg_geom_type, tg_line, tg_point, tg_poly, tg_rect, tg_ring, tg_segment};
+
+/// A nearest-segment search around one point.
+struct Query {
+    point: tg_point,
+    nearest: Option<(tg_point, f64)>,
+}
+
+impl Query {
+    fn new(point: tg_point) -> Query {
+        Query {
+            point,
+            nearest: None,
+        }
+    }
+
+    fn candidate(&mut self, p: tg_point, dist: f64) {
+        match self.nearest {
+            Some((_, best)) if best <= dist => {}
+            _ => self.nearest = Some((p, dist)),
+        }
+    }
+
+    fn vertex(&mut self, p: tg_point) {
+        self.candidate(p, distance(self.point, p));
+    }
+
+    fn line(&mut self, line: &tg_line) {
+        if let [p] = line.points() {
+            return self.vertex(*p);
+        }
+        let udata = self as *mut Query as *mut libc::c_void;
+        let ok = unsafe { tg_line_nearest_segment(line, rect_dist, seg_dist, first, udata) };
+        assert!(ok, "tg: out of memory");
+    }
+
+    fn ring(&mut self, ring: &tg_ring) {
+        if let [p] = ring.points() {
+            return self.vertex(*p);
+        }
+        let udata = self as *mut Query as *mut libc::c_void;
+        let ok = unsafe { tg_ring_nearest_segment(ring, rect_dist, seg_dist, first, udata) };
+        assert!(ok, "tg: out of memory");
+    }
+
+    fn poly(&mut self, poly: &tg_poly) {
+        if poly.as_geom().intersects_xy(self.point.x, self.point.y) {
+            return self.candidate(self.point, 0.0);
+        }
+        self.ring(poly.exterior());
+        for hole in poly.holes() {
+            self.ring(hole);
+        }
+    }
+
+    fn geom(&mut self, geom: &tg_geom) {
+        if geom.is_empty() {
+            return;
+        }
+        match geom.geom_type() {
+            tg_geom_type::TG_POINT => self.vertex(geom.point()),
+            tg_geom_type::TG_MULTIPOINT => {
+                for p in geom.points() {
+                    self.vertex(p);
+                }
+            }
+            tg_geom_type::TG_LINESTRING => {
+                if let Some(line) = geom.line() {
+                    self.line(line);
+                }
+            }
+            tg_geom_type::TG_MULTILINESTRING => {
+                for line in geom.lines() {
+                    self.line(line);
+                }
+            }
+            tg_geom_type::TG_POLYGON => {
+                if let Some(poly) = geom.poly() {
+                    self.poly(poly);
+                }
+            }
+            tg_geom_type::TG_MULTIPOLYGON => {
+                for poly in geom.polys() {
+                    self.poly(poly);
+                }
+            }
+            tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                for member in geom.geometries() {
+                    self.geom(member);
+                }
+            }
+        }
+    }
+}
+
+extern "C" fn rect_dist(rect: tg_rect, _more: *mut libc::c_int, udata: *mut libc::c_void) -> f64 {
+    let query = unsafe { &*(udata as *const Query) };
+    rect.distance_to_point(query.point)
+}
+
+extern "C" fn seg_dist(seg: tg_segment, _more: *mut libc::c_int, udata: *mut libc::c_void) -> f64 {
+    let query = unsafe { &*(udata as *const Query) };
+    distance(query.point, project(seg, query.point))
+}
+
+/// Keeps the first segment visited, which is the nearest, and stops.
+extern "C" fn first(
+    seg: tg_segment,
+    dist: f64,
+    _index: libc::c_int,
+    udata: *mut libc::c_void,
+) -> bool {
+    let query = unsafe { &mut *(udata as *mut Query) };
+    let p = project(seg, query.point);
+    query.candidate(p, dist);
+    false
+}
+
+/// Returns the point of the segment nearest to `p`.
+fn project(seg: tg_segment, p: tg_point) -> tg_point {
+    let (dx, dy) = (seg.b.x - seg.a.x, seg.b.y - seg.a.y);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 > 0.0 {
+        (((p.x - seg.a.x) * dx + (p.y - seg.a.y) * dy) / len2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    tg_point {
+        x: seg.a.x + t * dx,
+        y: seg.a.y + t * dy,
+    }
+}
+
+fn distance(a: tg_point, b: tg_point) -> f64 {
+    (b.x - a.x).hypot(b.y - a.y)
+}
+
+/// Appends every vertex of the geometry to `out`.
+pub(crate) fn vertices(geom: &tg_geom, out: &mut Vec<tg_point>) {
+    match geom.geom_type() {
+        _ if geom.is_empty() => {}
+        tg_geom_type::TG_POINT => out.push(geom.point()),
+        tg_geom_type::TG_MULTIPOINT => out.extend(geom.points()),
+        tg_geom_type::TG_LINESTRING | tg_geom_type::TG_MULTILINESTRING => {
+            for line in geom.line().into_iter().chain(geom.lines()) {
+                out.extend_from_slice(line.points());
+            }
+        }
+        tg_geom_type::TG_POLYGON | tg_geom_type::TG_MULTIPOLYGON => {
+            for poly in geom.poly().into_iter().chain(geom.polys()) {
+                out.extend_from_slice(poly.exterior().points());
+                for hole in poly.holes() {
+                    out.extend_from_slice(hole.points());
+                }
+            }
+        }
+        tg_geom_type::TG_GEOMETRYCOLLECTION => {
+            for member in geom.geometries() {
+                vertices(member, out);
+            }
+        }
+    }
+}
+
+impl tg_line {
+    /// Returns the point of the line nearest to `point`, or `None` if the
+    /// line has no points.
+    pub fn closest_point(&self, point: tg_point) -> Option<tg_point> {
+        let mut query = Query::new(point);
+        query.line(self);
+        query.nearest.map(|(p, _)| p)
+    }
+}
+
+impl tg_ring {
+    /// Returns the point of the ring's boundary nearest to `point`, or `None`
+    /// if the ring has no points.
+    pub fn closest_point(&self, point: tg_point) -> Option<tg_point> {
+        let mut query = Query::new(point);
+        query.ring(self);
+        query.nearest.map(|(p, _)| p)
+    }
+}
+
+impl tg_poly {
+    /// Returns the point of the polygon nearest to `point`: `point` itself
+    /// when the polygon contains it, otherwise the nearest point of its
+    /// exterior or holes.
+    pub fn closest_point(&self, point: tg_point) -> Option<tg_point> {
+        let mut query = Query::new(point);
+        query.poly(self);
+        query.nearest.map(|(p, _)| p)
+    }
+}
+
+impl tg_geom {
+    /// Returns the point of the geometry nearest to `point`, or `None` if the
+    /// geometry is empty.
+    ///
+    /// Polygons are solid, so for a point inside a polygon this is the point
+    /// itself. Use [`tg_ring::closest_point()`] on the rings to find the
+    /// nearest point of the boundary instead.
+    pub fn closest_point(&self, point: tg_point) -> Option<tg_point> {
+        let mut query = Query::new(point);
+        query.geom(self);
+        query.nearest.map(|(p, _)| p)
+    }
+
+    /// Returns the planar distance between the nearest points of the two
+    /// geometries: zero if they intersect, and infinity if either is empty.
+    pub fn distance_to(&self, other: &tg_geom) -> f64 {
+        if self.is_empty() || other.is_empty() {
+            return f64::INFINITY;
+        }
+        if self.intersects(other) {
+            return 0.0;
+        }
+        // Between disjoint geometries the shortest distance always runs from
+        // a vertex of one to the other.
+        let mut best = f64::INFINITY;
+        for (from, to) in [(self, other), (other, self)] {
+            let mut points = Vec::new();
+            vertices(from, &mut points);
+            for p in points {
+                let mut query = Query::new(p);
+                query.geom(to);
+                if let Some((_, dist)) = query.nearest {
+                    best = best.min(dist);
+                }
+            }
+        }
+        best
+    }
+}
+
+/// A distance between two paths and the pair of points that realizes it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathDistance {
+    pub distance: f64,
+    /// The witness point on the first path.
+    pub a: tg_point,
+    /// The witness point on the second path.
+    pub b: tg_point,
+}
+
+/// Returns the vertex of `points` farthest from the path searched by
+/// `nearest`, paired with the nearest point of that path.
+fn directed_hausdorff(points: &[tg_point], nearest: impl Fn(&mut Query)) -> Option<PathDistance> {
+    let mut worst: Option<PathDistance> = None;
+    for &p in points {
+        let mut query = Query::new(p);
+        nearest(&mut query);
+        let (q, distance) = query.nearest?;
+        match worst {
+            Some(w) if w.distance >= distance => {}
+            _ => {
+                worst = Some(PathDistance {
+                    distance,
+                    a: p,
+                    b: q,
+                })
+            }
+        }
+    }
+    worst
+}
+
+fn hausdorff(
+    a: &[tg_point],
+    a_nearest: impl Fn(&mut Query),
+    b: &[tg_point],
+    b_nearest: impl Fn(&mut Query),
+) -> Option<PathDistance> {
+    let forward = directed_hausdorff(a, b_nearest)?;
+    let backward = directed_hausdorff(b, a_nearest)?;
+    if backward.distance > forward.distance {
+        Some(PathDistance {
+            distance: backward.distance,
+            a: backward.b,
+            b: backward.a,
+        })
+    } else {
+        Some(forward)
+    }
+}
+
+/// The discrete Fréchet distance of Eiter and Mannila, keeping one row of
+/// the coupling table at a time. Each cell holds the cost of the best
+/// coupling reaching it and the pair of vertices that set that cost.
+fn frechet(a: &[tg_point], b: &[tg_point]) -> Option<PathDistance> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let mut prev: Vec<(f64, usize, usize)> = Vec::new();
+    for (i, &p) in a.iter().enumerate() {
+        let mut row: Vec<(f64, usize, usize)> = Vec::with_capacity(b.len());
+        for (j, &q) in b.iter().enumerate() {
+            let here = (distance(p, q), i, j);
+            let reach = match (i, j) {
+                (0, 0) => None,
+                (0, _) => Some(row[j - 1]),
+                (_, 0) => Some(prev[0]),
+                _ => [prev[j - 1], prev[j], row[j - 1]]
+                    .into_iter()
+                    .min_by(|x, y| x.0.total_cmp(&y.0)),
+            };
+            row.push(match reach {
+                Some(reach) if reach.0 > here.0 => reach,
+                _ => here,
+            });
+        }
+        prev = row;
+    }
+    let (distance, i, j) = prev[b.len() - 1];
+    Some(PathDistance {
+        distance,
+        a: a[i],
+        b: b[j],
+    })
+}
+
+impl tg_line {
+    /// Returns the Hausdorff distance between the lines: the farthest any
+    /// vertex of either line lies from the other line.
+    ///
+    /// Each vertex is measured against the segments of the other line
+    /// through its index, so this takes O(n log m) time rather than
+    /// comparing every pair of vertices. Returns `None` if either line has
+    /// no points.
+    pub fn hausdorff_distance(&self, other: &tg_line) -> Option<PathDistance> {
+        hausdorff(
+            self.points(),
+            |query| query.line(self),
+            other.points(),
+            |query| query.line(other),
+        )
+    }
+
+    /// Returns the discrete Fréchet distance between the lines: the
+    /// shortest leash that lets two walkers step through the vertices of
+    /// each line in order, never going back. Unlike the Hausdorff distance
+    /// this notices when the lines visit the same places in a different
+    /// order.
+    ///
+    /// This takes O(nm) time and O(m) memory. Returns `None` if either line
+    /// has no points.
+    pub fn frechet_distance(&self, other: &tg_line) -> Option<PathDistance> {
+        frechet(self.points(), other.points())
+    }
+}
+
+impl tg_ring {
+    /// Returns the Hausdorff distance between the boundaries of the rings.
+    /// See [`tg_line::hausdorff_distance()`].
+    pub fn hausdorff_distance(&self, other: &tg_ring) -> Option<PathDistance> {
+        hausdorff(
+            self.points(),
+            |query| query.ring(self),
+            other.points(),
+            |query| query.ring(other),
+        )
+    }
+
+    /// Returns the discrete Fréchet distance between the rings, walking
+    /// each from its first point. See [`tg_line::frechet_distance()`].
+    pub fn frechet_distance(&self, other: &tg_ring) -> Option<PathDistance> {
+        frechet(self.points(), other.points())
+    }
+}
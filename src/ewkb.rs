@@ -0,0 +1,262 @@
+//! PostGIS Extended WKT and WKB.
+//!
+//! EWKT prefixes WKT with `SRID=<srid>;`. EWKB replaces the ISO WKB dimension
+//! codes with flag bits in the geometry type, and may embed the SRID after the
+//! outermost type. tg reads and writes plain WKT and ISO WKB, so these are
+//! converted on the way in and out, and the SRID is carried by the [`Geom`].
+
+use crate::{tg_index, Error, Geom};
+
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+/// The deepest nesting of collections accepted, so that hostile input can't
+/// overflow the stack.
+const MAX_DEPTH: usize = 1024;
+
+impl Geom {
+    /// Parses PostGIS Extended WKT, such as `SRID=4326;POINT(1 2)`.
+    ///
+    /// The `SRID=` prefix is optional, so plain WKT is accepted too.
+    pub fn parse_ewkt(ewkt: &str) -> Result<Geom, Error> {
+        Geom::parse_ewkt_ix(ewkt, tg_index::TG_DEFAULT)
+    }
+
+    /// Parses PostGIS Extended WKT using the provided indexing option.
+    pub fn parse_ewkt_ix(ewkt: &str, ix: tg_index) -> Result<Geom, Error> {
+        let ewkt = ewkt.trim_start();
+        let prefix = ewkt.get(..5).filter(|p| p.eq_ignore_ascii_case("SRID="));
+        let (srid, wkt) = match prefix {
+            Some(_) => {
+                let (srid, wkt) = ewkt[5..]
+                    .split_once(';')
+                    .ok_or_else(|| Error::new("ewkt: missing ';' after SRID"))?;
+                let srid = srid
+                    .trim()
+                    .parse()
+                    .map_err(|_| Error::new(format!("ewkt: invalid SRID {:?}", srid)))?;
+                (Some(srid), wkt)
+            }
+            None => (None, ewkt),
+        };
+        Ok(Geom::parse_wkt_ix(wkt, ix)?.with_srid(srid))
+    }
+
+    /// Parses PostGIS Extended WKB.
+    ///
+    /// Plain ISO WKB is accepted too.
+    pub fn parse_ewkb(ewkb: &[u8]) -> Result<Geom, Error> {
+        Geom::parse_ewkb_ix(ewkb, tg_index::TG_DEFAULT)
+    }
+
+    /// Parses PostGIS Extended WKB using the provided indexing option.
+    pub fn parse_ewkb_ix(ewkb: &[u8], ix: tg_index) -> Result<Geom, Error> {
+        let mut wkb = Vec::with_capacity(ewkb.len());
+        let srid = Transcoder::new(ewkb, &mut wkb, Target::Iso).geom(0)?;
+        Ok(Geom::parse_wkb_ix(&wkb, ix)?.with_srid(srid))
+    }
+
+    /// Parses hex encoded PostGIS Extended WKB, as PostGIS returns for
+    /// geometry columns.
+    pub fn parse_ewkb_hex(hex: &str) -> Result<Geom, Error> {
+        Geom::parse_ewkb_hex_ix(hex, tg_index::TG_DEFAULT)
+    }
+
+    /// Parses hex encoded PostGIS Extended WKB using the provided indexing
+    /// option.
+    pub fn parse_ewkb_hex_ix(hex: &str, ix: tg_index) -> Result<Geom, Error> {
+        Geom::parse_ewkb_ix(&decode_hex(hex.trim())?, ix)
+    }
+
+    /// Writes the geometry as PostGIS Extended WKT, prefixed with the SRID if
+    /// it has one.
+    pub fn to_ewkt(&self) -> String {
+        match self.srid() {
+            Some(srid) => format!("SRID={};{}", srid, self.to_wkt()),
+            None => self.to_wkt(),
+        }
+    }
+
+    /// Writes the geometry as PostGIS Extended WKB, embedding the SRID if it
+    /// has one.
+    pub fn to_ewkb(&self) -> Vec<u8> {
+        let wkb = self.to_wkb();
+        let mut ewkb = Vec::with_capacity(wkb.len() + 4);
+        Transcoder::new(&wkb, &mut ewkb, Target::Ewkb(self.srid()))
+            .geom(0)
+            .expect("tg writes valid wkb");
+        ewkb
+    }
+
+    /// Writes the geometry as hex encoded PostGIS Extended WKB.
+    pub fn to_ewkb_hex(&self) -> String {
+        self.to_ewkb()
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect()
+    }
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    let digit = |b: u8| match b {
+        b'0'..=b'9' => Ok(b - b'0'),
+        b'a'..=b'f' => Ok(b - b'a' + 10),
+        b'A'..=b'F' => Ok(b - b'A' + 10),
+        _ => Err(Error::new("ewkb: invalid hex digit")),
+    };
+    let hex = hex.as_bytes();
+    if hex.len() % 2 == 1 {
+        return Err(Error::new("ewkb: odd number of hex digits"));
+    }
+    hex.chunks(2)
+        .map(|pair| Ok(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect()
+}
+
+#[derive(Clone, Copy)]
+enum Target {
+    /// ISO WKB, as read by tg.
+    Iso,
+    /// EWKB, with the SRID to embed in the outermost geometry.
+    Ewkb(Option<i32>),
+}
+
+/// Rewrites the geometry headers of a WKB document, copying coordinates
+/// through untouched. Accepts both ISO and EWKB dimension encodings.
+struct Transcoder<'a> {
+    src: &'a [u8],
+    pos: usize,
+    dst: &'a mut Vec<u8>,
+    target: Target,
+}
+
+impl<'a> Transcoder<'a> {
+    fn new(src: &'a [u8], dst: &'a mut Vec<u8>, target: Target) -> Transcoder<'a> {
+        Transcoder {
+            src,
+            pos: 0,
+            dst,
+            target,
+        }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.src.len())
+            .ok_or_else(|| Error::new("ewkb: unexpected end of input"))?;
+        let bytes = &self.src[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self, little: bool) -> Result<u32, Error> {
+        let bytes: [u8; 4] = self.take(4)?.try_into().unwrap();
+        Ok(if little {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn write_u32(&mut self, v: u32, little: bool) {
+        let bytes = if little {
+            v.to_le_bytes()
+        } else {
+            v.to_be_bytes()
+        };
+        self.dst.extend_from_slice(&bytes);
+    }
+
+    /// Copies a point count followed by that many points.
+    fn copy_points(&mut self, little: bool, dims: usize) -> Result<(), Error> {
+        let n = self.read_u32(little)?;
+        self.write_u32(n, little);
+        let len = (n as usize)
+            .checked_mul(dims * 8)
+            .ok_or_else(|| Error::new("ewkb: too many points"))?;
+        let coords = self.take(len)?;
+        self.dst.extend_from_slice(coords);
+        Ok(())
+    }
+
+    /// Transcodes one geometry nested `depth` collections deep, returning the
+    /// SRID it carried.
+    fn geom(&mut self, depth: usize) -> Result<Option<i32>, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::new("ewkb: collections nested too deeply"));
+        }
+        let order = self.take(1)?[0];
+        let little = match order {
+            0 => false,
+            1 => true,
+            _ => return Err(Error::new("ewkb: invalid byte order")),
+        };
+        let code = self.read_u32(little)?;
+        let srid = match code & EWKB_SRID {
+            0 => None,
+            _ => Some(self.read_u32(little)? as i32),
+        };
+        let iso_dims = (code & 0x0fff_ffff) / 1000;
+        let base = (code & 0x0fff_ffff) % 1000;
+        let has_z = code & EWKB_Z != 0 || iso_dims == 1 || iso_dims == 3;
+        let has_m = code & EWKB_M != 0 || iso_dims == 2 || iso_dims == 3;
+
+        self.dst.push(order);
+        match self.target {
+            Target::Iso => {
+                let dims = u32::from(has_z) * 1000 + u32::from(has_m) * 2000;
+                self.write_u32(base + dims, little);
+            }
+            Target::Ewkb(srid) => {
+                let srid = srid.filter(|_| depth == 0);
+                let mut code = base;
+                if has_z {
+                    code |= EWKB_Z;
+                }
+                if has_m {
+                    code |= EWKB_M;
+                }
+                if srid.is_some() {
+                    code |= EWKB_SRID;
+                }
+                self.write_u32(code, little);
+                if let Some(srid) = srid {
+                    self.write_u32(srid as u32, little);
+                }
+            }
+        }
+
+        let dims = 2 + usize::from(has_z) + usize::from(has_m);
+        match base {
+            1 => {
+                let coords = self.take(dims * 8)?;
+                self.dst.extend_from_slice(coords);
+            }
+            2 => self.copy_points(little, dims)?,
+            3 => {
+                let rings = self.read_u32(little)?;
+                self.write_u32(rings, little);
+                for _ in 0..rings {
+                    self.copy_points(little, dims)?;
+                }
+            }
+            4..=7 => {
+                let n = self.read_u32(little)?;
+                self.write_u32(n, little);
+                for _ in 0..n {
+                    self.geom(depth + 1)?;
+                }
+            }
+            _ => {
+                return Err(Error::new(format!(
+                    "ewkb: unsupported geometry type {}",
+                    base
+                )))
+            }
+        }
+        Ok(srid)
+    }
+}
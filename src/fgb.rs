@@ -0,0 +1,896 @@
+//! FlatGeobuf input.
+//!
+//! A FlatGeobuf file holds a header, an optional packed Hilbert R-tree over
+//! the feature bounding boxes, and the features themselves, all encoded as
+//! FlatBuffers. See <https://flatgeobuf.org>.
+//!
+//! ```no_run
+//! use tg_sys::{tg_point, tg_rect, FgbReader};
+//!
+//! let mut reader = FgbReader::open("fences.fgb")?;
+//! let rect = tg_rect {
+//!     min: tg_point { x: -122.5, y: 37.7 },
+//!     max: tg_point { x: -122.4, y: 37.8 },
+//! };
+//! for feature in reader.select_rect(rect)? {
+//!     let feature = feature?;
+//!     println!("{} {:?}", feature.index(), feature.property("name"));
+//! }
+//! # Ok::<(), tg_sys::ReadError>(())
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::{
+    tg_geom_type, tg_index, tg_point, tg_rect, Dims, Error, Geom, Line, Poly, ReadError, Ring,
+    Value,
+};
+
+/// The first bytes of a FlatGeobuf file. The fourth byte is the major
+/// version, and the last is the patch version, which readers ignore.
+const MAGIC: [u8; 3] = *b"fgb";
+const VERSION: u8 = 3;
+
+/// The size of a node in the packed R-tree: a bounding box and an offset.
+const NODE_SIZE: u64 = 40;
+
+/// The deepest nesting of collections accepted, so that hostile input can't
+/// overflow the stack.
+const MAX_DEPTH: usize = 1024;
+
+/// The header of a FlatGeobuf file.
+#[derive(Clone, Debug)]
+pub struct FgbHeader {
+    name: Option<String>,
+    envelope: Option<tg_rect>,
+    geometry_type: u8,
+    dims: Dims,
+    columns: Vec<Column>,
+    features_count: u64,
+    index_node_size: u16,
+    srid: Option<i32>,
+}
+
+#[derive(Clone, Debug)]
+struct Column {
+    name: String,
+    column_type: u8,
+}
+
+impl FgbHeader {
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the bounding rectangle of the dataset, if the file records
+    /// one.
+    pub fn envelope(&self) -> Option<tg_rect> {
+        self.envelope
+    }
+
+    /// Returns the type shared by every geometry in the file, or `None` if
+    /// the types are mixed.
+    pub fn geometry_type(&self) -> Option<tg_geom_type> {
+        geom_type(self.geometry_type)
+    }
+
+    pub fn dims(&self) -> Dims {
+        self.dims
+    }
+
+    /// Returns the names of the attribute columns.
+    pub fn columns(&self) -> impl ExactSizeIterator<Item = &str> + '_ {
+        self.columns.iter().map(|c| c.name.as_str())
+    }
+
+    /// Returns the number of features, which is zero if the writer didn't
+    /// know it in advance.
+    pub fn features_count(&self) -> u64 {
+        self.features_count
+    }
+
+    /// Returns the branching factor of the spatial index, or zero if the
+    /// file has none.
+    pub fn index_node_size(&self) -> u16 {
+        self.index_node_size
+    }
+
+    /// Returns the CRS code, such as 4326 for WGS 84, which is given to each
+    /// geometry as its SRID.
+    pub fn srid(&self) -> Option<i32> {
+        self.srid
+    }
+
+    /// Returns the size in bytes of the spatial index, or `None` if it
+    /// overflows.
+    fn index_size(&self) -> Option<u64> {
+        if self.index_node_size < 2 || self.features_count == 0 {
+            return Some(0);
+        }
+        let levels = level_bounds(self.features_count, self.index_node_size.into())?;
+        let leaves = levels.last()?;
+        leaves.end.checked_mul(NODE_SIZE)
+    }
+}
+
+/// A single feature read from a FlatGeobuf file.
+#[derive(Clone, Debug)]
+pub struct FgbFeature {
+    index: usize,
+    geom: Geom,
+    properties: Vec<(String, Value)>,
+}
+
+impl FgbFeature {
+    /// The position of the feature within the file.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn geom(&self) -> &Geom {
+        &self.geom
+    }
+
+    pub fn into_geom(self) -> Geom {
+        self.geom
+    }
+
+    /// Returns the feature's attributes in the order they're stored.
+    /// Columns without a value for this feature are omitted.
+    pub fn properties(&self) -> &[(String, Value)] {
+        &self.properties
+    }
+
+    /// Returns the value of the named attribute.
+    pub fn property(&self, name: &str) -> Option<&Value> {
+        self.properties
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Reads features from a FlatGeobuf file, optionally selecting them by
+/// bounding rectangle with the file's spatial index.
+pub struct FgbReader<R> {
+    reader: R,
+    ix: tg_index,
+    header: FgbHeader,
+    index_start: u64,
+    features_start: u64,
+}
+
+impl FgbReader<BufReader<File>> {
+    /// Opens a FlatGeobuf file and reads its header.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ReadError> {
+        FgbReader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> FgbReader<R> {
+    /// Reads the header of a FlatGeobuf file, starting at the current
+    /// position of `reader`.
+    pub fn new(reader: R) -> Result<Self, ReadError> {
+        FgbReader::with_index(reader, tg_index::TG_DEFAULT)
+    }
+
+    /// Creates a reader that indexes each geometry with the provided indexing
+    /// option.
+    pub fn with_index(mut reader: R, ix: tg_index) -> Result<Self, ReadError> {
+        let start = reader.stream_position()?;
+        let mut prefix = [0; 12];
+        reader.read_exact(&mut prefix)?;
+        if prefix[..3] != MAGIC || prefix[4..7] != MAGIC {
+            return Err(syntax(start, "not a FlatGeobuf file"));
+        }
+        if prefix[3] != VERSION {
+            return Err(syntax(
+                start + 3,
+                format!("unsupported FlatGeobuf version {}", prefix[3]),
+            ));
+        }
+        let size = u32::from_le_bytes(prefix[8..].try_into().unwrap());
+        let buf = read_buf(&mut reader, size)?;
+        let header = parse_header(&buf).map_err(|err| syntax(start + 8, err.message()))?;
+        let index_start = start + 12 + u64::from(size);
+        let features_start = header
+            .index_size()
+            .and_then(|size| index_start.checked_add(size))
+            .ok_or_else(|| syntax(start + 8, "spatial index is too large"))?;
+        // Every feature takes at least the four bytes of its size.
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(index_start))?;
+        if features_start > end {
+            return Err(syntax(
+                start + 8,
+                "spatial index runs past the end of the file",
+            ));
+        }
+        if header.features_count > (end - features_start) / 4 {
+            return Err(syntax(
+                start + 8,
+                format!("{} features don't fit in the file", header.features_count),
+            ));
+        }
+        Ok(FgbReader {
+            reader,
+            ix,
+            header,
+            index_start,
+            features_start,
+        })
+    }
+
+    pub fn header(&self) -> &FgbHeader {
+        &self.header
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Returns an iterator over every feature in the file, in order.
+    pub fn features(&mut self) -> FgbFeatures<'_, R> {
+        FgbFeatures {
+            reader: self,
+            selection: Selection::All {
+                offset: 0,
+                index: 0,
+                filter: None,
+            },
+        }
+    }
+
+    /// Returns an iterator over the features whose bounding boxes intersect
+    /// `rect`, in file order.
+    ///
+    /// The spatial index is searched up front, reading only the nodes that
+    /// intersect `rect`. Without an index, every feature is read and tested
+    /// against `rect` in turn.
+    pub fn select_rect(&mut self, rect: tg_rect) -> Result<FgbFeatures<'_, R>, ReadError> {
+        if self.features_start == self.index_start {
+            return Ok(FgbFeatures {
+                reader: self,
+                selection: Selection::All {
+                    offset: 0,
+                    index: 0,
+                    filter: Some(rect),
+                },
+            });
+        }
+        let hits = self.search(rect)?;
+        Ok(FgbFeatures {
+            reader: self,
+            selection: Selection::Hits(hits.into_iter()),
+        })
+    }
+
+    /// Searches the packed Hilbert R-tree, returning the offset and position
+    /// of each matching feature, sorted by offset.
+    fn search(&mut self, rect: tg_rect) -> Result<Vec<(u64, usize)>, ReadError> {
+        let node_size = u64::from(self.header.index_node_size);
+        let levels = level_bounds(self.header.features_count, node_size)
+            .expect("index size was checked when the header was read");
+        let leaves = levels.last().expect("index has a leaf level").clone();
+        let mut hits = Vec::new();
+        let mut queue = vec![(0u64, 0usize)];
+        let mut buf = Vec::new();
+        while let Some((first, level)) = queue.pop() {
+            if !levels[level].contains(&first) {
+                return Err(syntax(self.index_start, "index node out of range"));
+            }
+            let end = (first + node_size).min(levels[level].end);
+            buf.resize(((end - first) * NODE_SIZE) as usize, 0);
+            self.reader
+                .seek(SeekFrom::Start(self.index_start + first * NODE_SIZE))?;
+            self.reader.read_exact(&mut buf)?;
+            for (pos, node) in (first..end).zip(buf.chunks_exact(NODE_SIZE as usize)) {
+                let f64_at = |i: usize| f64::from_le_bytes(node[i..i + 8].try_into().unwrap());
+                let bbox = tg_rect {
+                    min: tg_point {
+                        x: f64_at(0),
+                        y: f64_at(8),
+                    },
+                    max: tg_point {
+                        x: f64_at(16),
+                        y: f64_at(24),
+                    },
+                };
+                if !bbox.intersects_rect(rect) {
+                    continue;
+                }
+                let offset = u64::from_le_bytes(node[32..].try_into().unwrap());
+                if level + 1 == levels.len() {
+                    if self.features_start.checked_add(offset).is_none() {
+                        return Err(syntax(self.index_start, "feature offset out of range"));
+                    }
+                    hits.push((offset, (pos - leaves.start) as usize));
+                } else {
+                    queue.push((offset, level + 1));
+                }
+            }
+        }
+        hits.sort_unstable();
+        Ok(hits)
+    }
+
+    /// Reads the feature at `offset` from the start of the feature section,
+    /// returning the offset of the next one. Returns `None` at the end of the
+    /// file.
+    #[allow(clippy::type_complexity)]
+    fn read_feature(
+        &mut self,
+        offset: u64,
+        index: usize,
+    ) -> io::Result<Option<(u64, Result<FgbFeature, Error>)>> {
+        self.reader
+            .seek(SeekFrom::Start(self.features_start + offset))?;
+        let mut prefix = [0; 4];
+        match self.reader.read_exact(&mut prefix) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+        let size = u32::from_le_bytes(prefix);
+        let buf = read_buf(&mut self.reader, size)?;
+        let feature =
+            parse_feature(&buf, &self.header, self.ix).map(|(geom, properties)| FgbFeature {
+                index,
+                geom,
+                properties,
+            });
+        Ok(Some((offset + 4 + u64::from(size), feature)))
+    }
+}
+
+enum Selection {
+    All {
+        offset: u64,
+        index: usize,
+        filter: Option<tg_rect>,
+    },
+    Hits(std::vec::IntoIter<(u64, usize)>),
+    Done,
+}
+
+/// Iterator returned by [`FgbReader::features()`] and
+/// [`FgbReader::select_rect()`].
+///
+/// A feature whose geometry can't be decoded is reported as
+/// [`ReadError::Feature`] and reading continues with the next one. I/O
+/// errors end the iteration.
+pub struct FgbFeatures<'a, R> {
+    reader: &'a mut FgbReader<R>,
+    selection: Selection,
+}
+
+impl<R: Read + Seek> Iterator for FgbFeatures<'_, R> {
+    type Item = Result<FgbFeature, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (offset, index, filter) = match &mut self.selection {
+                Selection::All {
+                    offset,
+                    index,
+                    filter,
+                } => {
+                    let count = self.reader.header.features_count;
+                    if count != 0 && *index as u64 >= count {
+                        return None;
+                    }
+                    (*offset, *index, *filter)
+                }
+                Selection::Hits(hits) => {
+                    let (offset, index) = hits.next()?;
+                    (offset, index, None)
+                }
+                Selection::Done => return None,
+            };
+            let read = self.reader.read_feature(offset, index);
+            let (next, feature) = match read {
+                Ok(Some(read)) => read,
+                Ok(None) if matches!(self.selection, Selection::All { .. }) => return None,
+                // The index points past the end of the file.
+                Ok(None) => {
+                    self.selection = Selection::Done;
+                    return Some(Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()));
+                }
+                Err(err) => {
+                    self.selection = Selection::Done;
+                    return Some(Err(err.into()));
+                }
+            };
+            if let Selection::All { offset, index, .. } = &mut self.selection {
+                *offset = next;
+                *index += 1;
+            }
+            return match feature {
+                Ok(feature)
+                    if filter.is_some_and(|rect| !feature.geom.rect().intersects_rect(rect)) =>
+                {
+                    continue
+                }
+                Ok(feature) => Some(Ok(feature)),
+                Err(error) => Some(Err(ReadError::Feature { index, error })),
+            };
+        }
+    }
+}
+
+fn syntax(offset: u64, message: impl Into<String>) -> ReadError {
+    ReadError::Syntax {
+        offset,
+        message: message.into(),
+    }
+}
+
+/// Reads `size` bytes, growing the buffer as data arrives so that a corrupt
+/// size can't cause a huge allocation.
+fn read_buf<R: Read>(reader: &mut R, size: u32) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    reader.take(size.into()).read_to_end(&mut buf)?;
+    if buf.len() != size as usize {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(buf)
+}
+
+/// Returns the range of node positions for each level of a packed R-tree
+/// over `num_items` leaves, from the root down to the leaves, or `None` if
+/// the positions overflow.
+fn level_bounds(num_items: u64, node_size: u64) -> Option<Vec<Range<u64>>> {
+    let mut counts = vec![num_items];
+    let mut n = num_items;
+    loop {
+        n = n.div_ceil(node_size);
+        counts.push(n);
+        if n <= 1 {
+            break;
+        }
+    }
+    let mut start = 0u64;
+    counts
+        .iter()
+        .rev()
+        .map(|&count| {
+            let range = start..start.checked_add(count)?;
+            start = range.end;
+            Some(range)
+        })
+        .collect()
+}
+
+fn geom_type(code: u8) -> Option<tg_geom_type> {
+    Some(match code {
+        1 => tg_geom_type::TG_POINT,
+        2 => tg_geom_type::TG_LINESTRING,
+        3 => tg_geom_type::TG_POLYGON,
+        4 => tg_geom_type::TG_MULTIPOINT,
+        5 => tg_geom_type::TG_MULTILINESTRING,
+        6 => tg_geom_type::TG_MULTIPOLYGON,
+        7 => tg_geom_type::TG_GEOMETRYCOLLECTION,
+        _ => return None,
+    })
+}
+
+fn parse_header(buf: &[u8]) -> Result<FgbHeader, Error> {
+    let header = Table::root(buf)?;
+    let envelope = header.f64s(1)?.and_then(|e| match e[..] {
+        [min_x, min_y, max_x, max_y, ..] => Some(tg_rect {
+            min: tg_point { x: min_x, y: min_y },
+            max: tg_point { x: max_x, y: max_y },
+        }),
+        _ => None,
+    });
+    let columns = header
+        .tables(7)?
+        .into_iter()
+        .map(|column| {
+            Ok(Column {
+                name: column.str(0)?.unwrap_or_default().to_string(),
+                column_type: column.scalar(1, [0])?[0],
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+    let srid = match header.table(10)? {
+        Some(crs) => Some(i32::from_le_bytes(crs.scalar(1, [0; 4])?)).filter(|&code| code != 0),
+        None => None,
+    };
+    Ok(FgbHeader {
+        name: header.str(0)?.map(str::to_string),
+        envelope,
+        geometry_type: header.scalar(2, [0])?[0],
+        dims: Dims::new(
+            header.scalar(3, [0])?[0] != 0,
+            header.scalar(4, [0])?[0] != 0,
+        ),
+        columns,
+        features_count: u64::from_le_bytes(header.scalar(8, [0; 8])?),
+        index_node_size: u16::from_le_bytes(header.scalar(9, 16u16.to_le_bytes())?),
+        srid,
+    })
+}
+
+fn parse_feature(
+    buf: &[u8],
+    header: &FgbHeader,
+    ix: tg_index,
+) -> Result<(Geom, Vec<(String, Value)>), Error> {
+    let feature = Table::root(buf)?;
+    let geom = match feature.table(0)? {
+        Some(geometry) => {
+            let mut decoder = GeometryDecoder {
+                dims: header.dims,
+                ix,
+                budget: buf.len(),
+            };
+            decoder.geom(&geometry, header.geometry_type, 0)?
+        }
+        None => return Err(Error::new("fgb: feature has no geometry")),
+    };
+    let properties = match feature.bytes(1)? {
+        Some(bytes) => parse_properties(bytes, &header.columns)?,
+        None => Vec::new(),
+    };
+    Ok((geom.with_srid(header.srid), properties))
+}
+
+fn parse_properties(mut bytes: &[u8], columns: &[Column]) -> Result<Vec<(String, Value)>, Error> {
+    fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8], Error> {
+        if bytes.len() < n {
+            return Err(Error::new("fgb: truncated properties"));
+        }
+        let (head, tail) = bytes.split_at(n);
+        *bytes = tail;
+        Ok(head)
+    }
+    fn array<const N: usize>(bytes: &mut &[u8]) -> Result<[u8; N], Error> {
+        Ok(take(bytes, N)?.try_into().unwrap())
+    }
+
+    let mut properties = Vec::new();
+    while !bytes.is_empty() {
+        let column = u16::from_le_bytes(array(&mut bytes)?);
+        let column = columns
+            .get(usize::from(column))
+            .ok_or_else(|| Error::new("fgb: property for unknown column"))?;
+        let value = match column.column_type {
+            0 => Value::Int(i8::from_le_bytes(array(&mut bytes)?).into()),
+            1 => Value::UInt(u8::from_le_bytes(array(&mut bytes)?).into()),
+            2 => Value::Bool(array::<1>(&mut bytes)?[0] != 0),
+            3 => Value::Int(i16::from_le_bytes(array(&mut bytes)?).into()),
+            4 => Value::UInt(u16::from_le_bytes(array(&mut bytes)?).into()),
+            5 => Value::Int(i32::from_le_bytes(array(&mut bytes)?).into()),
+            6 => Value::UInt(u32::from_le_bytes(array(&mut bytes)?).into()),
+            7 => Value::Int(i64::from_le_bytes(array(&mut bytes)?)),
+            8 => Value::UInt(u64::from_le_bytes(array(&mut bytes)?)),
+            9 => Value::Float(f32::from_le_bytes(array(&mut bytes)?).into()),
+            10 => Value::Float(f64::from_le_bytes(array(&mut bytes)?)),
+            column_type @ 11..=14 => {
+                let len = u32::from_le_bytes(array(&mut bytes)?);
+                let data = take(&mut bytes, len as usize)?;
+                let text = || String::from_utf8_lossy(data).into_owned();
+                match column_type {
+                    11 => Value::String(text()),
+                    12 => Value::Json(text()),
+                    13 => Value::DateTime(text()),
+                    _ => Value::Binary(data.to_vec()),
+                }
+            }
+            column_type => {
+                return Err(Error::new(format!(
+                    "fgb: unsupported column type {}",
+                    column_type
+                )))
+            }
+        };
+        properties.push((column.name.clone(), value));
+    }
+    Ok(properties)
+}
+
+/// Splits `n` points into parts at the given end positions.
+fn parts(n: usize, ends: Option<Vec<u32>>) -> Result<Vec<Range<usize>>, Error> {
+    let ends = match ends {
+        Some(ends) if !ends.is_empty() => ends,
+        _ => return Ok(std::iter::once(0..n).collect()),
+    };
+    let mut start = 0;
+    ends.into_iter()
+        .map(|end| {
+            let end = end as usize;
+            if end < start || end > n {
+                return Err(Error::new("fgb: invalid part ends"));
+            }
+            let part = start..end;
+            start = end;
+            Ok(part)
+        })
+        .collect()
+}
+
+struct GeometryDecoder {
+    dims: Dims,
+    ix: tg_index,
+    /// The bytes left to decode. Each table and coordinate is charged once
+    /// per use, so parts that share a table can't decode to more than the
+    /// feature holds.
+    budget: usize,
+}
+
+/// The coordinates of one Geometry table.
+struct Coords {
+    points: Vec<tg_point>,
+    z: Option<Vec<f64>>,
+    m: Option<Vec<f64>>,
+}
+
+impl Coords {
+    /// Returns the Z and M coordinates of the points in `range`, interleaved
+    /// as tg expects.
+    fn extra(&self, dims: Dims, range: Range<usize>) -> Vec<f64> {
+        let get = |coords: &Option<Vec<f64>>, i: usize| {
+            coords
+                .as_ref()
+                .and_then(|c| c.get(i).copied())
+                .unwrap_or(0.0)
+        };
+        let mut extra = Vec::with_capacity(range.len() * dims.extra());
+        for i in range {
+            if dims.has_z() {
+                extra.push(get(&self.z, i));
+            }
+            if dims.has_m() {
+                extra.push(get(&self.m, i));
+            }
+        }
+        extra
+    }
+}
+
+impl GeometryDecoder {
+    fn charge(&mut self, bytes: usize) -> Result<(), Error> {
+        self.budget = self
+            .budget
+            .checked_sub(bytes)
+            .ok_or_else(|| Error::new("fgb: geometry parts overlap"))?;
+        Ok(())
+    }
+
+    fn coords(&mut self, geometry: &Table) -> Result<Coords, Error> {
+        let xy = geometry.f64s(1)?.unwrap_or_default();
+        if xy.len() % 2 == 1 {
+            return Err(Error::new("fgb: odd number of xy coordinates"));
+        }
+        let z = geometry.f64s(2)?;
+        let m = geometry.f64s(3)?;
+        let len = |coords: &Option<Vec<f64>>| coords.as_ref().map_or(0, Vec::len);
+        self.charge(8 * (xy.len() + len(&z) + len(&m)))?;
+        Ok(Coords {
+            points: xy
+                .chunks_exact(2)
+                .map(|p| tg_point { x: p[0], y: p[1] })
+                .collect(),
+            z,
+            m,
+        })
+    }
+
+    fn poly(&mut self, geometry: &Table, extra: &mut Vec<f64>) -> Result<Option<Poly>, Error> {
+        let coords = self.coords(geometry)?;
+        if coords.points.is_empty() {
+            return Ok(None);
+        }
+        let parts = parts(coords.points.len(), geometry.u32s(0)?)?;
+        let rings: Vec<Ring> = parts
+            .iter()
+            .map(|part| Ring::new_ix(&coords.points[part.clone()], self.ix))
+            .collect();
+        extra.extend(coords.extra(self.dims, 0..coords.points.len()));
+        let holes: Vec<_> = rings[1..].iter().map(|h| &**h).collect();
+        Ok(Some(Poly::new(&rings[0], &holes)))
+    }
+
+    /// Decodes a Geometry table nested `depth` collections deep, using
+    /// `default_type` if it doesn't name its own type.
+    fn geom(&mut self, geometry: &Table, default_type: u8, depth: usize) -> Result<Geom, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::new("fgb: collections nested too deeply"));
+        }
+        self.charge(4)?;
+        let code = match geometry.scalar(6, [0])?[0] {
+            0 => default_type,
+            code => code,
+        };
+        let geom_type = geom_type(code)
+            .ok_or_else(|| Error::new(format!("fgb: unsupported geometry type {}", code)))?;
+        let dims = self.dims;
+        let geom = match geom_type {
+            tg_geom_type::TG_POINT
+            | tg_geom_type::TG_LINESTRING
+            | tg_geom_type::TG_MULTIPOINT
+            | tg_geom_type::TG_MULTILINESTRING => {
+                let coords = self.coords(geometry)?;
+                let n = coords.points.len();
+                if n == 0 {
+                    return Ok(Geom::new_empty(geom_type));
+                }
+                let extra = coords.extra(dims, 0..n);
+                match geom_type {
+                    tg_geom_type::TG_POINT => Geom::new_point_ex(coords.points[0], dims, &extra),
+                    tg_geom_type::TG_LINESTRING => {
+                        let line = Line::new_ix(&coords.points, self.ix);
+                        Geom::new_linestring_ex(&line, dims, &extra)
+                    }
+                    tg_geom_type::TG_MULTIPOINT => {
+                        Geom::new_multipoint_ex(&coords.points, dims, &extra)
+                    }
+                    _ => {
+                        let lines: Vec<Line> = parts(n, geometry.u32s(0)?)?
+                            .into_iter()
+                            .map(|part| Line::new_ix(&coords.points[part], self.ix))
+                            .collect();
+                        let lines: Vec<_> = lines.iter().map(|l| &**l).collect();
+                        Geom::new_multilinestring_ex(&lines, dims, &extra)
+                    }
+                }
+            }
+            tg_geom_type::TG_POLYGON => {
+                let mut extra = Vec::new();
+                match self.poly(geometry, &mut extra)? {
+                    Some(poly) => Geom::new_polygon_ex(&poly, dims, &extra),
+                    None => Geom::new_empty(geom_type),
+                }
+            }
+            tg_geom_type::TG_MULTIPOLYGON => {
+                let mut extra = Vec::new();
+                let mut polys = Vec::new();
+                for part in geometry.tables(7)? {
+                    self.charge(4)?;
+                    polys.extend(self.poly(&part, &mut extra)?);
+                }
+                if polys.is_empty() {
+                    return Ok(Geom::new_empty(geom_type));
+                }
+                let polys: Vec<_> = polys.iter().map(|p| &**p).collect();
+                Geom::new_multipolygon_ex(&polys, dims, &extra)
+            }
+            tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                let mut members = Vec::new();
+                for part in geometry.tables(7)? {
+                    members.push(self.geom(&part, 0, depth + 1)?);
+                }
+                let members: Vec<_> = members.iter().map(|g| &**g).collect();
+                Geom::new_geometrycollection(&members)
+            }
+        };
+        Ok(geom)
+    }
+}
+
+/// A FlatBuffers table, read without generated code.
+struct Table<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Table<'a> {
+    fn root(buf: &'a [u8]) -> Result<Table<'a>, Error> {
+        let pos = read_u32(buf, 0)? as usize;
+        Ok(Table { buf, pos })
+    }
+
+    /// Returns the position of field `i`, if it's present.
+    fn field(&self, i: usize) -> Result<Option<usize>, Error> {
+        let soffset = read_u32(self.buf, self.pos)? as i32;
+        let vtable = (self.pos as i64 - i64::from(soffset)) as usize;
+        let vtable_len = usize::from(read_u16(self.buf, vtable)?);
+        let entry = 4 + 2 * i;
+        if entry + 2 > vtable_len {
+            return Ok(None);
+        }
+        match read_u16(self.buf, vtable + entry)? {
+            0 => Ok(None),
+            offset => Ok(Some(self.pos + usize::from(offset))),
+        }
+    }
+
+    /// Reads a scalar field as little-endian bytes.
+    fn scalar<const N: usize>(&self, i: usize, default: [u8; N]) -> Result<[u8; N], Error> {
+        match self.field(i)? {
+            Some(pos) => Ok(slice(self.buf, pos, N)?.try_into().unwrap()),
+            None => Ok(default),
+        }
+    }
+
+    /// Follows the offset stored in field `i`.
+    fn indirect(&self, i: usize) -> Result<Option<usize>, Error> {
+        match self.field(i)? {
+            Some(pos) => Ok(Some(pos + read_u32(self.buf, pos)? as usize)),
+            None => Ok(None),
+        }
+    }
+
+    fn table(&self, i: usize) -> Result<Option<Table<'a>>, Error> {
+        Ok(self.indirect(i)?.map(|pos| Table { buf: self.buf, pos }))
+    }
+
+    /// Returns the contents of a vector field with elements of `size` bytes.
+    fn vector(&self, i: usize, size: usize) -> Result<Option<&'a [u8]>, Error> {
+        match self.indirect(i)? {
+            Some(pos) => {
+                let len = read_u32(self.buf, pos)? as usize;
+                let bytes = len
+                    .checked_mul(size)
+                    .ok_or_else(|| Error::new("fgb: vector is too long"))?;
+                Ok(Some(slice(self.buf, pos + 4, bytes)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn bytes(&self, i: usize) -> Result<Option<&'a [u8]>, Error> {
+        self.vector(i, 1)
+    }
+
+    fn str(&self, i: usize) -> Result<Option<&'a str>, Error> {
+        match self.bytes(i)? {
+            Some(bytes) => std::str::from_utf8(bytes)
+                .map(Some)
+                .map_err(|_| Error::new("fgb: invalid utf-8 in string")),
+            None => Ok(None),
+        }
+    }
+
+    fn f64s(&self, i: usize) -> Result<Option<Vec<f64>>, Error> {
+        Ok(self.vector(i, 8)?.map(|bytes| {
+            bytes
+                .chunks_exact(8)
+                .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+                .collect()
+        }))
+    }
+
+    fn u32s(&self, i: usize) -> Result<Option<Vec<u32>>, Error> {
+        Ok(self.vector(i, 4)?.map(|bytes| {
+            bytes
+                .chunks_exact(4)
+                .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                .collect()
+        }))
+    }
+
+    fn tables(&self, i: usize) -> Result<Vec<Table<'a>>, Error> {
+        let Some(offsets) = self.vector(i, 4)? else {
+            return Ok(Vec::new());
+        };
+        let start = offsets.as_ptr() as usize - self.buf.as_ptr() as usize;
+        (0..offsets.len() / 4)
+            .map(|k| {
+                let pos = start + 4 * k;
+                Ok(Table {
+                    buf: self.buf,
+                    pos: pos + read_u32(self.buf, pos)? as usize,
+                })
+            })
+            .collect()
+    }
+}
+
+fn slice(buf: &[u8], pos: usize, len: usize) -> Result<&[u8], Error> {
+    pos.checked_add(len)
+        .and_then(|end| buf.get(pos..end))
+        .ok_or_else(|| Error::new("fgb: offset out of bounds"))
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> Result<u16, Error> {
+    Ok(u16::from_le_bytes(slice(buf, pos, 2)?.try_into().unwrap()))
+}
+
+fn read_u32(buf: &[u8], pos: usize) -> Result<u32, Error> {
+    Ok(u32::from_le_bytes(slice(buf, pos, 4)?.try_into().unwrap()))
+}
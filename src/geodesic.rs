@@ -0,0 +1,337 @@
+//! Measurements in meters for longitude/latitude geometries on the WGS84
+//! ellipsoid.
+//!
+//! tg's own measures, such as [`tg_ring::area()`] and [`tg_line::length()`],
+//! are planar, which for coordinates in degrees gives square degrees. These
+//! treat `x` as longitude and `y` as latitude, in degrees.
+//!
+//! Distances use Vincenty's inverse formula, accurate to within a millimeter
+//! or so. Areas use the spherical excess on the authalic sphere, the sphere
+//! with the same surface area as the ellipsoid, after mapping latitudes to
+//! authalic latitudes, which keeps areas bounded by the equator and
+//! meridians exact.
+//!
+//! ```no_run
+//! use tg_sys::{distance_m, tg_point};
+//!
+//! let jfk = tg_point { x: -73.8, y: 40.6 };
+//! let lhr = tg_point { x: -0.5, y: 51.6 };
+//! assert!((distance_m(jfk, lhr) - 5_551_759.4).abs() < 1.0);
+//! ```
+
+use std::f64::consts::PI;
+
+use crate::{tg_geom, tg_geom_type, tg_line, tg_point, tg_poly, tg_ring};
+
+/// The WGS84 semi-major axis, in meters.
+const A: f64 = 6_378_137.0;
+
+/// The WGS84 flattening.
+const F: f64 = 1.0 / 298.257_223_563;
+
+/// The WGS84 semi-minor axis, in meters.
+const B: f64 = A * (1.0 - F);
+
+/// The mean radius of the ellipsoid, used for haversine estimates.
+const MEAN_RADIUS: f64 = (2.0 * A + B) / 3.0;
+
+/// Returns the geodesic distance in meters between two points.
+///
+/// Vincenty's formula may fail to converge for nearly antipodal points, in
+/// which case the great-circle distance on the mean sphere is returned
+/// instead, which is within about half a percent.
+pub fn distance_m(a: tg_point, b: tg_point) -> f64 {
+    vincenty(a, b).unwrap_or_else(|| haversine(a, b))
+}
+
+impl tg_line {
+    /// Returns the geodesic length of the line in meters.
+    pub fn length_m(&self) -> f64 {
+        path_length(self.points())
+    }
+}
+
+impl tg_ring {
+    /// Returns the area enclosed by the ring in square meters.
+    ///
+    /// Edges are taken as great circles on the authalic sphere rather than
+    /// as geodesics on the ellipsoid. Against GeographicLib's geodesic
+    /// areas, that's within 1e-5 of the area for edges of a degree or so,
+    /// and within 1e-4 for edges of ten degrees. Edges cross the
+    /// antimeridian when that's the shorter way round; rings that enclose a
+    /// pole aren't supported.
+    pub fn area_m2(&self) -> f64 {
+        ring_area(self.points())
+    }
+
+    /// Returns the geodesic length of the ring in meters.
+    pub fn perimeter_m(&self) -> f64 {
+        path_length(self.points())
+    }
+}
+
+impl tg_poly {
+    /// Returns the area of the exterior less the area of the holes, in
+    /// square meters. See [`tg_ring::area_m2()`].
+    pub fn area_m2(&self) -> f64 {
+        self.exterior().area_m2() - self.holes().map(tg_ring::area_m2).sum::<f64>()
+    }
+
+    /// Returns the geodesic length of the boundary in meters.
+    pub fn perimeter_m(&self) -> f64 {
+        self.exterior().perimeter_m() + self.holes().map(tg_ring::perimeter_m).sum::<f64>()
+    }
+}
+
+impl tg_geom {
+    /// Returns the total area of the polygons in the geometry in square
+    /// meters, like [`area()`][tg_geom::area].
+    pub fn area_m2(&self) -> f64 {
+        self.sum(tg_poly::area_m2, |_| 0.0)
+    }
+
+    /// Returns the total geodesic length of the lines in the geometry in
+    /// meters, like [`length()`][tg_geom::length].
+    pub fn length_m(&self) -> f64 {
+        self.sum(|_| 0.0, tg_line::length_m)
+    }
+
+    /// Returns the total geodesic perimeter of the polygons in the geometry
+    /// in meters, like [`perimeter()`][tg_geom::perimeter].
+    pub fn perimeter_m(&self) -> f64 {
+        self.sum(tg_poly::perimeter_m, |_| 0.0)
+    }
+
+    /// Returns the distance in meters from `point` to the nearest point of
+    /// the geometry, or zero if the point is on or inside it.
+    ///
+    /// The nearest point of each segment is found in a local equirectangular
+    /// projection around `point` and the candidates are compared with the
+    /// haversine formula. Only the distance to the winner is measured with
+    /// Vincenty's formula, so the result is geodesic even though the choice
+    /// of nearest point is approximate. Returns infinity for an empty
+    /// geometry.
+    pub fn distance_m(&self, point: tg_point) -> f64 {
+        if self.intersects_xy(point.x, point.y) {
+            return 0.0;
+        }
+        let mut nearest = Nearest {
+            origin: point,
+            scale: point.y.to_radians().cos(),
+            best: None,
+        };
+        nearest.geom(self);
+        match nearest.best {
+            Some((p, _)) => distance_m(point, p),
+            None => f64::INFINITY,
+        }
+    }
+}
+
+/// Finds the vertex or point along a segment closest to `origin`.
+struct Nearest {
+    origin: tg_point,
+    /// Shrinks longitudes to match latitudes near `origin`.
+    scale: f64,
+    best: Option<(tg_point, f64)>,
+}
+
+impl Nearest {
+    fn geom(&mut self, geom: &tg_geom) {
+        match geom.geom_type() {
+            _ if geom.is_empty() => {}
+            tg_geom_type::TG_POINT => self.candidate(geom.point()),
+            tg_geom_type::TG_MULTIPOINT => {
+                for p in geom.points() {
+                    self.candidate(p);
+                }
+            }
+            tg_geom_type::TG_LINESTRING | tg_geom_type::TG_MULTILINESTRING => {
+                let lines: Vec<&tg_line> = match geom.line() {
+                    Some(line) => vec![line],
+                    None => geom.lines().collect(),
+                };
+                for line in lines {
+                    self.path(line.points());
+                }
+            }
+            tg_geom_type::TG_POLYGON | tg_geom_type::TG_MULTIPOLYGON => {
+                let polys: Vec<&tg_poly> = match geom.poly() {
+                    Some(poly) => vec![poly],
+                    None => geom.polys().collect(),
+                };
+                for poly in polys {
+                    self.path(poly.exterior().points());
+                    for hole in poly.holes() {
+                        self.path(hole.points());
+                    }
+                }
+            }
+            tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                for member in geom.geometries() {
+                    self.geom(member);
+                }
+            }
+        }
+    }
+
+    fn candidate(&mut self, p: tg_point) {
+        let d = haversine(self.origin, p);
+        match self.best {
+            Some((_, best)) if best <= d => {}
+            _ => self.best = Some((p, d)),
+        }
+    }
+
+    fn path(&mut self, points: &[tg_point]) {
+        if let [p] = points {
+            self.candidate(*p);
+        }
+        for pair in points.windows(2) {
+            let (a, b) = (self.project(pair[0]), self.project(pair[1]));
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            let len2 = dx * dx + dy * dy;
+            let t = if len2 > 0.0 {
+                (-(a.x * dx + a.y * dy) / len2).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            self.candidate(self.unproject(tg_point {
+                x: a.x + t * dx,
+                y: a.y + t * dy,
+            }));
+        }
+    }
+
+    /// Projects a point to planar offsets from `origin`, in degrees of
+    /// latitude.
+    fn project(&self, p: tg_point) -> tg_point {
+        tg_point {
+            x: wrap_degrees(p.x - self.origin.x) * self.scale,
+            y: p.y - self.origin.y,
+        }
+    }
+
+    fn unproject(&self, p: tg_point) -> tg_point {
+        let x = if self.scale > 0.0 {
+            p.x / self.scale
+        } else {
+            0.0
+        };
+        tg_point {
+            x: self.origin.x + x,
+            y: self.origin.y + p.y,
+        }
+    }
+}
+
+/// Wraps a longitude difference into [-180, 180].
+fn wrap_degrees(d: f64) -> f64 {
+    (d + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// Wraps a longitude difference into [-π, π].
+fn wrap_radians(d: f64) -> f64 {
+    (d + PI).rem_euclid(2.0 * PI) - PI
+}
+
+fn path_length(points: &[tg_point]) -> f64 {
+    points
+        .windows(2)
+        .map(|pair| distance_m(pair[0], pair[1]))
+        .sum()
+}
+
+/// Returns the great-circle distance on the mean sphere.
+fn haversine(a: tg_point, b: tg_point) -> f64 {
+    let (lat1, lat2) = (a.y.to_radians(), b.y.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (b.x - a.x).to_radians();
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * MEAN_RADIUS * h.sqrt().min(1.0).asin()
+}
+
+/// Vincenty's inverse formula, or `None` if it fails to converge.
+fn vincenty(a: tg_point, b: tg_point) -> Option<f64> {
+    let l = wrap_radians((b.x - a.x).to_radians());
+    let u1 = ((1.0 - F) * a.y.to_radians().tan()).atan();
+    let u2 = ((1.0 - F) * b.y.to_radians().tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    for _ in 0..200 {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            // The points coincide.
+            return Some(0.0);
+        }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos2_alpha = 1.0 - sin_alpha * sin_alpha;
+        // On the equator cos²α is zero and so is the term it divides.
+        let cos_2sigma_m = if cos2_alpha != 0.0 {
+            cos_sigma - 2.0 * sin_u1 * sin_u2 / cos2_alpha
+        } else {
+            0.0
+        };
+        let c = F / 16.0 * cos2_alpha * (4.0 + F * (4.0 - 3.0 * cos2_alpha));
+        let previous = lambda;
+        lambda = l
+            + (1.0 - c)
+                * F
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+        if (lambda - previous).abs() < 1e-12 {
+            let u2 = cos2_alpha * (A * A - B * B) / (B * B);
+            let big_a = 1.0 + u2 / 16384.0 * (4096.0 + u2 * (-768.0 + u2 * (320.0 - 175.0 * u2)));
+            let big_b = u2 / 1024.0 * (256.0 + u2 * (-128.0 + u2 * (74.0 - 47.0 * u2)));
+            let delta_sigma = big_b
+                * sin_sigma
+                * (cos_2sigma_m
+                    + big_b / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                            - big_b / 6.0
+                                * cos_2sigma_m
+                                * (-3.0 + 4.0 * sin_sigma.powi(2))
+                                * (-3.0 + 4.0 * cos_2sigma_m.powi(2))));
+            return Some(B * big_a * (sigma - delta_sigma));
+        }
+    }
+    None
+}
+
+/// The squared eccentricity of the ellipsoid.
+const E2: f64 = F * (2.0 - F);
+
+/// Returns the authalic `q` function of a latitude, in radians.
+fn q(lat: f64) -> f64 {
+    let e = E2.sqrt();
+    let s = lat.sin();
+    (1.0 - E2) * (s / (1.0 - E2 * s * s) - (1.0 / (2.0 * e)) * ((1.0 - e * s) / (1.0 + e * s)).ln())
+}
+
+/// Sums the signed spherical excess of the quadrilateral between each edge
+/// and the equator, on the authalic sphere.
+fn ring_area(points: &[tg_point]) -> f64 {
+    let qp = q(PI / 2.0);
+    let radius2 = A * A * qp / 2.0;
+    // The sine of the authalic latitude is q(φ) / q(90°).
+    let tan_half_beta = |p: &tg_point| {
+        let beta = (q(p.y.to_radians()) / qp).clamp(-1.0, 1.0).asin();
+        (beta / 2.0).tan()
+    };
+    let mut sum = 0.0;
+    for pair in points.windows(2) {
+        let dlon = wrap_radians((pair[1].x - pair[0].x).to_radians());
+        let (t1, t2) = (tan_half_beta(&pair[0]), tan_half_beta(&pair[1]));
+        sum += 2.0 * ((dlon / 2.0).tan() * (t1 + t2)).atan2(1.0 + t1 * t2);
+    }
+    (sum * radius2).abs()
+}
@@ -0,0 +1,355 @@
+//! Streaming GeoJSON input, and the errors shared by all streaming readers.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+
+use crate::GeometryParsing::tg_parse_geojsonn_ix;
+use crate::{tg_index, Error, Geom};
+
+/// An error from reading a stream of geometries.
+#[derive(Debug)]
+pub enum ReadError {
+    /// The underlying reader failed.
+    Io(io::Error),
+    /// The input is malformed, such as a GeoJSON document that isn't a
+    /// FeatureCollection. `offset` is the byte offset of the problem from the
+    /// start of the input.
+    Syntax { offset: u64, message: String },
+    /// tg rejected a single feature. Reading can continue with the next one.
+    Feature { index: usize, error: Error },
+    /// tg rejected the record starting on `line`, counting from one. Reading
+    /// can continue with the next record.
+    Record { line: usize, error: Error },
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(err) => write!(f, "{}", err),
+            ReadError::Syntax { offset, message } => write!(f, "byte {}: {}", offset, message),
+            ReadError::Feature { index, error } => write!(f, "feature {}: {}", index, error),
+            ReadError::Record { line, error } => write!(f, "line {}: {}", line, error),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(err) => Some(err),
+            ReadError::Syntax { .. } => None,
+            ReadError::Feature { error, .. } | ReadError::Record { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(err: io::Error) -> ReadError {
+        ReadError::Io(err)
+    }
+}
+
+/// A single feature read from a FeatureCollection.
+#[derive(Clone, Debug)]
+pub struct Feature {
+    index: usize,
+    geom: Geom,
+}
+
+impl Feature {
+    /// The position of the feature within the collection's `features` array.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The feature's geometry. Its `id` and `properties` are available from
+    /// [`extra_json()`][crate::tg_geom::extra_json].
+    pub fn geom(&self) -> &Geom {
+        &self.geom
+    }
+
+    pub fn into_geom(self) -> Geom {
+        self.geom
+    }
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Start,
+    Members { first: bool },
+    Features { first: bool },
+    Done,
+}
+
+/// Reads the features of a GeoJSON FeatureCollection one at a time.
+///
+/// [`tg_parse_geojsonn()`][crate::GeometryParsing::tg_parse_geojsonn] needs
+/// the whole document in memory and builds the entire collection as one
+/// geometry. This reader instead scans the `features` array of the top-level
+/// object and hands each feature to tg separately, so memory use is bounded
+/// by the largest single feature rather than the size of the document.
+///
+/// The top-level object must have a `"type"` of `"FeatureCollection"`;
+/// anything else, including a bare Feature or geometry, is a
+/// [`ReadError::Syntax`]. Since members may come in any order, features
+/// that precede a wrong or missing `"type"` are yielded before the error.
+/// Other members of the top-level object are skipped. Each feature is yielded
+/// as it's parsed; a feature that tg rejects is reported as
+/// [`ReadError::Feature`] and reading continues with the next one. I/O and
+/// syntax errors end the iteration.
+///
+/// ```no_run
+/// # use std::fs::File;
+/// # use tg_sys::FeatureReader;
+/// for feature in FeatureReader::new(File::open("counties.geojson")?) {
+///     match feature {
+///         Ok(feature) => println!("{}", feature.geom().to_wkt()),
+///         Err(err) => eprintln!("skipping: {}", err),
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct FeatureReader<R> {
+    reader: BufReader<R>,
+    ix: tg_index,
+    state: State,
+    offset: u64,
+    index: usize,
+    typed: bool,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> FeatureReader<R> {
+    pub fn new(reader: R) -> FeatureReader<R> {
+        FeatureReader::with_index(reader, tg_index::TG_DEFAULT)
+    }
+
+    /// Creates a reader that indexes each feature with the provided indexing
+    /// option.
+    pub fn with_index(reader: R, ix: tg_index) -> FeatureReader<R> {
+        FeatureReader {
+            reader: BufReader::new(reader),
+            ix,
+            state: State::Start,
+            offset: 0,
+            index: 0,
+            typed: false,
+            buf: Vec::new(),
+        }
+    }
+
+    fn syntax(&self, message: impl Into<String>) -> ReadError {
+        ReadError::Syntax {
+            offset: self.offset,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, ReadError> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    /// Consumes and returns the next byte, failing at the end of input.
+    fn bump(&mut self) -> Result<u8, ReadError> {
+        let b = self
+            .peek()?
+            .ok_or_else(|| self.syntax("unexpected end of input"))?;
+        self.reader.consume(1);
+        self.offset += 1;
+        Ok(b)
+    }
+
+    /// Consumes the next byte, appending it to `out` if provided.
+    fn bump_into(&mut self, out: &mut Option<&mut Vec<u8>>) -> Result<u8, ReadError> {
+        let b = self.bump()?;
+        if let Some(out) = out {
+            out.push(b);
+        }
+        Ok(b)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), ReadError> {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek()? {
+            self.bump()?;
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), ReadError> {
+        match self.peek()? {
+            Some(b) if b == expected => {
+                self.bump()?;
+                Ok(())
+            }
+            Some(b) => Err(self.syntax(format!(
+                "expected '{}', found '{}'",
+                expected as char, b as char
+            ))),
+            None => Err(self.syntax("unexpected end of input")),
+        }
+    }
+
+    /// Reads the rest of a string whose opening quote has been consumed,
+    /// leaving escape sequences as they are.
+    fn scan_string(&mut self, out: &mut Option<&mut Vec<u8>>) -> Result<(), ReadError> {
+        loop {
+            match self.bump_into(out)? {
+                b'"' => return Ok(()),
+                b'\\' => {
+                    self.bump_into(out)?;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads one JSON value, appending its bytes to `out` if provided.
+    ///
+    /// Only the nesting of objects and arrays is tracked; the contents of a
+    /// captured value are validated by tg when the feature is parsed.
+    fn scan_value(&mut self, mut out: Option<&mut Vec<u8>>) -> Result<(), ReadError> {
+        match self.peek()? {
+            Some(b'{' | b'[') => {
+                let mut depth = 0usize;
+                loop {
+                    match self.bump_into(&mut out)? {
+                        b'"' => self.scan_string(&mut out)?,
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                return Ok(());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Some(b'"') => {
+                self.bump_into(&mut out)?;
+                self.scan_string(&mut out)
+            }
+            Some(_) => {
+                let start = self.offset;
+                while let Some(b) = self.peek()? {
+                    if matches!(b, b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r') {
+                        break;
+                    }
+                    self.bump_into(&mut out)?;
+                }
+                if self.offset == start {
+                    return Err(self.syntax("expected a value"));
+                }
+                Ok(())
+            }
+            None => Err(self.syntax("unexpected end of input")),
+        }
+    }
+
+    /// Advances to the next feature, leaving its bytes in `buf`. Returns false
+    /// once the collection has been read.
+    fn next_feature(&mut self) -> Result<bool, ReadError> {
+        loop {
+            match self.state {
+                State::Start => {
+                    self.skip_whitespace()?;
+                    self.expect(b'{')?;
+                    self.state = State::Members { first: true };
+                }
+                State::Members { first } => {
+                    self.skip_whitespace()?;
+                    if self.peek()? == Some(b'}') {
+                        if !self.typed {
+                            return Err(self.syntax("missing \"type\" member"));
+                        }
+                        self.bump()?;
+                        self.state = State::Done;
+                        continue;
+                    }
+                    if !first {
+                        self.expect(b',')?;
+                        self.skip_whitespace()?;
+                    }
+                    let mut key = Vec::new();
+                    self.expect(b'"')?;
+                    self.scan_string(&mut Some(&mut key))?;
+                    key.pop(); // the closing quote
+                    self.skip_whitespace()?;
+                    self.expect(b':')?;
+                    self.skip_whitespace()?;
+                    if key == b"features" {
+                        self.expect(b'[')?;
+                        self.state = State::Features { first: true };
+                    } else if key == b"type" {
+                        let start = self.offset;
+                        let mut kind = Vec::new();
+                        self.scan_value(Some(&mut kind))?;
+                        if kind != b"\"FeatureCollection\"" {
+                            return Err(ReadError::Syntax {
+                                offset: start,
+                                message: format!(
+                                    "expected a FeatureCollection, found type {}",
+                                    String::from_utf8_lossy(&kind)
+                                ),
+                            });
+                        }
+                        self.typed = true;
+                        self.state = State::Members { first: false };
+                    } else {
+                        self.scan_value(None)?;
+                        self.state = State::Members { first: false };
+                    }
+                }
+                State::Features { first } => {
+                    self.skip_whitespace()?;
+                    if self.peek()? == Some(b']') {
+                        self.bump()?;
+                        self.state = State::Members { first: false };
+                        continue;
+                    }
+                    if !first {
+                        self.expect(b',')?;
+                        self.skip_whitespace()?;
+                    }
+                    let mut buf = std::mem::take(&mut self.buf);
+                    buf.clear();
+                    let scanned = self.scan_value(Some(&mut buf));
+                    self.buf = buf;
+                    scanned?;
+                    self.state = State::Features { first: false };
+                    return Ok(true);
+                }
+                State::Done => return Ok(false),
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for FeatureReader<R> {
+    type Item = Result<Feature, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_feature() {
+            Ok(true) => {
+                let index = self.index;
+                self.index += 1;
+                let geom = unsafe {
+                    Geom::from_raw(tg_parse_geojsonn_ix(
+                        self.buf.as_ptr().cast(),
+                        self.buf.len(),
+                        self.ix,
+                    ))
+                };
+                Some(match geom {
+                    Ok(geom) => Ok(Feature { index, geom }),
+                    Err(error) => Err(ReadError::Feature { index, error }),
+                })
+            }
+            Ok(false) => None,
+            Err(err) => {
+                self.state = State::Done;
+                Some(Err(err))
+            }
+        }
+    }
+}
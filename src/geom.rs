@@ -0,0 +1,735 @@
+//! An owned, safe handle to a [`tg_geom`].
+
+use std::borrow::Borrow;
+use std::ffi::CStr;
+use std::fmt;
+use std::ops::Deref;
+use std::ptr::NonNull;
+
+use crate::shape::{alloc, c_int, slice};
+use crate::GeometryAccessors::{
+    tg_geom_extra_coords, tg_geom_extra_json, tg_geom_geometry_at, tg_geom_has_m, tg_geom_has_z,
+    tg_geom_is_empty, tg_geom_is_feature, tg_geom_line, tg_geom_line_at, tg_geom_m,
+    tg_geom_memsize, tg_geom_num_extra_coords, tg_geom_num_geometries, tg_geom_num_lines,
+    tg_geom_num_points, tg_geom_num_polys, tg_geom_point, tg_geom_point_at, tg_geom_poly,
+    tg_geom_poly_at, tg_geom_rect, tg_geom_typeof, tg_geom_z,
+};
+use crate::GeometryConstructors::{
+    tg_geom_clone, tg_geom_free, tg_geom_new_geometrycollection, tg_geom_new_linestring,
+    tg_geom_new_multilinestring, tg_geom_new_multipoint, tg_geom_new_multipolygon,
+    tg_geom_new_point, tg_geom_new_polygon,
+};
+use crate::GeometryConstructorsEx::{
+    tg_geom_new_geometrycollection_empty, tg_geom_new_linestring_empty, tg_geom_new_linestring_m,
+    tg_geom_new_linestring_z, tg_geom_new_linestring_zm, tg_geom_new_multilinestring_empty,
+    tg_geom_new_multilinestring_m, tg_geom_new_multilinestring_z, tg_geom_new_multilinestring_zm,
+    tg_geom_new_multipoint_empty, tg_geom_new_multipoint_m, tg_geom_new_multipoint_z,
+    tg_geom_new_multipoint_zm, tg_geom_new_multipolygon_empty, tg_geom_new_multipolygon_m,
+    tg_geom_new_multipolygon_z, tg_geom_new_multipolygon_zm, tg_geom_new_point_empty,
+    tg_geom_new_point_m, tg_geom_new_point_z, tg_geom_new_point_zm, tg_geom_new_polygon_empty,
+    tg_geom_new_polygon_m, tg_geom_new_polygon_z, tg_geom_new_polygon_zm,
+};
+use crate::GeometryParsing::{
+    tg_geom_error, tg_parse_geojsonn_ix, tg_parse_hexn_ix, tg_parse_wkb_ix, tg_parse_wktn_ix,
+};
+use crate::GeometryPredicates::{
+    tg_geom_contains, tg_geom_coveredby, tg_geom_covers, tg_geom_disjoint, tg_geom_equals,
+    tg_geom_intersects, tg_geom_intersects_rect, tg_geom_intersects_xy, tg_geom_within,
+};
+use crate::GeometryWriting::{tg_geom_geojson, tg_geom_hex, tg_geom_wkb, tg_geom_wkt};
+use crate::{tg_geom, tg_geom_type, tg_index, tg_line, tg_point, tg_poly, tg_rect};
+
+/// An error reported by tg, usually while parsing a geometry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+}
+
+impl Error {
+    pub(crate) fn new(message: impl Into<String>) -> Error {
+        Error {
+            message: message.into(),
+        }
+    }
+
+    /// The error message, as reported by [`tg_geom_error()`][crate::GeometryParsing::tg_geom_error].
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A spatial relationship between two geometries, evaluated by the matching
+/// [`GeometryPredicates`][crate::GeometryPredicates] function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Predicate {
+    Equals,
+    Intersects,
+    Disjoint,
+    Contains,
+    Within,
+    Covers,
+    CoveredBy,
+}
+
+impl Predicate {
+    /// Tests whether `a` and `b` satisfy the relationship.
+    pub fn eval(self, a: &tg_geom, b: &tg_geom) -> bool {
+        match self {
+            Predicate::Equals => a.equals(b),
+            Predicate::Intersects => a.intersects(b),
+            Predicate::Disjoint => a.disjoint(b),
+            Predicate::Contains => a.contains(b),
+            Predicate::Within => a.within(b),
+            Predicate::Covers => a.covers(b),
+            Predicate::CoveredBy => a.coveredby(b),
+        }
+    }
+
+    /// Whether the relationship can only hold when the bounding rectangles of
+    /// both geometries intersect. This is true of every predicate except
+    /// [`Predicate::Disjoint`], and lets spatial indexes skip most pairs.
+    pub fn requires_intersecting_rects(self) -> bool {
+        self != Predicate::Disjoint
+    }
+}
+
+/// The coordinates stored for each point of a geometry.
+///
+/// tg keeps X and Y in each [`tg_point`] and any Z and M coordinates in a
+/// separate array, interleaved Z before M for each point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dims {
+    Xy,
+    Xyz,
+    Xym,
+    Xyzm,
+}
+
+impl Dims {
+    pub fn new(has_z: bool, has_m: bool) -> Dims {
+        match (has_z, has_m) {
+            (false, false) => Dims::Xy,
+            (true, false) => Dims::Xyz,
+            (false, true) => Dims::Xym,
+            (true, true) => Dims::Xyzm,
+        }
+    }
+
+    pub fn has_z(self) -> bool {
+        matches!(self, Dims::Xyz | Dims::Xyzm)
+    }
+
+    pub fn has_m(self) -> bool {
+        matches!(self, Dims::Xym | Dims::Xyzm)
+    }
+
+    /// Returns the number of coordinates stored per point beyond X and Y.
+    pub fn extra(self) -> usize {
+        usize::from(self.has_z()) + usize::from(self.has_m())
+    }
+}
+
+/// An owned geometry.
+///
+/// Dereferences to [`tg_geom`], which carries the safe accessors and
+/// predicates, so a `&Geom` can be passed wherever a `&tg_geom` is expected.
+///
+/// Cloning is cheap: it uses [`tg_geom_clone()`][tg_geom_clone], which shares
+/// the underlying geometry through a reference counter. The geometry is freed
+/// with [`tg_geom_free()`][tg_geom_free] when the last handle is dropped.
+///
+/// tg has no notion of spatial reference systems, so a geometry read from
+/// EWKT or EWKB carries its SRID alongside the tg geometry, in the handle.
+pub struct Geom {
+    ptr: NonNull<tg_geom>,
+    srid: Option<i32>,
+}
+
+// With the `atomics` feature the reference counter shared between clones is
+// atomic, and tg never mutates a geometry after construction.
+#[cfg(feature = "atomics")]
+unsafe impl Send for Geom {}
+#[cfg(feature = "atomics")]
+unsafe impl Sync for Geom {}
+
+impl Geom {
+    /// Takes ownership of a geometry returned by one of the tg constructors or
+    /// parsers.
+    ///
+    /// Returns an error if `ptr` is NULL, which tg uses to signal that the
+    /// system is out of memory, or if the geometry carries a parse error. In
+    /// the latter case the geometry is freed.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be NULL or a geometry allocated by tg that the caller owns.
+    pub unsafe fn from_raw(ptr: *mut tg_geom) -> Result<Geom, Error> {
+        let ptr = NonNull::new(ptr).ok_or_else(|| Error::new("out of memory"))?;
+        let geom = Geom { ptr, srid: None };
+        let err = tg_geom_error(geom.as_ptr());
+        if !err.is_null() {
+            return Err(Error::new(CStr::from_ptr(err).to_string_lossy()));
+        }
+        Ok(geom)
+    }
+
+    /// Returns the underlying geometry pointer, which stays owned by `self`.
+    pub fn as_ptr(&self) -> *const tg_geom {
+        self.ptr.as_ptr()
+    }
+
+    /// Returns the spatial reference system identifier, if the geometry was
+    /// given one.
+    pub fn srid(&self) -> Option<i32> {
+        self.srid
+    }
+
+    /// Sets the spatial reference system identifier.
+    pub fn with_srid(mut self, srid: Option<i32>) -> Geom {
+        self.srid = srid;
+        self
+    }
+
+    /// Releases ownership of the underlying geometry, discarding any SRID.
+    ///
+    /// The caller is responsible for freeing it with [`tg_geom_free()`][tg_geom_free].
+    pub fn into_raw(self) -> *mut tg_geom {
+        let ptr = self.ptr.as_ptr();
+        std::mem::forget(self);
+        ptr
+    }
+
+    /// Parses Well-known text (WKT).
+    pub fn parse_wkt(wkt: &str) -> Result<Geom, Error> {
+        Geom::parse_wkt_ix(wkt, tg_index::TG_DEFAULT)
+    }
+
+    /// Parses Well-known text (WKT) using the provided indexing option.
+    pub fn parse_wkt_ix(wkt: &str, ix: tg_index) -> Result<Geom, Error> {
+        unsafe { Geom::from_raw(tg_parse_wktn_ix(wkt.as_ptr().cast(), wkt.len(), ix)) }
+    }
+
+    /// Parses GeoJSON.
+    pub fn parse_geojson(geojson: &str) -> Result<Geom, Error> {
+        Geom::parse_geojson_ix(geojson, tg_index::TG_DEFAULT)
+    }
+
+    /// Parses GeoJSON using the provided indexing option.
+    pub fn parse_geojson_ix(geojson: &str, ix: tg_index) -> Result<Geom, Error> {
+        unsafe {
+            Geom::from_raw(tg_parse_geojsonn_ix(
+                geojson.as_ptr().cast(),
+                geojson.len(),
+                ix,
+            ))
+        }
+    }
+
+    /// Parses Well-known binary (WKB).
+    pub fn parse_wkb(wkb: &[u8]) -> Result<Geom, Error> {
+        Geom::parse_wkb_ix(wkb, tg_index::TG_DEFAULT)
+    }
+
+    /// Parses Well-known binary (WKB) using the provided indexing option.
+    pub fn parse_wkb_ix(wkb: &[u8], ix: tg_index) -> Result<Geom, Error> {
+        unsafe { Geom::from_raw(tg_parse_wkb_ix(wkb.as_ptr(), wkb.len(), ix)) }
+    }
+
+    /// Parses hex encoded Well-known binary (WKB).
+    pub fn parse_hex(hex: &str) -> Result<Geom, Error> {
+        Geom::parse_hex_ix(hex, tg_index::TG_DEFAULT)
+    }
+
+    /// Parses hex encoded Well-known binary (WKB) using the provided indexing
+    /// option.
+    pub fn parse_hex_ix(hex: &str, ix: tg_index) -> Result<Geom, Error> {
+        unsafe { Geom::from_raw(tg_parse_hexn_ix(hex.as_ptr().cast(), hex.len(), ix)) }
+    }
+    fn new(ptr: *mut tg_geom) -> Geom {
+        Geom {
+            ptr: alloc(ptr),
+            srid: None,
+        }
+    }
+
+    /// Creates a Point geometry.
+    pub fn new_point(point: tg_point) -> Geom {
+        Geom::new(unsafe { tg_geom_new_point(point) })
+    }
+
+    /// Creates a Point geometry with the extra coordinates given by `dims`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `extra` holds fewer than [`dims.extra()`][Dims::extra]
+    /// coordinates.
+    pub fn new_point_ex(point: tg_point, dims: Dims, extra: &[f64]) -> Geom {
+        Geom::new(unsafe {
+            match dims {
+                Dims::Xy => tg_geom_new_point(point),
+                Dims::Xyz => tg_geom_new_point_z(point, extra[0]),
+                Dims::Xym => tg_geom_new_point_m(point, extra[0]),
+                Dims::Xyzm => tg_geom_new_point_zm(point, extra[0], extra[1]),
+            }
+        })
+    }
+
+    /// Creates a LineString geometry.
+    pub fn new_linestring(line: &tg_line) -> Geom {
+        Geom::new(unsafe { tg_geom_new_linestring(line) })
+    }
+
+    /// Creates a LineString geometry with extra coordinates, laid out point
+    /// by point as described by `dims`.
+    pub fn new_linestring_ex(line: &tg_line, dims: Dims, extra: &[f64]) -> Geom {
+        let (coords, n) = (extra.as_ptr(), c_int(extra.len()));
+        Geom::new(unsafe {
+            match dims {
+                Dims::Xy => tg_geom_new_linestring(line),
+                Dims::Xyz => tg_geom_new_linestring_z(line, coords, n),
+                Dims::Xym => tg_geom_new_linestring_m(line, coords, n),
+                Dims::Xyzm => tg_geom_new_linestring_zm(line, coords, n),
+            }
+        })
+    }
+
+    /// Creates a Polygon geometry.
+    pub fn new_polygon(poly: &tg_poly) -> Geom {
+        Geom::new(unsafe { tg_geom_new_polygon(poly) })
+    }
+
+    /// Creates a Polygon geometry with extra coordinates for the points of
+    /// the exterior followed by those of each hole.
+    pub fn new_polygon_ex(poly: &tg_poly, dims: Dims, extra: &[f64]) -> Geom {
+        let (coords, n) = (extra.as_ptr(), c_int(extra.len()));
+        Geom::new(unsafe {
+            match dims {
+                Dims::Xy => tg_geom_new_polygon(poly),
+                Dims::Xyz => tg_geom_new_polygon_z(poly, coords, n),
+                Dims::Xym => tg_geom_new_polygon_m(poly, coords, n),
+                Dims::Xyzm => tg_geom_new_polygon_zm(poly, coords, n),
+            }
+        })
+    }
+
+    /// Creates a MultiPoint geometry.
+    pub fn new_multipoint(points: &[tg_point]) -> Geom {
+        Geom::new(unsafe { tg_geom_new_multipoint(points.as_ptr(), c_int(points.len())) })
+    }
+
+    /// Creates a MultiPoint geometry with extra coordinates.
+    pub fn new_multipoint_ex(points: &[tg_point], dims: Dims, extra: &[f64]) -> Geom {
+        let (ptr, len) = (points.as_ptr(), c_int(points.len()));
+        let (coords, n) = (extra.as_ptr(), c_int(extra.len()));
+        Geom::new(unsafe {
+            match dims {
+                Dims::Xy => tg_geom_new_multipoint(ptr, len),
+                Dims::Xyz => tg_geom_new_multipoint_z(ptr, len, coords, n),
+                Dims::Xym => tg_geom_new_multipoint_m(ptr, len, coords, n),
+                Dims::Xyzm => tg_geom_new_multipoint_zm(ptr, len, coords, n),
+            }
+        })
+    }
+
+    /// Creates a MultiLineString geometry.
+    pub fn new_multilinestring(lines: &[&tg_line]) -> Geom {
+        Geom::new_multilinestring_ex(lines, Dims::Xy, &[])
+    }
+
+    /// Creates a MultiLineString geometry with extra coordinates for the
+    /// points of each line in turn.
+    pub fn new_multilinestring_ex(lines: &[&tg_line], dims: Dims, extra: &[f64]) -> Geom {
+        let lines: Vec<*const tg_line> = lines.iter().map(|l| *l as *const tg_line).collect();
+        let (ptr, len) = (lines.as_ptr(), c_int(lines.len()));
+        let (coords, n) = (extra.as_ptr(), c_int(extra.len()));
+        Geom::new(unsafe {
+            match dims {
+                Dims::Xy => tg_geom_new_multilinestring(ptr, len),
+                Dims::Xyz => tg_geom_new_multilinestring_z(ptr, len, coords, n),
+                Dims::Xym => tg_geom_new_multilinestring_m(ptr, len, coords, n),
+                Dims::Xyzm => tg_geom_new_multilinestring_zm(ptr, len, coords, n),
+            }
+        })
+    }
+
+    /// Creates a MultiPolygon geometry.
+    pub fn new_multipolygon(polys: &[&tg_poly]) -> Geom {
+        Geom::new_multipolygon_ex(polys, Dims::Xy, &[])
+    }
+
+    /// Creates a MultiPolygon geometry with extra coordinates for the points
+    /// of each polygon in turn.
+    pub fn new_multipolygon_ex(polys: &[&tg_poly], dims: Dims, extra: &[f64]) -> Geom {
+        let polys: Vec<*const tg_poly> = polys.iter().map(|p| *p as *const tg_poly).collect();
+        let (ptr, len) = (polys.as_ptr(), c_int(polys.len()));
+        let (coords, n) = (extra.as_ptr(), c_int(extra.len()));
+        Geom::new(unsafe {
+            match dims {
+                Dims::Xy => tg_geom_new_multipolygon(ptr, len),
+                Dims::Xyz => tg_geom_new_multipolygon_z(ptr, len, coords, n),
+                Dims::Xym => tg_geom_new_multipolygon_m(ptr, len, coords, n),
+                Dims::Xyzm => tg_geom_new_multipolygon_zm(ptr, len, coords, n),
+            }
+        })
+    }
+
+    /// Creates a GeometryCollection geometry.
+    pub fn new_geometrycollection(geoms: &[&tg_geom]) -> Geom {
+        let geoms: Vec<*const tg_geom> = geoms.iter().map(|g| *g as *const tg_geom).collect();
+        Geom::new(unsafe { tg_geom_new_geometrycollection(geoms.as_ptr(), c_int(geoms.len())) })
+    }
+
+    /// Creates an empty geometry of the given type.
+    pub fn new_empty(geom_type: tg_geom_type) -> Geom {
+        Geom::new(unsafe {
+            match geom_type {
+                tg_geom_type::TG_POINT => tg_geom_new_point_empty(),
+                tg_geom_type::TG_LINESTRING => tg_geom_new_linestring_empty(),
+                tg_geom_type::TG_POLYGON => tg_geom_new_polygon_empty(),
+                tg_geom_type::TG_MULTIPOINT => tg_geom_new_multipoint_empty(),
+                tg_geom_type::TG_MULTILINESTRING => tg_geom_new_multilinestring_empty(),
+                tg_geom_type::TG_MULTIPOLYGON => tg_geom_new_multipolygon_empty(),
+                tg_geom_type::TG_GEOMETRYCOLLECTION => tg_geom_new_geometrycollection_empty(),
+            }
+        })
+    }
+}
+
+impl Clone for Geom {
+    fn clone(&self) -> Geom {
+        let ptr = unsafe { tg_geom_clone(self.as_ptr()) };
+        Geom {
+            ptr: alloc(ptr),
+            srid: self.srid,
+        }
+    }
+}
+
+impl Drop for Geom {
+    fn drop(&mut self) {
+        unsafe { tg_geom_free(self.ptr.as_ptr()) }
+    }
+}
+
+impl Deref for Geom {
+    type Target = tg_geom;
+
+    fn deref(&self) -> &tg_geom {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl AsRef<tg_geom> for Geom {
+    fn as_ref(&self) -> &tg_geom {
+        self
+    }
+}
+
+impl Borrow<tg_geom> for Geom {
+    fn borrow(&self) -> &tg_geom {
+        self
+    }
+}
+
+/// Takes a new reference to a borrowed geometry, as cheaply as
+/// [`Geom::clone()`].
+impl ToOwned for tg_geom {
+    type Owned = Geom;
+
+    fn to_owned(&self) -> Geom {
+        Geom::new(unsafe { tg_geom_clone(self) })
+    }
+}
+
+impl fmt::Debug for Geom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Geom").field(&self.to_wkt()).finish()
+    }
+}
+
+impl tg_geom {
+    /// Returns the geometry type.
+    pub fn geom_type(&self) -> tg_geom_type {
+        unsafe { tg_geom_typeof(self) }
+    }
+
+    /// Returns the minimum bounding rectangle of the geometry.
+    pub fn rect(&self) -> tg_rect {
+        unsafe { tg_geom_rect(self) }
+    }
+
+    /// Returns true if the geometry was parsed from a GeoJSON Feature.
+    pub fn is_feature(&self) -> bool {
+        unsafe { tg_geom_is_feature(self) }
+    }
+
+    /// Returns the members of a GeoJSON object that aren't part of the
+    /// geometry itself, such as a Feature's `id` and `properties`, as a JSON
+    /// object.
+    pub fn extra_json(&self) -> Option<&str> {
+        let json = unsafe { tg_geom_extra_json(self) };
+        if json.is_null() {
+            return None;
+        }
+        unsafe { CStr::from_ptr(json) }.to_str().ok()
+    }
+
+    /// Returns true if the geometry has no points.
+    pub fn is_empty(&self) -> bool {
+        unsafe { tg_geom_is_empty(self) }
+    }
+
+    /// Returns the coordinates stored for each point.
+    pub fn dims(&self) -> Dims {
+        unsafe { Dims::new(tg_geom_has_z(self), tg_geom_has_m(self)) }
+    }
+
+    /// Returns the Z coordinate of a Point, or zero.
+    pub fn z(&self) -> f64 {
+        unsafe { tg_geom_z(self) }
+    }
+
+    /// Returns the M coordinate of a Point, or zero.
+    pub fn m(&self) -> f64 {
+        unsafe { tg_geom_m(self) }
+    }
+
+    /// Returns the Z and M coordinates of a geometry other than a Point,
+    /// laid out point by point as described by [`dims()`][tg_geom::dims].
+    pub fn extra_coords(&self) -> &[f64] {
+        unsafe { slice(tg_geom_extra_coords(self), tg_geom_num_extra_coords(self)) }
+    }
+
+    pub fn memsize(&self) -> usize {
+        unsafe { tg_geom_memsize(self) }
+    }
+
+    /// Returns the point of a Point geometry, or the center of its bounding
+    /// rectangle otherwise.
+    pub fn point(&self) -> tg_point {
+        unsafe { tg_geom_point(self) }
+    }
+
+    /// Returns the line of a LineString geometry.
+    pub fn line(&self) -> Option<&tg_line> {
+        unsafe { tg_geom_line(self).as_ref() }
+    }
+
+    /// Returns the polygon of a Polygon geometry.
+    pub fn poly(&self) -> Option<&tg_poly> {
+        unsafe { tg_geom_poly(self).as_ref() }
+    }
+
+    /// Returns the points of a MultiPoint geometry.
+    pub fn points(&self) -> impl ExactSizeIterator<Item = tg_point> + '_ {
+        let n = unsafe { tg_geom_num_points(self) }.max(0);
+        (0..n).map(move |i| unsafe { tg_geom_point_at(self, i) })
+    }
+
+    /// Returns the lines of a MultiLineString geometry.
+    pub fn lines(&self) -> impl ExactSizeIterator<Item = &tg_line> + '_ {
+        let n = unsafe { tg_geom_num_lines(self) }.max(0);
+        (0..n).map(move |i| unsafe { &*tg_geom_line_at(self, i) })
+    }
+
+    /// Returns the polygons of a MultiPolygon geometry.
+    pub fn polys(&self) -> impl ExactSizeIterator<Item = &tg_poly> + '_ {
+        let n = unsafe { tg_geom_num_polys(self) }.max(0);
+        (0..n).map(move |i| unsafe { &*tg_geom_poly_at(self, i) })
+    }
+
+    /// Returns the members of a GeometryCollection.
+    pub fn geometries(&self) -> impl ExactSizeIterator<Item = &tg_geom> + '_ {
+        let n = unsafe { tg_geom_num_geometries(self) }.max(0);
+        (0..n).map(move |i| unsafe { &*tg_geom_geometry_at(self, i) })
+    }
+
+    /// Returns the total area of the polygons in the geometry, including
+    /// those in collections. Points and lines have no area.
+    pub fn area(&self) -> f64 {
+        self.sum(tg_poly::area, |_| 0.0)
+    }
+
+    /// Returns the total length of the lines in the geometry, including
+    /// those in collections. Polygon boundaries don't count towards the
+    /// length; see [`perimeter()`][tg_geom::perimeter].
+    pub fn length(&self) -> f64 {
+        self.sum(|_| 0.0, tg_line::length)
+    }
+
+    /// Returns the total perimeter of the polygons in the geometry,
+    /// including those in collections. Lines don't count towards the
+    /// perimeter; see [`length()`][tg_geom::length].
+    pub fn perimeter(&self) -> f64 {
+        self.sum(tg_poly::perimeter, |_| 0.0)
+    }
+
+    /// Sums a measure over the polygons and lines of the geometry.
+    pub(crate) fn sum(&self, poly: fn(&tg_poly) -> f64, line: fn(&tg_line) -> f64) -> f64 {
+        match self.geom_type() {
+            _ if self.is_empty() => 0.0,
+            tg_geom_type::TG_POINT | tg_geom_type::TG_MULTIPOINT => 0.0,
+            tg_geom_type::TG_LINESTRING => self.line().map_or(0.0, line),
+            tg_geom_type::TG_MULTILINESTRING => self.lines().map(line).sum(),
+            tg_geom_type::TG_POLYGON => self.poly().map_or(0.0, poly),
+            tg_geom_type::TG_MULTIPOLYGON => self.polys().map(poly).sum(),
+            tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                self.geometries().map(|g| g.sum(poly, line)).sum()
+            }
+        }
+    }
+
+    pub fn equals(&self, other: &tg_geom) -> bool {
+        unsafe { tg_geom_equals(self, other) }
+    }
+
+    pub fn intersects(&self, other: &tg_geom) -> bool {
+        unsafe { tg_geom_intersects(self, other) }
+    }
+
+    pub fn disjoint(&self, other: &tg_geom) -> bool {
+        unsafe { tg_geom_disjoint(self, other) }
+    }
+
+    pub fn contains(&self, other: &tg_geom) -> bool {
+        unsafe { tg_geom_contains(self, other) }
+    }
+
+    pub fn within(&self, other: &tg_geom) -> bool {
+        unsafe { tg_geom_within(self, other) }
+    }
+
+    pub fn covers(&self, other: &tg_geom) -> bool {
+        unsafe { tg_geom_covers(self, other) }
+    }
+
+    pub fn coveredby(&self, other: &tg_geom) -> bool {
+        unsafe { tg_geom_coveredby(self, other) }
+    }
+
+    pub fn intersects_rect(&self, rect: tg_rect) -> bool {
+        unsafe { tg_geom_intersects_rect(self, rect) }
+    }
+
+    pub fn intersects_xy(&self, x: f64, y: f64) -> bool {
+        unsafe { tg_geom_intersects_xy(self, x, y) }
+    }
+
+    /// Tests each point against the geometry, in input order.
+    ///
+    /// Points on the boundary are contained, matching
+    /// [`intersects_xy()`][tg_geom::intersects_xy]. Points outside the
+    /// geometry's bounding rectangle are rejected without calling into tg.
+    ///
+    /// This suits points that already have good spatial locality, such as
+    /// consecutive fixes from a GPS track. For scattered points use
+    /// [`intersects_xy_batch()`][tg_geom::intersects_xy_batch].
+    pub fn contains_points(&self, points: &[tg_point]) -> Vec<bool> {
+        let rect = self.rect();
+        points
+            .iter()
+            .map(|p| rect.intersects_point(*p) && self.intersects_xy(p.x, p.y))
+            .collect()
+    }
+
+    /// Tests each point against the geometry, returning results in input
+    /// order.
+    ///
+    /// Points outside the geometry's bounding rectangle are rejected up front,
+    /// and the rest are visited along a Z-order curve, so that consecutive
+    /// lookups walk neighbouring parts of the ring index and stay in cache.
+    pub fn intersects_xy_batch(&self, points: &[tg_point]) -> Vec<bool> {
+        let rect = self.rect();
+        let mut order: Vec<(u32, usize)> = points
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| rect.intersects_point(**p))
+            .map(|(i, p)| (morton(rect, *p), i))
+            .collect();
+        order.sort_unstable();
+
+        let mut results = vec![false; points.len()];
+        for (_, i) in order {
+            results[i] = self.intersects_xy(points[i].x, points[i].y);
+        }
+        results
+    }
+
+    fn write(
+        &self,
+        write: unsafe extern "C" fn(*const tg_geom, *mut u8, libc::size_t) -> libc::size_t,
+    ) -> Vec<u8> {
+        // The first call reports the required length, which excludes the nul
+        // terminator appended by the text writers.
+        let n = unsafe { write(self, std::ptr::null_mut(), 0) };
+        let mut buf = vec![0u8; n + 1];
+        unsafe { write(self, buf.as_mut_ptr(), buf.len()) };
+        buf.truncate(n);
+        buf
+    }
+
+    fn write_string(
+        &self,
+        write: unsafe extern "C" fn(
+            *const tg_geom,
+            *mut libc::c_char,
+            libc::size_t,
+        ) -> libc::size_t,
+    ) -> String {
+        let n = unsafe { write(self, std::ptr::null_mut(), 0) };
+        let mut buf = vec![0u8; n + 1];
+        unsafe { write(self, buf.as_mut_ptr().cast(), buf.len()) };
+        buf.truncate(n);
+        String::from_utf8(buf).expect("tg writes utf-8")
+    }
+
+    /// Writes the geometry as Well-known text (WKT).
+    pub fn to_wkt(&self) -> String {
+        self.write_string(tg_geom_wkt)
+    }
+
+    /// Writes the geometry as GeoJSON.
+    pub fn to_geojson(&self) -> String {
+        self.write_string(tg_geom_geojson)
+    }
+
+    /// Writes the geometry as Well-known binary (WKB).
+    pub fn to_wkb(&self) -> Vec<u8> {
+        self.write(tg_geom_wkb)
+    }
+
+    /// Writes the geometry as hex encoded Well-known binary (WKB).
+    pub fn to_hex(&self) -> String {
+        self.write_string(tg_geom_hex)
+    }
+}
+
+/// Returns the position of `p` along a Z-order curve covering `rect`.
+fn morton(rect: tg_rect, p: tg_point) -> u32 {
+    fn scale(v: f64, min: f64, max: f64) -> u32 {
+        if max > min {
+            ((v - min) / (max - min) * 65535.0) as u32
+        } else {
+            0
+        }
+    }
+    fn spread(mut v: u32) -> u32 {
+        v &= 0xffff;
+        v = (v | (v << 8)) & 0x00ff_00ff;
+        v = (v | (v << 4)) & 0x0f0f_0f0f;
+        v = (v | (v << 2)) & 0x3333_3333;
+        v = (v | (v << 1)) & 0x5555_5555;
+        v
+    }
+    let x = scale(p.x, rect.min.x, rect.max.x);
+    let y = scale(p.y, rect.min.y, rect.max.y);
+    spread(x) | (spread(y) << 1)
+}
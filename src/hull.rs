@@ -0,0 +1,67 @@
+//! Convex hulls.
+
+use crate::distance::vertices;
+use crate::{tg_geom, tg_geom_type, tg_point, Geom, Line, Poly, Ring};
+
+/// Returns twice the signed area of the triangle `a`, `b`, `c`: positive
+/// when it turns counterclockwise, negative when clockwise, and zero when
+/// the points are collinear.
+fn cross(a: tg_point, b: tg_point, c: tg_point) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Andrew's monotone chain: sorts the points, then builds the lower and
+/// upper halves of the hull in one pass each, popping any point that
+/// doesn't make a strict left turn. Collinear points are dropped, so the
+/// hull has no redundant vertices. Returns the hull counterclockwise and
+/// open, without repeating its first point.
+fn monotone_chain(mut points: Vec<tg_point>) -> Vec<tg_point> {
+    points.retain(|p| p.x.is_finite() && p.y.is_finite());
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let mut hull: Vec<tg_point> = Vec::with_capacity(points.len() + 1);
+    let chain = |hull: &mut Vec<tg_point>, p: tg_point, floor: usize| {
+        while hull.len() > floor && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(p);
+    };
+    for &p in &points {
+        chain(&mut hull, p, 1);
+    }
+    let lower = hull.len();
+    for &p in points.iter().rev().skip(1) {
+        chain(&mut hull, p, lower);
+    }
+    // The upper chain ends back at the first point.
+    hull.pop();
+    hull
+}
+
+impl tg_geom {
+    /// Returns the convex hull of every point of the geometry, including
+    /// those in collections.
+    ///
+    /// The hull is a Polygon with a counterclockwise exterior and no
+    /// collinear vertices, unless the points don't span an area: then it's
+    /// a Point when they all coincide, and a two-point LineString when
+    /// they're collinear. Empty geometries give an empty Polygon.
+    /// Non-finite coordinates are ignored.
+    pub fn convex_hull(&self) -> Geom {
+        let mut points = Vec::new();
+        vertices(self, &mut points);
+        let mut hull = monotone_chain(points);
+        match hull.len() {
+            0 => Geom::new_empty(tg_geom_type::TG_POLYGON),
+            1 => Geom::new_point(hull[0]),
+            2 => Geom::new_linestring(&Line::new(&hull)),
+            _ => {
+                hull.push(hull[0]);
+                Geom::new_polygon(&Poly::new(&Ring::new(&hull), &[]))
+            }
+        }
+    }
+}
@@ -0,0 +1,491 @@
+//! An R-tree over many geometries.
+//!
+//! tg indexes the segments inside a single geometry. [`GeomIndex`] sits one
+//! level above that: it indexes whole geometries by their bounding rectangles,
+//! so finding the geometries near a point or rectangle doesn't mean testing
+//! every one of them.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::{tg_geom, tg_point, tg_rect, Geom, Predicate};
+
+/// The maximum number of entries in a node, matching tg's default index
+/// spread.
+const MAX_ENTRIES: usize = 16;
+
+/// A rectangle covering the whole plane, for visiting every entry.
+pub(crate) const EVERYWHERE: tg_rect = tg_rect {
+    min: tg_point {
+        x: f64::NEG_INFINITY,
+        y: f64::NEG_INFINITY,
+    },
+    max: tg_point {
+        x: f64::INFINITY,
+        y: f64::INFINITY,
+    },
+};
+
+/// A spatial index of geometries, keyed by their
+/// [`tg_geom_rect()`][crate::GeometryAccessors::tg_geom_rect].
+///
+/// Each geometry is identified by the id returned from
+/// [`insert()`][GeomIndex::insert], or by its position in the input for
+/// [`bulk_load()`][GeomIndex::bulk_load]. The ids of removed geometries are
+/// reused by later inserts.
+///
+/// Queries first select candidates whose bounding rectangles match, then
+/// refine them with the exact [`GeometryPredicates`][crate::GeometryPredicates]
+/// test.
+#[derive(Default)]
+pub struct GeomIndex {
+    slots: Vec<Option<Slot>>,
+    free: Vec<usize>,
+    root: Node,
+}
+
+struct Slot {
+    geom: Geom,
+    rect: tg_rect,
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    rect: tg_rect,
+    id: usize,
+}
+
+struct Child {
+    rect: tg_rect,
+    node: Box<Node>,
+}
+
+enum Node {
+    Leaf(Vec<Entry>),
+    Branch(Vec<Child>),
+}
+
+impl Default for Node {
+    fn default() -> Node {
+        Node::Leaf(Vec::new())
+    }
+}
+
+impl Node {
+    fn rect(&self) -> tg_rect {
+        let mut rects: Box<dyn Iterator<Item = tg_rect>> = match self {
+            Node::Leaf(entries) => Box::new(entries.iter().map(|e| e.rect)),
+            Node::Branch(children) => Box::new(children.iter().map(|c| c.rect)),
+        };
+        let first = rects.next().unwrap_or(EVERYWHERE);
+        rects.fold(first, tg_rect::expand)
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Node::Leaf(entries) => entries.is_empty(),
+            Node::Branch(children) => children.is_empty(),
+        }
+    }
+
+    /// Inserts an entry, returning a new sibling if the node had to split.
+    fn insert(&mut self, entry: Entry) -> Option<Node> {
+        match self {
+            Node::Leaf(entries) => {
+                entries.push(entry);
+                (entries.len() > MAX_ENTRIES).then(|| Node::Leaf(split(entries, |e| e.rect)))
+            }
+            Node::Branch(children) => {
+                let i = choose_subtree(children, entry.rect);
+                let child = &mut children[i];
+                let sibling = child.node.insert(entry);
+                child.rect = child.node.rect();
+                children.extend(sibling.map(Child::new));
+                (children.len() > MAX_ENTRIES).then(|| Node::Branch(split(children, |c| c.rect)))
+            }
+        }
+    }
+
+    /// Removes an entry, returning whether it was found.
+    ///
+    /// Emptied nodes are removed from their parents, but underfull nodes are
+    /// left in place rather than reinserted.
+    fn remove(&mut self, entry: Entry) -> bool {
+        match self {
+            Node::Leaf(entries) => match entries.iter().position(|e| e.id == entry.id) {
+                Some(i) => {
+                    entries.swap_remove(i);
+                    true
+                }
+                None => false,
+            },
+            Node::Branch(children) => {
+                for i in 0..children.len() {
+                    let child = &mut children[i];
+                    if child.rect.intersects_rect(entry.rect) && child.node.remove(entry) {
+                        if child.node.is_empty() {
+                            children.swap_remove(i);
+                        } else {
+                            child.rect = child.node.rect();
+                        }
+                        return true;
+                    }
+                }
+                false
+            }
+        }
+    }
+}
+
+impl Child {
+    fn new(node: Node) -> Child {
+        Child {
+            rect: node.rect(),
+            node: Box::new(node),
+        }
+    }
+}
+
+/// Picks the child whose rectangle grows the least to cover `rect`, breaking
+/// ties by the smallest area.
+fn choose_subtree(children: &[Child], rect: tg_rect) -> usize {
+    let cost = |c: &Child| {
+        let area = c.rect.area();
+        (c.rect.expand(rect).area() - area, area)
+    };
+    (0..children.len())
+        .min_by(|&a, &b| {
+            let (a, b) = (cost(&children[a]), cost(&children[b]));
+            a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1))
+        })
+        .expect("branch nodes are never empty")
+}
+
+/// Splits an overfull node in half along the axis where the centers of its
+/// items are most spread out, returning the upper half.
+fn split<T>(items: &mut Vec<T>, rect: impl Fn(&T) -> tg_rect) -> Vec<T> {
+    let bounds = items
+        .iter()
+        .map(|item| rect(item).center())
+        .fold(None, |bounds: Option<tg_rect>, p| match bounds {
+            Some(bounds) => Some(bounds.expand_point(p)),
+            None => Some(tg_rect { min: p, max: p }),
+        })
+        .expect("split nodes are never empty");
+    if bounds.max.x - bounds.min.x >= bounds.max.y - bounds.min.y {
+        items.sort_by(|a, b| rect(a).center().x.total_cmp(&rect(b).center().x));
+    } else {
+        items.sort_by(|a, b| rect(a).center().y.total_cmp(&rect(b).center().y));
+    }
+    items.split_off(items.len() / 2)
+}
+
+/// Groups items into nodes using Sort-Tile-Recursive packing.
+fn pack<T>(mut items: Vec<T>, rect: impl Fn(&T) -> tg_rect) -> Vec<Vec<T>> {
+    let nodes = items.len().div_ceil(MAX_ENTRIES);
+    let slices = (nodes as f64).sqrt().ceil() as usize;
+    let slice_len = MAX_ENTRIES * nodes.div_ceil(slices.max(1));
+
+    items.sort_by(|a, b| rect(a).center().x.total_cmp(&rect(b).center().x));
+    let mut groups = Vec::with_capacity(nodes);
+    let mut items = items.into_iter().peekable();
+    while items.peek().is_some() {
+        let mut slice: Vec<T> = items.by_ref().take(slice_len).collect();
+        slice.sort_by(|a, b| rect(a).center().y.total_cmp(&rect(b).center().y));
+        let mut slice = slice.into_iter().peekable();
+        while slice.peek().is_some() {
+            groups.push(slice.by_ref().take(MAX_ENTRIES).collect());
+        }
+    }
+    groups
+}
+
+impl GeomIndex {
+    /// Creates an empty index.
+    pub fn new() -> GeomIndex {
+        GeomIndex::default()
+    }
+
+    /// Builds an index from many geometries at once.
+    ///
+    /// This packs the tree much more tightly than inserting the geometries one
+    /// at a time, which makes queries faster. Ids are assigned in input order,
+    /// starting from zero.
+    pub fn bulk_load(geoms: impl IntoIterator<Item = Geom>) -> GeomIndex {
+        let slots: Vec<Option<Slot>> = geoms
+            .into_iter()
+            .map(|geom| {
+                let rect = geom.rect();
+                Some(Slot { geom, rect })
+            })
+            .collect();
+        let entries: Vec<Entry> = slots
+            .iter()
+            .enumerate()
+            .map(|(id, slot)| Entry {
+                rect: slot.as_ref().unwrap().rect,
+                id,
+            })
+            .collect();
+
+        let root = if entries.len() <= MAX_ENTRIES {
+            Node::Leaf(entries)
+        } else {
+            let mut children: Vec<Child> = pack(entries, |e| e.rect)
+                .into_iter()
+                .map(|group| Child::new(Node::Leaf(group)))
+                .collect();
+            while children.len() > MAX_ENTRIES {
+                children = pack(children, |c| c.rect)
+                    .into_iter()
+                    .map(|group| Child::new(Node::Branch(group)))
+                    .collect();
+            }
+            Node::Branch(children)
+        };
+
+        GeomIndex {
+            slots,
+            free: Vec::new(),
+            root,
+        }
+    }
+
+    /// Returns the number of geometries in the index.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the geometry with the given id.
+    pub fn get(&self, id: usize) -> Option<&Geom> {
+        self.slots.get(id)?.as_ref().map(|slot| &slot.geom)
+    }
+
+    /// Iterates over every geometry in the index, in id order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Geom)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(id, slot)| Some((id, &slot.as_ref()?.geom)))
+    }
+
+    /// Adds a geometry to the index, returning its id.
+    pub fn insert(&mut self, geom: Geom) -> usize {
+        let rect = geom.rect();
+        let slot = Some(Slot { geom, rect });
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.slots[id] = slot;
+                id
+            }
+            None => {
+                self.slots.push(slot);
+                self.slots.len() - 1
+            }
+        };
+
+        if let Some(sibling) = self.root.insert(Entry { rect, id }) {
+            let root = std::mem::take(&mut self.root);
+            self.root = Node::Branch(vec![Child::new(root), Child::new(sibling)]);
+        }
+        id
+    }
+
+    /// Removes a geometry from the index, returning it if the id was present.
+    pub fn remove(&mut self, id: usize) -> Option<Geom> {
+        let slot = self.slots.get_mut(id)?.take()?;
+        let removed = self.root.remove(Entry {
+            rect: slot.rect,
+            id,
+        });
+        debug_assert!(removed, "indexed geometry missing from tree");
+        self.free.push(id);
+
+        // Shrink the tree when the root is left with a single child.
+        loop {
+            match &mut self.root {
+                Node::Branch(children) if children.len() <= 1 => {
+                    self.root = match children.pop() {
+                        Some(child) => *child.node,
+                        None => Node::default(),
+                    }
+                }
+                _ => break,
+            }
+        }
+        Some(slot.geom)
+    }
+
+    /// Iterates over the geometries whose bounding rectangles intersect
+    /// `rect`, without testing the geometries themselves.
+    pub fn candidates(&self, rect: tg_rect) -> Candidates<'_> {
+        Candidates {
+            index: self,
+            rect,
+            stack: vec![&self.root],
+            entries: [].iter(),
+        }
+    }
+
+    /// Iterates over the geometries that intersect `rect`.
+    pub fn query_rect(&self, rect: tg_rect) -> impl Iterator<Item = (usize, &Geom)> + '_ {
+        self.candidates(rect)
+            .filter(move |(_, geom)| geom.intersects_rect(rect))
+    }
+
+    /// Iterates over the geometries that contain `point`, including on their
+    /// boundary.
+    pub fn query_point(&self, point: tg_point) -> impl Iterator<Item = (usize, &Geom)> + '_ {
+        self.candidates(tg_rect {
+            min: point,
+            max: point,
+        })
+        .filter(move |(_, geom)| geom.intersects_xy(point.x, point.y))
+    }
+
+    /// Iterates over the indexed geometries `g` for which
+    /// `predicate(g, query)` holds.
+    ///
+    /// For example, `index.query(&point, Predicate::Contains)` finds every
+    /// indexed polygon containing `point`.
+    pub fn query<'a>(
+        &'a self,
+        query: &'a tg_geom,
+        predicate: Predicate,
+    ) -> impl Iterator<Item = (usize, &'a Geom)> + 'a {
+        let rect = if predicate.requires_intersecting_rects() {
+            query.rect()
+        } else {
+            EVERYWHERE
+        };
+        self.candidates(rect)
+            .filter(move |(_, geom)| predicate.eval(geom, query))
+    }
+
+    /// Iterates over the geometries in order of the distance from `point` to
+    /// their bounding rectangles, nearest first.
+    ///
+    /// Each item carries that distance, which is zero when the point is inside
+    /// the rectangle. It is a lower bound on the distance to the geometry
+    /// itself.
+    pub fn nearest(&self, point: tg_point) -> Nearest<'_> {
+        let mut queue = BinaryHeap::new();
+        if !self.root.is_empty() {
+            queue.push(Queued {
+                dist: self.root.rect().distance_to_point(point),
+                item: Item::Node(&self.root),
+            });
+        }
+        Nearest {
+            index: self,
+            point,
+            queue,
+        }
+    }
+}
+
+impl FromIterator<Geom> for GeomIndex {
+    fn from_iter<I: IntoIterator<Item = Geom>>(geoms: I) -> GeomIndex {
+        GeomIndex::bulk_load(geoms)
+    }
+}
+
+/// Iterator returned by [`GeomIndex::candidates()`].
+pub struct Candidates<'a> {
+    index: &'a GeomIndex,
+    rect: tg_rect,
+    stack: Vec<&'a Node>,
+    entries: std::slice::Iter<'a, Entry>,
+}
+
+impl<'a> Iterator for Candidates<'a> {
+    type Item = (usize, &'a Geom);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.entries.find(|e| e.rect.intersects_rect(self.rect)) {
+                let geom = self.index.get(entry.id).expect("indexed geometry missing");
+                return Some((entry.id, geom));
+            }
+            match self.stack.pop()? {
+                Node::Leaf(entries) => self.entries = entries.iter(),
+                Node::Branch(children) => self.stack.extend(
+                    children
+                        .iter()
+                        .filter(|c| c.rect.intersects_rect(self.rect))
+                        .map(|c| &*c.node),
+                ),
+            }
+        }
+    }
+}
+
+/// Iterator returned by [`GeomIndex::nearest()`].
+pub struct Nearest<'a> {
+    index: &'a GeomIndex,
+    point: tg_point,
+    queue: BinaryHeap<Queued<'a>>,
+}
+
+enum Item<'a> {
+    Node(&'a Node),
+    Entry(usize),
+}
+
+struct Queued<'a> {
+    dist: f64,
+    item: Item<'a>,
+}
+
+// Ordered so that the max-heap pops the smallest distance first.
+impl Ord for Queued<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.dist.total_cmp(&self.dist)
+    }
+}
+
+impl PartialOrd for Queued<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Queued<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued<'_> {}
+
+impl<'a> Iterator for Nearest<'a> {
+    type Item = (usize, &'a Geom, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Queued { dist, item } = self.queue.pop()?;
+            match item {
+                Item::Entry(id) => {
+                    let geom = self.index.get(id).expect("indexed geometry missing");
+                    return Some((id, geom, dist));
+                }
+                Item::Node(Node::Leaf(entries)) => {
+                    self.queue.extend(entries.iter().map(|e| Queued {
+                        dist: e.rect.distance_to_point(self.point),
+                        item: Item::Entry(e.id),
+                    }))
+                }
+                Item::Node(Node::Branch(children)) => {
+                    self.queue.extend(children.iter().map(|c| Queued {
+                        dist: c.rect.distance_to_point(self.point),
+                        item: Item::Node(&c.node),
+                    }))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Spatial joins between two sets of geometries.
+
+use crate::index::EVERYWHERE;
+use crate::{tg_geom, Geom, GeomIndex, Predicate};
+
+/// Joins two sets of geometries, yielding the `(left_idx, right_idx)` pairs
+/// for which `predicate(left[left_idx], right[right_idx])` holds.
+///
+/// A [`GeomIndex`] is built over `right`, and `left` is streamed through it
+/// one geometry at a time, so `right` should be the smaller set: in a
+/// points-to-polygons join, pass the points as `left` and the polygons as
+/// `right`. Pairs are yielded in `left` order, and by ascending `right_idx`
+/// for each left geometry.
+///
+/// ```no_run
+/// # use tg_sys::{spatial_join, Geom, Predicate};
+/// let zones = vec![Geom::parse_wkt("POLYGON((0 0,10 0,10 10,0 10,0 0))").unwrap()];
+/// let fixes = ["POINT(5 5)", "POINT(20 20)"].map(|wkt| Geom::parse_wkt(wkt).unwrap());
+/// let pairs: Vec<_> = spatial_join(&fixes, &zones, Predicate::Within).collect();
+/// assert_eq!(pairs, [(0, 0)]);
+/// ```
+pub fn spatial_join<L>(left: L, right: &[Geom], predicate: Predicate) -> SpatialJoin<L::IntoIter>
+where
+    L: IntoIterator,
+    L::Item: AsRef<tg_geom>,
+{
+    SpatialJoin {
+        index: GeomIndex::bulk_load(right.iter().cloned()),
+        left: left.into_iter().enumerate(),
+        predicate,
+        current: 0,
+        pending: Vec::new().into_iter(),
+    }
+}
+
+/// Iterator returned by [`spatial_join()`].
+pub struct SpatialJoin<I> {
+    index: GeomIndex,
+    left: std::iter::Enumerate<I>,
+    predicate: Predicate,
+    current: usize,
+    pending: std::vec::IntoIter<usize>,
+}
+
+impl<I> Iterator for SpatialJoin<I>
+where
+    I: Iterator,
+    I::Item: AsRef<tg_geom>,
+{
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        loop {
+            if let Some(right) = self.pending.next() {
+                return Some((self.current, right));
+            }
+            let (i, geom) = self.left.next()?;
+            let mut matches: Vec<usize> =
+                matches(&self.index, geom.as_ref(), self.predicate).collect();
+            matches.sort_unstable();
+            self.current = i;
+            self.pending = matches.into_iter();
+        }
+    }
+}
+
+/// Returns the ids of the indexed geometries `r` for which
+/// `predicate(geom, r)` holds.
+fn matches<'a>(
+    index: &'a GeomIndex,
+    geom: &'a tg_geom,
+    predicate: Predicate,
+) -> impl Iterator<Item = usize> + 'a {
+    let rect = if predicate.requires_intersecting_rects() {
+        geom.rect()
+    } else {
+        EVERYWHERE
+    };
+    index
+        .candidates(rect)
+        .filter(move |(_, other)| predicate.eval(geom, other))
+        .map(|(id, _)| id)
+}
+
+/// Like [`spatial_join()`], but evaluates the left geometries in parallel on
+/// the rayon thread pool.
+///
+/// The pairs are collected and returned in the same order as
+/// [`spatial_join()`] would yield them.
+///
+/// Sharing geometries between threads relies on the atomic reference
+/// counters enabled by the `atomics` feature.
+#[cfg(all(feature = "rayon", feature = "atomics"))]
+pub fn par_spatial_join(
+    left: &[Geom],
+    right: &[Geom],
+    predicate: Predicate,
+) -> Vec<(usize, usize)> {
+    use rayon::prelude::*;
+
+    let index = GeomIndex::bulk_load(right.iter().cloned());
+    left.par_iter()
+        .enumerate()
+        .flat_map_iter(|(i, geom)| {
+            let mut matches: Vec<usize> = matches(&index, geom, predicate).collect();
+            matches.sort_unstable();
+            matches.into_iter().map(move |right| (i, right))
+        })
+        .collect()
+}
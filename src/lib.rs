@@ -1,5 +1,55 @@
 #![allow(nonstandard_style)]
 
+mod centroid;
+mod distance;
+mod ewkb;
+mod fgb;
+mod geodesic;
+mod geojson;
+mod geom;
+mod hull;
+mod index;
+mod join;
+mod mvt;
+mod orient;
+#[cfg(all(feature = "rayon", feature = "atomics"))]
+mod par;
+mod polyline;
+mod rect;
+mod seq;
+mod shape;
+mod shp;
+mod stats;
+mod svg;
+mod twkb;
+mod valid;
+mod value;
+
+pub use distance::PathDistance;
+pub use fgb::{FgbFeature, FgbFeatures, FgbHeader, FgbReader};
+pub use geodesic::distance_m;
+pub use geojson::{Feature, FeatureReader, ReadError};
+pub use geom::{Dims, Error, Geom, Predicate};
+pub use index::{Candidates, GeomIndex, Nearest};
+#[cfg(all(feature = "rayon", feature = "atomics"))]
+pub use join::par_spatial_join;
+pub use join::{spatial_join, SpatialJoin};
+pub use mvt::{decode_mvt, encode_mvt_layer, MvtDecodedFeature, MvtFeature, MvtLayer};
+pub use orient::Orientation;
+#[cfg(all(feature = "rayon", feature = "atomics"))]
+pub use par::{par_contains_points, par_intersects, par_parse_wkt};
+pub use seq::{
+    read_geojsonseq, read_geojsonseq_ix, read_wkt_lines, read_wkt_lines_ix, write_geojsonseq,
+    write_geojsonseq_rfc7946, write_wkt_lines, RecordReader,
+};
+pub use shape::{Line, Poly, Ring};
+pub use shp::{ShpHeader, ShpReader, ShpRecord, ShpRecords};
+pub use stats::{recommend_index, IndexStats, LevelStats};
+pub use svg::{to_svg, SvgStyle};
+pub use twkb::TwkbOptions;
+pub use valid::{ValidityError, ValidityErrorKind};
+pub use value::Value;
+
 /// The base point type used for all geometries.
 ///
 /// - See [`PointFuncs`]
@@ -10,7 +60,6 @@ pub struct tg_point {
     pub y: libc::c_double,
 }
 
-
 /// The base segment type used in [`tg_line`] and [`tg_ring`] for joining two vertices.
 ///
 /// - See [`SegmentFuncs`]`
@@ -42,6 +91,7 @@ pub struct tg_rect {
 /// - See [`tg_geom_type_string()`][`GeometryAccessors::tg_geom_type_string`]
 /// - See [`GeometryAccessors`]
 #[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum tg_geom_type {
     ///< Point
     TG_POINT = 1,
@@ -68,6 +118,7 @@ pub enum tg_geom_type {
 /// performing nearest-neighbor (kNN) queries on the segment using
 /// [`tg_ring_index_*()`][RingFuncs] and [`tg_ring_nearest_segment()`][RingFuncs::tg_ring_nearest_segment] functions.
 #[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum tg_index {
     ///< default is TG_NATURAL or tg_env_set_default_index().
     TG_DEFAULT,
@@ -102,6 +153,9 @@ pub enum tg_index {
 #[repr(C)]
 pub struct tg_line {
     _unused: [u8; 0],
+    // See the note on `tg_geom`.
+    #[cfg(not(feature = "atomics"))]
+    _marker: std::marker::PhantomData<*const ()>,
 }
 
 /// A ring is series of [`tg_segment`] which creates a shape that does not
@@ -130,6 +184,9 @@ pub struct tg_line {
 #[repr(C)]
 pub struct tg_ring {
     _unused: [u8; 0],
+    // See the note on `tg_geom`.
+    #[cfg(not(feature = "atomics"))]
+    _marker: std::marker::PhantomData<*const ()>,
 }
 
 /// A polygon consists of one exterior ring and zero or more holes.
@@ -155,6 +212,9 @@ pub struct tg_ring {
 #[repr(C)]
 pub struct tg_poly {
     _unused: [u8; 0],
+    // See the note on `tg_geom`.
+    #[cfg(not(feature = "atomics"))]
+    _marker: std::marker::PhantomData<*const ()>,
 }
 
 /// A geometry is the common generic type that can represent a Point,
@@ -193,9 +253,23 @@ pub struct tg_poly {
 /// - See [`GeometryPredicates`]
 /// - See [`GeometryParsing`]
 /// - See [`GeometryWriting`]
+///
+/// Without the `atomics` feature, tg's reference counts are not atomic, and
+/// cloning a shared geometry from two threads would race. The tg types are
+/// then neither `Send` nor `Sync`:
+///
+#[cfg_attr(feature = "atomics", doc = "```")]
+#[cfg_attr(not(feature = "atomics"), doc = "```compile_fail")]
+/// fn shared<T: Send + Sync>() {}
+/// shared::<tg_sys::tg_geom>();
+/// ```
 #[repr(C)]
 pub struct tg_geom {
     _unused: [u8; 0],
+    // A shared `&tg_geom` can take a new reference with `to_owned()`, so it
+    // must not cross threads unless the count is atomic.
+    #[cfg(not(feature = "atomics"))]
+    _marker: std::marker::PhantomData<*const ()>,
 }
 
 /// Geometry constructors
@@ -215,8 +289,7 @@ pub mod GeometryConstructors {
         /// # Safety:
         ///
         /// The caller is responsible for freeing with [`tg_geom_free()`][tg_geom_free].
-        pub fn tg_geom_new_point(
-            point: tg_point) -> *mut tg_geom;
+        pub fn tg_geom_new_point(point: tg_point) -> *mut tg_geom;
 
         /// Creates a LineString geometry.
         ///
@@ -257,7 +330,6 @@ pub mod GeometryConstructors {
             npoints: libc::c_int,
         ) -> *mut tg_geom;
 
-
         /// Creates a MultiLineString geometry.
         ///
         /// Returns a newly allocated geometry or NULL if system is out of memory.
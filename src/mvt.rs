@@ -0,0 +1,838 @@
+//! Mapbox Vector Tile (MVT) encoding.
+//!
+//! [`encode_mvt_layer()`] projects longitude/latitude geometries into the
+//! Web Mercator tile at `z/x/y`, clips them to the tile, and writes a tile
+//! with one layer as protobuf bytes, following version 2.1 of the Mapbox
+//! Vector Tile Specification. [`decode_mvt()`] reads tiles back, mostly for
+//! testing.
+//!
+//! ```no_run
+//! use tg_sys::{encode_mvt_layer, Geom, MvtFeature, Value};
+//!
+//! let fence = Geom::parse_wkt("POLYGON((-122.5 37.7,-122.4 37.7,-122.4 37.8,-122.5 37.7))")?;
+//! let properties = [("name".to_string(), Value::String("depot".to_string()))];
+//! let features = [MvtFeature {
+//!     id: Some(1),
+//!     geom: &fence,
+//!     properties: &properties,
+//! }];
+//! let tile = encode_mvt_layer("fences", &features, 12, 655, 1583, 4096)?;
+//! # Ok::<(), tg_sys::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use crate::{
+    tg_geom, tg_geom_type, tg_line, tg_point, tg_poly, tg_rect, tg_ring, Error, Geom, Line, Poly,
+    Ring, Value,
+};
+
+/// The MVT specification version written to each layer.
+const VERSION: u32 = 2;
+
+/// The latitude at which Web Mercator tiles end.
+const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+const MOVE_TO: u32 = 1;
+const LINE_TO: u32 = 2;
+const CLOSE_PATH: u32 = 7;
+
+/// A feature to encode with [`encode_mvt_layer()`].
+#[derive(Clone, Copy)]
+pub struct MvtFeature<'a> {
+    pub id: Option<u64>,
+    /// The geometry, in longitude/latitude.
+    pub geom: &'a tg_geom,
+    /// Attributes to tag the feature with. Null and binary values are
+    /// skipped, as MVT can't represent them.
+    pub properties: &'a [(String, Value)],
+}
+
+/// Encodes features as a vector tile holding a single layer.
+///
+/// Geometries are projected from longitude/latitude into the Web Mercator
+/// tile at `z/x/y`, scaled to `extent` units, and clipped to the tile plus a
+/// buffer of 1/16 of the extent on each side, so that lines and polygon
+/// edges join up across tiles. Features that don't reach the buffered tile
+/// are dropped early with [`intersects_rect()`][tg_geom::intersects_rect].
+///
+/// Polygon rings are rewound as the specification requires, with exteriors
+/// clockwise and holes counterclockwise in tile coordinates, and rings that
+/// collapse once rounded to the tile grid are dropped. Members of a
+/// GeometryCollection are written as separate features sharing the
+/// collection's id and properties.
+///
+/// Tiles are protobuf messages whose layers are a repeated field, so the
+/// output of several calls can be concatenated into a multi-layer tile.
+///
+/// Returns an error if `extent` is zero.
+pub fn encode_mvt_layer<'a, I>(
+    name: &str,
+    features: I,
+    z: u32,
+    x: u32,
+    y: u32,
+    extent: u32,
+) -> Result<Vec<u8>, Error>
+where
+    I: IntoIterator<Item = &'a MvtFeature<'a>>,
+{
+    let tile = TileProjection::new(z, x, y, extent)?;
+    let quick_reject = tile.lonlat_rect();
+    let mut layer = LayerWriter::default();
+    for feature in features {
+        if !feature.geom.intersects_rect(quick_reject) {
+            continue;
+        }
+        layer.feature(&tile, feature, feature.geom);
+    }
+
+    let mut body = Vec::new();
+    write_varint_field(&mut body, 15, VERSION.into());
+    write_bytes_field(&mut body, 1, name.as_bytes());
+    for feature in &layer.features {
+        write_bytes_field(&mut body, 2, feature);
+    }
+    for key in &layer.keys {
+        write_bytes_field(&mut body, 3, key.as_bytes());
+    }
+    for value in &layer.values {
+        write_bytes_field(&mut body, 4, value);
+    }
+    write_varint_field(&mut body, 5, extent.into());
+
+    let mut out = Vec::with_capacity(body.len() + 8);
+    write_bytes_field(&mut out, 3, &body);
+    Ok(out)
+}
+
+/// Maps longitude/latitude to the coordinates of one tile.
+struct TileProjection {
+    scale: f64,
+    x: f64,
+    y: f64,
+    extent: f64,
+    buffer: f64,
+}
+
+impl TileProjection {
+    fn new(z: u32, x: u32, y: u32, extent: u32) -> Result<TileProjection, Error> {
+        if extent == 0 {
+            return Err(Error::new("mvt: extent must be positive"));
+        }
+        let extent = f64::from(extent);
+        Ok(TileProjection {
+            scale: 2f64.powi(z as i32),
+            x: x.into(),
+            y: y.into(),
+            extent,
+            buffer: extent / 16.0,
+        })
+    }
+
+    fn project(&self, p: tg_point) -> tg_point {
+        let lat = p.y.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+        let wx = (p.x + 180.0) / 360.0 * self.scale;
+        let wy = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * self.scale;
+        tg_point {
+            x: (wx - self.x) * self.extent,
+            y: (wy - self.y) * self.extent,
+        }
+    }
+
+    /// Returns the buffered tile in longitude/latitude.
+    fn lonlat_rect(&self) -> tg_rect {
+        let unproject = |tx: f64, ty: f64| {
+            let wx = self.x + tx / self.extent;
+            let wy = self.y + ty / self.extent;
+            tg_point {
+                x: wx / self.scale * 360.0 - 180.0,
+                y: (PI * (1.0 - 2.0 * wy / self.scale))
+                    .sinh()
+                    .atan()
+                    .to_degrees(),
+            }
+        };
+        let lo = -self.buffer;
+        let hi = self.extent + self.buffer;
+        let (min, max) = (unproject(lo, hi), unproject(hi, lo));
+        // Let tiles on the edge of the map take everything beyond it.
+        let edge = |v: f64, limit: f64| {
+            if v.abs() >= limit {
+                v.signum() * 1e300
+            } else {
+                v
+            }
+        };
+        tg_rect {
+            min: tg_point {
+                x: edge(min.x, 180.0),
+                y: edge(min.y, MAX_LATITUDE),
+            },
+            max: tg_point {
+                x: edge(max.x, 180.0),
+                y: edge(max.y, MAX_LATITUDE),
+            },
+        }
+    }
+
+    /// Projects points, without rounding.
+    fn points(&self, points: &[tg_point]) -> Vec<tg_point> {
+        points.iter().map(|&p| self.project(p)).collect()
+    }
+
+    fn min(&self) -> f64 {
+        -self.buffer
+    }
+
+    fn max(&self) -> f64 {
+        self.extent + self.buffer
+    }
+
+    fn contains(&self, p: tg_point) -> bool {
+        p.x >= self.min() && p.x <= self.max() && p.y >= self.min() && p.y <= self.max()
+    }
+
+    /// Clips a line to the buffered tile, which may split it into parts.
+    fn clip_line(&self, points: &[tg_point]) -> Vec<Vec<tg_point>> {
+        let (lo, hi) = (self.min(), self.max());
+        let mut parts = Vec::new();
+        let mut part = Vec::new();
+        for segment in points.windows(2) {
+            let (a, b) = (segment[0], segment[1]);
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            // Liang-Barsky: narrow the visible range of the segment against
+            // each edge of the tile in turn.
+            let mut t0: f64 = 0.0;
+            let mut t1: f64 = 1.0;
+            let mut visible = true;
+            for (p, q) in [
+                (-dx, a.x - lo),
+                (dx, hi - a.x),
+                (-dy, a.y - lo),
+                (dy, hi - a.y),
+            ] {
+                if p == 0.0 {
+                    if q < 0.0 {
+                        visible = false;
+                    }
+                } else if p < 0.0 {
+                    t0 = t0.max(q / p);
+                } else {
+                    t1 = t1.min(q / p);
+                }
+            }
+            if !visible || t0 > t1 {
+                if !part.is_empty() {
+                    parts.push(std::mem::take(&mut part));
+                }
+                continue;
+            }
+            let at = |t: f64| tg_point {
+                x: a.x + t * dx,
+                y: a.y + t * dy,
+            };
+            if part.is_empty() {
+                part.push(at(t0));
+            }
+            part.push(at(t1));
+            if t1 < 1.0 {
+                parts.push(std::mem::take(&mut part));
+            }
+        }
+        if !part.is_empty() {
+            parts.push(part);
+        }
+        parts
+    }
+
+    /// Clips a ring to the buffered tile with Sutherland-Hodgman, which
+    /// keeps the ring's winding.
+    fn clip_ring(&self, points: &[tg_point]) -> Vec<tg_point> {
+        let (lo, hi) = (self.min(), self.max());
+        let mut ring = points.to_vec();
+        // Each edge is an axis (x or y), a bound, and which side to keep.
+        for (along_x, bound, keep_above) in [
+            (true, lo, true),
+            (true, hi, false),
+            (false, lo, true),
+            (false, hi, false),
+        ] {
+            let axis = |p: tg_point| if along_x { p.x } else { p.y };
+            let inside = |p: tg_point| (axis(p) >= bound) == keep_above || axis(p) == bound;
+            let mut clipped = Vec::with_capacity(ring.len());
+            for (i, &b) in ring.iter().enumerate() {
+                let a = ring[(i + ring.len() - 1) % ring.len()];
+                let crossing = || {
+                    let t = (bound - axis(a)) / (axis(b) - axis(a));
+                    tg_point {
+                        x: a.x + t * (b.x - a.x),
+                        y: a.y + t * (b.y - a.y),
+                    }
+                };
+                match (inside(a), inside(b)) {
+                    (true, true) => clipped.push(b),
+                    (true, false) => clipped.push(crossing()),
+                    (false, true) => {
+                        clipped.push(crossing());
+                        clipped.push(b);
+                    }
+                    (false, false) => {}
+                }
+            }
+            ring = clipped;
+            if ring.is_empty() {
+                break;
+            }
+        }
+        ring
+    }
+}
+
+/// Rounds points to the tile grid, dropping repeated points.
+fn snap(points: &[tg_point]) -> Vec<(i32, i32)> {
+    let mut snapped: Vec<(i32, i32)> = Vec::with_capacity(points.len());
+    for p in points {
+        let q = (p.x.round() as i32, p.y.round() as i32);
+        if snapped.last() != Some(&q) {
+            snapped.push(q);
+        }
+    }
+    snapped
+}
+
+/// Returns twice the signed area of a ring in tile coordinates, which is
+/// positive for rings that are clockwise on screen.
+fn signed_area(ring: &[(i32, i32)]) -> i64 {
+    let mut sum = 0;
+    for (i, &(x1, y1)) in ring.iter().enumerate() {
+        let (x2, y2) = ring[(i + 1) % ring.len()];
+        sum += i64::from(x1) * i64::from(y2) - i64::from(x2) * i64::from(y1);
+    }
+    sum
+}
+
+/// Encodes the commands of one geometry.
+#[derive(Default)]
+struct GeometryWriter {
+    commands: Vec<u32>,
+    cursor: (i32, i32),
+}
+
+impl GeometryWriter {
+    fn command(&mut self, id: u32, count: usize) {
+        self.commands.push(id | (count as u32) << 3);
+    }
+
+    fn point(&mut self, p: (i32, i32)) {
+        let dx = p.0.wrapping_sub(self.cursor.0);
+        let dy = p.1.wrapping_sub(self.cursor.1);
+        self.commands.push(((dx << 1) ^ (dx >> 31)) as u32);
+        self.commands.push(((dy << 1) ^ (dy >> 31)) as u32);
+        self.cursor = p;
+    }
+
+    fn path(&mut self, points: &[(i32, i32)]) {
+        self.command(MOVE_TO, 1);
+        self.point(points[0]);
+        self.command(LINE_TO, points.len() - 1);
+        for &p in &points[1..] {
+            self.point(p);
+        }
+    }
+
+    fn line(&mut self, points: &[(i32, i32)]) {
+        if points.len() >= 2 {
+            self.path(points);
+        }
+    }
+
+    /// Writes a ring that is already clockwise on screen if `exterior`, or
+    /// counterclockwise otherwise. Returns false if the ring collapsed, or
+    /// flipped, when rounded to the tile grid.
+    fn ring(&mut self, mut points: Vec<(i32, i32)>, exterior: bool) -> bool {
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        let area = signed_area(&points);
+        if points.len() < 3 || area == 0 || (area > 0) != exterior {
+            return false;
+        }
+        self.path(&points);
+        self.command(CLOSE_PATH, 1);
+        true
+    }
+}
+
+#[derive(Default)]
+struct LayerWriter {
+    features: Vec<Vec<u8>>,
+    keys: Vec<String>,
+    key_index: HashMap<String, u32>,
+    values: Vec<Vec<u8>>,
+    value_index: HashMap<Vec<u8>, u32>,
+}
+
+impl LayerWriter {
+    fn tags(&mut self, properties: &[(String, Value)]) -> Vec<u32> {
+        let mut tags = Vec::with_capacity(properties.len() * 2);
+        for (key, value) in properties {
+            let Some(value) = encode_value(value) else {
+                continue;
+            };
+            let next = self.keys.len() as u32;
+            let key = *self.key_index.entry(key.clone()).or_insert_with(|| {
+                self.keys.push(key.clone());
+                next
+            });
+            let next = self.values.len() as u32;
+            let value = *self.value_index.entry(value.clone()).or_insert_with(|| {
+                self.values.push(value);
+                next
+            });
+            tags.push(key);
+            tags.push(value);
+        }
+        tags
+    }
+
+    /// Encodes `geom` as one or more features.
+    fn feature(&mut self, tile: &TileProjection, feature: &MvtFeature, geom: &tg_geom) {
+        let mut writer = GeometryWriter::default();
+        let geom_type = match geom.geom_type() {
+            tg_geom_type::TG_POINT | tg_geom_type::TG_MULTIPOINT => {
+                let points: Vec<tg_point> = match geom.geom_type() {
+                    tg_geom_type::TG_POINT if !geom.is_empty() => vec![geom.point()],
+                    tg_geom_type::TG_POINT => Vec::new(),
+                    _ => geom.points().collect(),
+                };
+                let points: Vec<tg_point> = tile
+                    .points(&points)
+                    .into_iter()
+                    .filter(|&p| tile.contains(p))
+                    .collect();
+                let points = snap(&points);
+                if !points.is_empty() {
+                    writer.command(MOVE_TO, points.len());
+                    for &p in &points {
+                        writer.point(p);
+                    }
+                }
+                1
+            }
+            tg_geom_type::TG_LINESTRING | tg_geom_type::TG_MULTILINESTRING => {
+                let lines: Vec<&tg_line> = match geom.line() {
+                    Some(line) => vec![line],
+                    None => geom.lines().collect(),
+                };
+                for line in lines {
+                    for part in tile.clip_line(&tile.points(line.points())) {
+                        writer.line(&snap(&part));
+                    }
+                }
+                2
+            }
+            tg_geom_type::TG_POLYGON | tg_geom_type::TG_MULTIPOLYGON => {
+                let polys: Vec<&tg_poly> = match geom.poly() {
+                    Some(poly) => vec![poly],
+                    None => geom.polys().collect(),
+                };
+                // Rings are wound in longitude/latitude, where y points up, so
+                // an exterior that is counterclockwise there is clockwise on
+                // screen, as MVT wants.
+                let ring = |ring: &tg_ring, exterior: bool| {
+                    let mut points = tile.points(ring.points());
+                    if ring.clockwise() == exterior {
+                        points.reverse();
+                    }
+                    snap(&tile.clip_ring(&points))
+                };
+                for poly in polys {
+                    if !writer.ring(ring(poly.exterior(), true), true) {
+                        continue;
+                    }
+                    for hole in poly.holes() {
+                        writer.ring(ring(hole, false), false);
+                    }
+                }
+                3
+            }
+            tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                for member in geom.geometries() {
+                    self.feature(tile, feature, member);
+                }
+                return;
+            }
+        };
+        if writer.commands.is_empty() {
+            return;
+        }
+
+        let mut out = Vec::new();
+        if let Some(id) = feature.id {
+            write_varint_field(&mut out, 1, id);
+        }
+        let tags = self.tags(feature.properties);
+        if !tags.is_empty() {
+            write_packed_field(&mut out, 2, &tags);
+        }
+        write_varint_field(&mut out, 3, geom_type);
+        write_packed_field(&mut out, 4, &writer.commands);
+        self.features.push(out);
+    }
+}
+
+/// Encodes a value as an MVT Value message.
+fn encode_value(value: &Value) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    match value {
+        Value::String(s) | Value::Json(s) | Value::DateTime(s) => {
+            write_bytes_field(&mut out, 1, s.as_bytes())
+        }
+        Value::Float(v) => {
+            write_key(&mut out, 3, 1);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Value::Int(v) => write_varint_field(&mut out, 6, ((v << 1) ^ (v >> 63)) as u64),
+        Value::UInt(v) => write_varint_field(&mut out, 5, *v),
+        Value::Bool(v) => write_varint_field(&mut out, 7, (*v).into()),
+        Value::Null | Value::Binary(_) => return None,
+    }
+    Some(out)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn write_key(out: &mut Vec<u8>, field: u32, wire_type: u32) {
+    write_varint(out, u64::from(field << 3 | wire_type));
+}
+
+fn write_varint_field(out: &mut Vec<u8>, field: u32, v: u64) {
+    write_key(out, field, 0);
+    write_varint(out, v);
+}
+
+fn write_bytes_field(out: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    write_key(out, field, 2);
+    write_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn write_packed_field(out: &mut Vec<u8>, field: u32, values: &[u32]) {
+    let mut packed = Vec::with_capacity(values.len() * 2);
+    for &v in values {
+        write_varint(&mut packed, v.into());
+    }
+    write_bytes_field(out, field, &packed);
+}
+
+/// A layer read by [`decode_mvt()`].
+#[derive(Clone, Debug)]
+pub struct MvtLayer {
+    name: String,
+    extent: u32,
+    features: Vec<MvtDecodedFeature>,
+}
+
+impl MvtLayer {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn extent(&self) -> u32 {
+        self.extent
+    }
+
+    pub fn features(&self) -> &[MvtDecodedFeature] {
+        &self.features
+    }
+}
+
+/// A feature read by [`decode_mvt()`].
+#[derive(Clone, Debug)]
+pub struct MvtDecodedFeature {
+    id: Option<u64>,
+    geom: Geom,
+    properties: Vec<(String, Value)>,
+}
+
+impl MvtDecodedFeature {
+    pub fn id(&self) -> Option<u64> {
+        self.id
+    }
+
+    /// Returns the geometry in tile coordinates, with y pointing down.
+    pub fn geom(&self) -> &Geom {
+        &self.geom
+    }
+
+    pub fn properties(&self) -> &[(String, Value)] {
+        &self.properties
+    }
+
+    /// Returns the value of the named property.
+    pub fn property(&self, name: &str) -> Option<&Value> {
+        self.properties
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Decodes the layers of a vector tile, keeping geometries in tile
+/// coordinates.
+///
+/// Polygon rings are grouped by winding, as the specification describes:
+/// each ring that is clockwise on screen starts a new polygon, and the
+/// rings after it are its holes.
+pub fn decode_mvt(tile: &[u8]) -> Result<Vec<MvtLayer>, Error> {
+    let mut layers = Vec::new();
+    let mut reader = ProtoReader::new(tile);
+    while let Some((field, value)) = reader.next()? {
+        if let (3, Field::Bytes(bytes)) = (field, value) {
+            layers.push(decode_layer(bytes)?);
+        }
+    }
+    Ok(layers)
+}
+
+fn decode_layer(bytes: &[u8]) -> Result<MvtLayer, Error> {
+    let mut name = String::new();
+    let mut extent = 4096;
+    let mut raw_features = Vec::new();
+    let mut keys = Vec::new();
+    let mut values = Vec::new();
+    let mut reader = ProtoReader::new(bytes);
+    while let Some((field, value)) = reader.next()? {
+        match (field, value) {
+            (1, Field::Bytes(b)) => name = String::from_utf8_lossy(b).into_owned(),
+            (2, Field::Bytes(b)) => raw_features.push(b),
+            (3, Field::Bytes(b)) => keys.push(String::from_utf8_lossy(b).into_owned()),
+            (4, Field::Bytes(b)) => values.push(decode_value(b)?),
+            (5, Field::Varint(v)) => extent = v as u32,
+            _ => {}
+        }
+    }
+    let features = raw_features
+        .into_iter()
+        .map(|bytes| decode_feature(bytes, &keys, &values))
+        .collect::<Result<_, _>>()?;
+    Ok(MvtLayer {
+        name,
+        extent,
+        features,
+    })
+}
+
+fn decode_value(bytes: &[u8]) -> Result<Value, Error> {
+    let mut value = Value::Null;
+    let mut reader = ProtoReader::new(bytes);
+    while let Some((field, raw)) = reader.next()? {
+        value = match (field, raw) {
+            (1, Field::Bytes(b)) => Value::String(String::from_utf8_lossy(b).into_owned()),
+            (2, Field::Fixed32(v)) => Value::Float(f32::from_bits(v).into()),
+            (3, Field::Fixed64(v)) => Value::Float(f64::from_bits(v)),
+            (4, Field::Varint(v)) => Value::Int(v as i64),
+            (5, Field::Varint(v)) => Value::UInt(v),
+            (6, Field::Varint(v)) => Value::Int((v >> 1) as i64 ^ -((v & 1) as i64)),
+            (7, Field::Varint(v)) => Value::Bool(v != 0),
+            _ => continue,
+        };
+    }
+    Ok(value)
+}
+
+fn decode_feature(
+    bytes: &[u8],
+    keys: &[String],
+    values: &[Value],
+) -> Result<MvtDecodedFeature, Error> {
+    let mut id = None;
+    let mut tags = Vec::new();
+    let mut geom_type = 0;
+    let mut commands = Vec::new();
+    let mut reader = ProtoReader::new(bytes);
+    while let Some((field, value)) = reader.next()? {
+        match (field, value) {
+            (1, Field::Varint(v)) => id = Some(v),
+            (2, Field::Bytes(b)) => tags = unpack(b)?,
+            (3, Field::Varint(v)) => geom_type = v,
+            (4, Field::Bytes(b)) => commands = unpack(b)?,
+            _ => {}
+        }
+    }
+    let properties = tags
+        .chunks(2)
+        .map(|tag| match tag {
+            [k, v] => match (keys.get(*k as usize), values.get(*v as usize)) {
+                (Some(k), Some(v)) => Ok((k.clone(), v.clone())),
+                _ => Err(Error::new("mvt: tag out of range")),
+            },
+            _ => Err(Error::new("mvt: odd number of tags")),
+        })
+        .collect::<Result<_, _>>()?;
+    let paths = decode_paths(&commands)?;
+    let geom = match geom_type {
+        1 => {
+            let points: Vec<tg_point> = paths.into_iter().flat_map(|(p, _)| p).collect();
+            match points[..] {
+                [] => Geom::new_empty(tg_geom_type::TG_POINT),
+                [point] => Geom::new_point(point),
+                _ => Geom::new_multipoint(&points),
+            }
+        }
+        2 => {
+            let lines: Vec<Line> = paths.iter().map(|(p, _)| Line::new(p)).collect();
+            match &lines[..] {
+                [] => Geom::new_empty(tg_geom_type::TG_LINESTRING),
+                [line] => Geom::new_linestring(line),
+                lines => Geom::new_multilinestring(&lines.iter().map(|l| &**l).collect::<Vec<_>>()),
+            }
+        }
+        3 => {
+            let mut groups: Vec<Vec<Ring>> = Vec::new();
+            for (points, _) in &paths {
+                let snapped: Vec<(i32, i32)> =
+                    points.iter().map(|p| (p.x as i32, p.y as i32)).collect();
+                let ring = Ring::new(points);
+                match groups.last_mut() {
+                    Some(group) if signed_area(&snapped) < 0 => group.push(ring),
+                    _ => groups.push(vec![ring]),
+                }
+            }
+            let polys: Vec<Poly> = groups
+                .iter()
+                .map(|rings| {
+                    let holes: Vec<_> = rings[1..].iter().map(|h| &**h).collect();
+                    Poly::new(&rings[0], &holes)
+                })
+                .collect();
+            match &polys[..] {
+                [] => Geom::new_empty(tg_geom_type::TG_POLYGON),
+                [poly] => Geom::new_polygon(poly),
+                polys => Geom::new_multipolygon(&polys.iter().map(|p| &**p).collect::<Vec<_>>()),
+            }
+        }
+        t => return Err(Error::new(format!("mvt: unsupported geometry type {}", t))),
+    };
+    Ok(MvtDecodedFeature {
+        id,
+        geom,
+        properties,
+    })
+}
+
+/// Splits geometry commands into paths, each starting with a MoveTo, and
+/// notes whether each was closed.
+fn decode_paths(commands: &[u32]) -> Result<Vec<(Vec<tg_point>, bool)>, Error> {
+    let mut paths: Vec<(Vec<tg_point>, bool)> = Vec::new();
+    let mut cursor = (0i64, 0i64);
+    let mut iter = commands.iter().copied();
+    while let Some(command) = iter.next() {
+        let (id, count) = (command & 7, command >> 3);
+        match id {
+            MOVE_TO | LINE_TO => {
+                for _ in 0..count {
+                    let (Some(dx), Some(dy)) = (iter.next(), iter.next()) else {
+                        return Err(Error::new("mvt: truncated geometry"));
+                    };
+                    cursor.0 += (dx >> 1) as i64 ^ -((dx & 1) as i64);
+                    cursor.1 += (dy >> 1) as i64 ^ -((dy & 1) as i64);
+                    let p = tg_point {
+                        x: cursor.0 as f64,
+                        y: cursor.1 as f64,
+                    };
+                    match paths.last_mut() {
+                        _ if id == MOVE_TO => paths.push((vec![p], false)),
+                        Some((path, _)) => path.push(p),
+                        None => return Err(Error::new("mvt: LineTo without MoveTo")),
+                    }
+                }
+            }
+            CLOSE_PATH => match paths.last_mut() {
+                Some((_, closed)) => *closed = true,
+                None => return Err(Error::new("mvt: ClosePath without MoveTo")),
+            },
+            _ => return Err(Error::new(format!("mvt: unknown command {}", id))),
+        }
+    }
+    Ok(paths)
+}
+
+fn unpack(bytes: &[u8]) -> Result<Vec<u32>, Error> {
+    let mut reader = ProtoReader::new(bytes);
+    let mut values = Vec::new();
+    while reader.pos < bytes.len() {
+        values.push(reader.varint()? as u32);
+    }
+    Ok(values)
+}
+
+enum Field<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// Reads the fields of a protobuf message.
+struct ProtoReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ProtoReader<'a> {
+    fn new(buf: &'a [u8]) -> ProtoReader<'a> {
+        ProtoReader { buf, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.buf.len())
+            .ok_or_else(|| Error::new("mvt: unexpected end of input"))?;
+        let bytes = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64, Error> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.take(1)?[0];
+            v |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err(Error::new("mvt: varint is too long"))
+    }
+
+    fn next(&mut self) -> Result<Option<(u32, Field<'a>)>, Error> {
+        if self.pos == self.buf.len() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let field = (key >> 3) as u32;
+        let value = match key & 7 {
+            0 => Field::Varint(self.varint()?),
+            1 => Field::Fixed64(u64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            2 => {
+                let len = self.varint()?;
+                let len = usize::try_from(len).map_err(|_| Error::new("mvt: field too long"))?;
+                Field::Bytes(self.take(len)?)
+            }
+            5 => Field::Fixed32(u32::from_le_bytes(self.take(4)?.try_into().unwrap())),
+            t => return Err(Error::new(format!("mvt: unsupported wire type {}", t))),
+        };
+        Ok(Some((field, value)))
+    }
+}
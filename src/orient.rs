@@ -0,0 +1,203 @@
+//! Ring winding order for polygons.
+//!
+//! tg accepts rings wound either way and only reports their winding, but
+//! formats differ in what they require: RFC 7946 GeoJSON wants
+//! counterclockwise exteriors and clockwise holes (the right-hand rule),
+//! while Esri shapefiles want the opposite.
+
+use crate::GeometryAccessors::tg_geom_is_featurecollection;
+use crate::{tg_geom, tg_geom_type, tg_poly, tg_ring, Geom, Poly, Ring};
+
+/// A winding order for polygon rings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    /// Counterclockwise exteriors and clockwise holes, as RFC 7946 requires
+    /// of GeoJSON.
+    Rfc7946,
+    /// Clockwise exteriors and counterclockwise holes, as Esri shapefiles
+    /// use.
+    Esri,
+}
+
+impl Orientation {
+    fn exterior_clockwise(self) -> bool {
+        self == Orientation::Esri
+    }
+}
+
+impl tg_poly {
+    /// Returns true if every ring of the polygon is wound as `orientation`
+    /// requires.
+    pub fn is_oriented(&self, orientation: Orientation) -> bool {
+        let clockwise = orientation.exterior_clockwise();
+        self.exterior().clockwise() == clockwise
+            && self.holes().all(|hole| hole.clockwise() != clockwise)
+    }
+
+    /// Returns a copy of the polygon with its rings rewound as
+    /// `orientation` requires. Rings that are already wound correctly are
+    /// shared with this polygon rather than copied.
+    pub fn oriented(&self, orientation: Orientation) -> Poly {
+        let clockwise = orientation.exterior_clockwise();
+        let exterior = rewound(self.exterior(), clockwise);
+        let holes: Vec<_> = self
+            .holes()
+            .map(|hole| (hole, rewound(hole, !clockwise)))
+            .collect();
+        let hole_refs: Vec<&tg_ring> = holes
+            .iter()
+            .map(|(hole, fixed)| fixed.as_deref().unwrap_or(hole))
+            .collect();
+        Poly::new(exterior.as_deref().unwrap_or(self.exterior()), &hole_refs)
+    }
+}
+
+/// Returns the ring reversed if it isn't wound as wanted.
+fn rewound(ring: &tg_ring, clockwise: bool) -> Option<Ring> {
+    if ring.clockwise() == clockwise {
+        return None;
+    }
+    let mut points = ring.points().to_vec();
+    points.reverse();
+    Some(Ring::new(&points))
+}
+
+impl tg_geom {
+    /// Returns true if every polygon in the geometry is wound as
+    /// `orientation` requires. Geometries without polygons always are.
+    pub fn is_oriented(&self, orientation: Orientation) -> bool {
+        match self.geom_type() {
+            tg_geom_type::TG_POLYGON | tg_geom_type::TG_MULTIPOLYGON => {
+                polys(self).iter().all(|poly| poly.is_oriented(orientation))
+            }
+            tg_geom_type::TG_GEOMETRYCOLLECTION => self
+                .geometries()
+                .all(|member| member.is_oriented(orientation)),
+            _ => true,
+        }
+    }
+
+    /// Returns a copy of the geometry with the rings of its polygons rewound
+    /// as `orientation` requires, keeping Z and M coordinates in step.
+    ///
+    /// Geometries that are already oriented are returned as a new reference
+    /// to the same geometry. Rebuilt geometries are plain geometries: the
+    /// members of a GeoJSON Feature are dropped, which
+    /// [`to_geojson_rfc7946()`][tg_geom::to_geojson_rfc7946] works around.
+    pub fn oriented(&self, orientation: Orientation) -> Geom {
+        if self.is_oriented(orientation) {
+            return self.to_owned();
+        }
+        match self.geom_type() {
+            tg_geom_type::TG_POLYGON | tg_geom_type::TG_MULTIPOLYGON => {
+                let source = polys(self);
+                let oriented: Vec<Poly> = source.iter().map(|p| p.oriented(orientation)).collect();
+                let dims = self.dims();
+                let extra = reorder_extra(&source, &oriented, dims.extra(), self.extra_coords());
+                if self.geom_type() == tg_geom_type::TG_POLYGON {
+                    Geom::new_polygon_ex(&oriented[0], dims, &extra)
+                } else {
+                    let refs: Vec<&tg_poly> = oriented.iter().map(|p| &**p).collect();
+                    Geom::new_multipolygon_ex(&refs, dims, &extra)
+                }
+            }
+            tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                let members: Vec<Geom> = self
+                    .geometries()
+                    .map(|member| member.oriented(orientation))
+                    .collect();
+                let refs: Vec<&tg_geom> = members.iter().map(|g| &**g).collect();
+                Geom::new_geometrycollection(&refs)
+            }
+            _ => self.to_owned(),
+        }
+    }
+
+    /// Writes the geometry as GeoJSON with the winding RFC 7946 requires,
+    /// counterclockwise exteriors and clockwise holes.
+    ///
+    /// Unlike [`oriented()`][tg_geom::oriented], this keeps the members of
+    /// Features and FeatureCollections, such as `id` and `properties`.
+    pub fn to_geojson_rfc7946(&self) -> String {
+        if self.is_oriented(Orientation::Rfc7946) {
+            return self.to_geojson();
+        }
+        if self.is_feature() {
+            let geometry = self.oriented(Orientation::Rfc7946).to_geojson();
+            return object("Feature", "geometry", &geometry, self.extra_json());
+        }
+        if unsafe { tg_geom_is_featurecollection(self) } {
+            let features: Vec<String> =
+                self.geometries().map(tg_geom::to_geojson_rfc7946).collect();
+            let features = format!("[{}]", features.join(","));
+            return object(
+                "FeatureCollection",
+                "features",
+                &features,
+                self.extra_json(),
+            );
+        }
+        self.oriented(Orientation::Rfc7946).to_geojson()
+    }
+}
+
+/// Writes a GeoJSON object with a type, one member, and the members of
+/// `extra`, a JSON object.
+fn object(kind: &str, key: &str, value: &str, extra: Option<&str>) -> String {
+    let extra = extra
+        .map(|json| {
+            let json = json.trim();
+            let json = json.strip_prefix('{').unwrap_or(json);
+            json.strip_suffix('}').unwrap_or(json).trim()
+        })
+        .unwrap_or("");
+    let sep = if extra.is_empty() { "" } else { "," };
+    format!(
+        r#"{{"type":"{}","{}":{}{}{}}}"#,
+        kind, key, value, sep, extra
+    )
+}
+
+fn polys(geom: &tg_geom) -> Vec<&tg_poly> {
+    match geom.poly() {
+        Some(poly) => vec![poly],
+        None => geom.polys().collect(),
+    }
+}
+
+/// Reorders the extra coordinates of `source` to follow the points of
+/// `oriented`, reversing them for each ring that was reversed. Extra
+/// coordinates that don't line up with the points are dropped.
+fn reorder_extra(
+    source: &[&tg_poly],
+    oriented: &[Poly],
+    per_point: usize,
+    extra: &[f64],
+) -> Vec<f64> {
+    let rings = |poly: &tg_poly| -> Vec<(usize, bool)> {
+        std::iter::once(poly.exterior())
+            .chain(poly.holes())
+            .map(|ring| (ring.points().len(), ring.clockwise()))
+            .collect()
+    };
+    let before: Vec<(usize, bool)> = source.iter().flat_map(|p| rings(p)).collect();
+    let after: Vec<(usize, bool)> = oriented.iter().flat_map(|p| rings(p)).collect();
+    let total: usize = before.iter().map(|(n, _)| n).sum();
+    if per_point == 0 || extra.len() != total * per_point {
+        return Vec::new();
+    }
+    let mut out = Vec::with_capacity(extra.len());
+    let mut offset = 0;
+    for ((n, was), (_, now)) in before.into_iter().zip(after) {
+        let ring = &extra[offset..offset + n * per_point];
+        if was == now {
+            out.extend_from_slice(ring);
+        } else {
+            for point in ring.chunks(per_point).rev() {
+                out.extend_from_slice(point);
+            }
+        }
+        offset += n * per_point;
+    }
+    out
+}
@@ -0,0 +1,40 @@
+//! Parallel helpers that fan tg calls out across the rayon thread pool.
+//!
+//! Sharing geometries between threads is only sound when their reference
+//! counters are atomic, so these helpers need the `atomics` feature as well as
+//! `rayon`. A build without `atomics` (which compiles tg with `TG_NOATOMICS`)
+//! leaves this module out.
+
+use rayon::prelude::*;
+
+use crate::{tg_point, Error, Geom};
+
+/// The number of points each rayon task tests in
+/// [`par_contains_points()`]. Large enough to amortize scheduling, small
+/// enough to balance work across threads.
+const POINTS_PER_TASK: usize = 4096;
+
+/// Tests `geom` against each of `others` in parallel with
+/// [`tg_geom_intersects()`][crate::GeometryPredicates::tg_geom_intersects],
+/// returning results in input order.
+pub fn par_intersects(geom: &Geom, others: &[Geom]) -> Vec<bool> {
+    others
+        .par_iter()
+        .map(|other| geom.intersects(other))
+        .collect()
+}
+
+/// Like [`contains_points()`][crate::tg_geom::contains_points], but splits
+/// the points into chunks that are tested in parallel.
+pub fn par_contains_points(geom: &Geom, points: &[tg_point]) -> Vec<bool> {
+    points
+        .par_chunks(POINTS_PER_TASK)
+        .flat_map_iter(|chunk| geom.contains_points(chunk))
+        .collect()
+}
+
+/// Parses many Well-known text (WKT) strings in parallel, returning results
+/// in input order.
+pub fn par_parse_wkt(wkts: &[&str]) -> Vec<Result<Geom, Error>> {
+    wkts.par_iter().map(|wkt| Geom::parse_wkt(wkt)).collect()
+}
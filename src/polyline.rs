@@ -0,0 +1,131 @@
+//! Google's Encoded Polyline Algorithm Format.
+//!
+//! Each point is written latitude first, as a pair of deltas from the
+//! previous point, rounded to `precision` decimal digits. Google uses a
+//! precision of 5; OSRM and Valhalla use 6. tg points are the other way
+//! around, with longitude in `x` and latitude in `y`, and the conversion
+//! happens here.
+//!
+//! Some services append a third value to each point for elevation. That's
+//! read and written by [`Geom::from_polyline_z()`] and
+//! [`to_polyline_z()`][tg_geom::to_polyline_z], which keep it as the Z
+//! coordinate of a LineString.
+
+use crate::{tg_geom, tg_geom_type, tg_line, tg_point, Dims, Error, Geom, Line};
+
+impl Line {
+    /// Decodes an encoded polyline with the given precision.
+    pub fn from_polyline(encoded: &str, precision: u32) -> Result<Line, Error> {
+        let values = decode(encoded, &[precision, precision])?;
+        let points: Vec<tg_point> = values
+            .chunks(2)
+            .map(|v| tg_point { x: v[1], y: v[0] })
+            .collect();
+        Ok(Line::new(&points))
+    }
+}
+
+impl tg_line {
+    /// Encodes the line as a polyline with the given precision.
+    pub fn to_polyline(&self, precision: u32) -> String {
+        let values = self.points().iter().flat_map(|p| [p.y, p.x]);
+        encode(values, &[precision, precision])
+    }
+}
+
+impl Geom {
+    /// Decodes an encoded polyline whose points carry a third value, as a
+    /// LineString with Z coordinates.
+    pub fn from_polyline_z(encoded: &str, precision: u32, z_precision: u32) -> Result<Geom, Error> {
+        let values = decode(encoded, &[precision, precision, z_precision])?;
+        let points: Vec<tg_point> = values
+            .chunks(3)
+            .map(|v| tg_point { x: v[1], y: v[0] })
+            .collect();
+        let z: Vec<f64> = values.chunks(3).map(|v| v[2]).collect();
+        Ok(Geom::new_linestring_ex(&Line::new(&points), Dims::Xyz, &z))
+    }
+}
+
+impl tg_geom {
+    /// Encodes a LineString as a polyline with a third value per point for
+    /// its Z coordinates, which are zero if it has none.
+    ///
+    /// Returns an error for other geometry types.
+    pub fn to_polyline_z(&self, precision: u32, z_precision: u32) -> Result<String, Error> {
+        let line = match self.geom_type() {
+            tg_geom_type::TG_LINESTRING => self.line(),
+            _ => None,
+        };
+        let line = line.ok_or_else(|| Error::new("polyline: geometry is not a LineString"))?;
+        let dims = self.dims();
+        let extra = self.extra_coords();
+        let values = line.points().iter().enumerate().flat_map(|(i, p)| {
+            let z = match dims.has_z() {
+                true => extra.get(i * dims.extra()).copied().unwrap_or(0.0),
+                false => 0.0,
+            };
+            [p.y, p.x, z]
+        });
+        Ok(encode(values, &[precision, precision, z_precision]))
+    }
+}
+
+/// Encodes a flat series of values, cycling through `precisions` for the
+/// values of each point.
+fn encode(values: impl Iterator<Item = f64>, precisions: &[u32]) -> String {
+    let scales: Vec<f64> = precisions.iter().map(|&p| 10f64.powi(p as i32)).collect();
+    let mut prev = vec![0i64; precisions.len()];
+    let mut out = String::new();
+    for (i, v) in values.enumerate() {
+        let axis = i % precisions.len();
+        let v = (v * scales[axis]).round() as i64;
+        let delta = v.wrapping_sub(prev[axis]);
+        prev[axis] = v;
+        let mut bits = ((delta << 1) ^ (delta >> 63)) as u64;
+        while bits >= 0x20 {
+            out.push(char::from((0x20 | (bits & 0x1f)) as u8 + 63));
+            bits >>= 5;
+        }
+        out.push(char::from(bits as u8 + 63));
+    }
+    out
+}
+
+/// Decodes a flat series of values, cycling through `precisions` for the
+/// values of each point.
+fn decode(encoded: &str, precisions: &[u32]) -> Result<Vec<f64>, Error> {
+    let scales: Vec<f64> = precisions.iter().map(|&p| 10f64.powi(p as i32)).collect();
+    let mut prev = vec![0i64; precisions.len()];
+    let mut values = Vec::new();
+    let mut bytes = encoded.trim().bytes().peekable();
+    while bytes.peek().is_some() {
+        let axis = values.len() % precisions.len();
+        let mut bits = 0u64;
+        let mut shift = 0;
+        loop {
+            let b = bytes
+                .next()
+                .ok_or_else(|| Error::new("polyline: unexpected end of input"))?;
+            let chunk = match b.checked_sub(63) {
+                Some(chunk) if chunk < 0x40 => u64::from(chunk),
+                _ => return Err(Error::new("polyline: invalid character")),
+            };
+            if shift >= 64 {
+                return Err(Error::new("polyline: value is too long"));
+            }
+            bits |= (chunk & 0x1f) << shift;
+            shift += 5;
+            if chunk & 0x20 == 0 {
+                break;
+            }
+        }
+        let delta = (bits >> 1) as i64 ^ -((bits & 1) as i64);
+        prev[axis] = prev[axis].wrapping_add(delta);
+        values.push(prev[axis] as f64 / scales[axis]);
+    }
+    if values.len() % precisions.len() != 0 {
+        return Err(Error::new("polyline: incomplete point"));
+    }
+    Ok(values)
+}
@@ -0,0 +1,66 @@
+//! Inline rectangle math.
+//!
+//! These mirror the [`RectFuncs`][crate::RectFuncs] functions, but are
+//! implemented in Rust so they can be inlined into hot loops instead of
+//! crossing the FFI boundary for a handful of comparisons.
+
+use crate::{tg_point, tg_rect};
+
+impl tg_rect {
+    /// Returns the smallest rectangle containing both rectangles.
+    pub fn expand(self, other: tg_rect) -> tg_rect {
+        tg_rect {
+            min: tg_point {
+                x: self.min.x.min(other.min.x),
+                y: self.min.y.min(other.min.y),
+            },
+            max: tg_point {
+                x: self.max.x.max(other.max.x),
+                y: self.max.y.max(other.max.y),
+            },
+        }
+    }
+
+    /// Returns the smallest rectangle containing the rectangle and a point.
+    pub fn expand_point(self, point: tg_point) -> tg_rect {
+        self.expand(tg_rect {
+            min: point,
+            max: point,
+        })
+    }
+
+    pub fn center(self) -> tg_point {
+        tg_point {
+            x: (self.min.x + self.max.x) / 2.0,
+            y: (self.min.y + self.max.y) / 2.0,
+        }
+    }
+
+    pub fn area(self) -> f64 {
+        (self.max.x - self.min.x) * (self.max.y - self.min.y)
+    }
+
+    /// Tests whether the rectangles intersect, including touching edges.
+    pub fn intersects_rect(self, other: tg_rect) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+
+    /// Tests whether the point is inside the rectangle, including its edges.
+    pub fn intersects_point(self, point: tg_point) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    /// Returns the Euclidean distance from the point to the nearest point of
+    /// the rectangle, which is zero when the point is inside.
+    pub fn distance_to_point(self, point: tg_point) -> f64 {
+        let dx = (self.min.x - point.x).max(point.x - self.max.x).max(0.0);
+        let dy = (self.min.y - point.y).max(point.y - self.max.y).max(0.0);
+        dx.hypot(dy)
+    }
+}
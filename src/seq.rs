@@ -0,0 +1,208 @@
+//! Line-oriented geometry streams: GeoJSON Text Sequences (RFC 8142) and
+//! one-WKT-per-line text.
+
+use std::io::{self, BufRead, Write};
+
+use crate::GeometryParsing::{tg_parse_geojsonn_ix, tg_parse_wktn_ix};
+use crate::{tg_geom, tg_index, Geom, ReadError};
+
+/// The ASCII record separator that starts each GeoJSON text sequence record.
+const RS: u8 = 0x1e;
+
+#[derive(Clone, Copy)]
+enum Format {
+    GeoJsonSeq,
+    Wkt,
+}
+
+/// Iterator returned by [`read_geojsonseq()`] and [`read_wkt_lines()`].
+///
+/// Yields one geometry per record. A record that tg rejects is reported as
+/// [`ReadError::Record`] with the line it starts on, and reading continues
+/// with the next record. An I/O error ends the iteration.
+pub struct RecordReader<R> {
+    reader: R,
+    format: Format,
+    ix: tg_index,
+    line: usize,
+    buf: Vec<u8>,
+    /// Whether records are delimited by record separators rather than line
+    /// feeds. Decided by the first record of a GeoJSON text sequence.
+    separated: Option<bool>,
+    /// A record that has been read up to the start of the next one.
+    pending: Option<(usize, Vec<u8>)>,
+    done: bool,
+}
+
+/// Reads a GeoJSON Text Sequence (RFC 8142), one geometry per record.
+///
+/// Records are delimited by the ASCII record separator, and may span several
+/// lines. Input without record separators is read as newline-delimited
+/// GeoJSON, one geometry per line. Blank records are skipped.
+pub fn read_geojsonseq<R: BufRead>(reader: R) -> RecordReader<R> {
+    read_geojsonseq_ix(reader, tg_index::TG_DEFAULT)
+}
+
+/// Like [`read_geojsonseq()`], using the provided indexing option.
+pub fn read_geojsonseq_ix<R: BufRead>(reader: R, ix: tg_index) -> RecordReader<R> {
+    RecordReader::new(reader, Format::GeoJsonSeq, ix)
+}
+
+/// Reads Well-known text (WKT), one geometry per line. Blank lines are
+/// skipped.
+pub fn read_wkt_lines<R: BufRead>(reader: R) -> RecordReader<R> {
+    read_wkt_lines_ix(reader, tg_index::TG_DEFAULT)
+}
+
+/// Like [`read_wkt_lines()`], using the provided indexing option.
+pub fn read_wkt_lines_ix<R: BufRead>(reader: R, ix: tg_index) -> RecordReader<R> {
+    RecordReader::new(reader, Format::Wkt, ix)
+}
+
+impl<R: BufRead> RecordReader<R> {
+    fn new(reader: R, format: Format, ix: tg_index) -> RecordReader<R> {
+        RecordReader {
+            reader,
+            format,
+            ix,
+            line: 0,
+            buf: Vec::new(),
+            separated: match format {
+                Format::GeoJsonSeq => None,
+                Format::Wkt => Some(false),
+            },
+            pending: None,
+            done: false,
+        }
+    }
+
+    /// Reads the next record, skipping blank lines, and returns its first line
+    /// number and contents.
+    fn next_record(&mut self) -> io::Result<Option<(usize, Vec<u8>)>> {
+        loop {
+            self.buf.clear();
+            if self.reader.read_until(b'\n', &mut self.buf)? == 0 {
+                return Ok(self.pending.take());
+            }
+            self.line += 1;
+            let line = self.buf.trim_ascii();
+            if line.is_empty() {
+                continue;
+            }
+            let starts_record = line[0] == RS;
+            let separated = *self.separated.get_or_insert(starts_record);
+            if !separated {
+                return Ok(Some((self.line, line.to_vec())));
+            }
+            if starts_record {
+                let record = strip_separators(line).to_vec();
+                if let Some(done) = self.pending.replace((self.line, record)) {
+                    return Ok(Some(done));
+                }
+            } else if let Some((_, record)) = &mut self.pending {
+                // A record that continues over several lines.
+                record.push(b'\n');
+                record.extend_from_slice(line);
+            }
+        }
+    }
+
+    fn parse(&self, record: &[u8]) -> *mut tg_geom {
+        let ptr = record.as_ptr().cast();
+        unsafe {
+            match self.format {
+                Format::GeoJsonSeq => tg_parse_geojsonn_ix(ptr, record.len(), self.ix),
+                Format::Wkt => tg_parse_wktn_ix(ptr, record.len(), self.ix),
+            }
+        }
+    }
+}
+
+/// Strips leading record separators and the whitespace around them.
+fn strip_separators(mut line: &[u8]) -> &[u8] {
+    while let [RS, rest @ ..] = line.trim_ascii_start() {
+        line = rest;
+    }
+    line.trim_ascii()
+}
+
+impl<R: BufRead> Iterator for RecordReader<R> {
+    type Item = Result<Geom, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let (line, record) = loop {
+            match self.next_record() {
+                Ok(Some((_, record))) if record.is_empty() => continue,
+                Ok(Some(record)) => break record,
+                Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(ReadError::Io(err)));
+                }
+            }
+        };
+        let geom = unsafe { Geom::from_raw(self.parse(&record)) };
+        Some(geom.map_err(|error| ReadError::Record { line, error }))
+    }
+}
+
+/// Writes geometries as a GeoJSON Text Sequence (RFC 8142), prefixing each
+/// record with the ASCII record separator and ending it with a line feed.
+pub fn write_geojsonseq<W, I>(writer: W, geoms: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: AsRef<tg_geom>,
+{
+    write_records(writer, geoms, tg_geom::to_geojson)
+}
+
+/// Writes geometries as a GeoJSON Text Sequence like [`write_geojsonseq()`],
+/// with polygons wound as RFC 7946 requires. See
+/// [`tg_geom::to_geojson_rfc7946()`].
+pub fn write_geojsonseq_rfc7946<W, I>(writer: W, geoms: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: AsRef<tg_geom>,
+{
+    write_records(writer, geoms, tg_geom::to_geojson_rfc7946)
+}
+
+fn write_records<W, I>(
+    mut writer: W,
+    geoms: I,
+    to_geojson: fn(&tg_geom) -> String,
+) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: AsRef<tg_geom>,
+{
+    for geom in geoms {
+        writer.write_all(&[RS])?;
+        writer.write_all(to_geojson(geom.as_ref()).as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Writes geometries as Well-known text (WKT), one per line.
+pub fn write_wkt_lines<W, I>(mut writer: W, geoms: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: AsRef<tg_geom>,
+{
+    for geom in geoms {
+        writer.write_all(geom.as_ref().to_wkt().as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,324 @@
+//! Owned, safe handles to [`tg_line`], [`tg_ring`] and [`tg_poly`].
+//!
+//! Like [`Geom`][crate::Geom], each handle dereferences to the underlying tg
+//! type, which carries the safe accessors, and clones share the underlying
+//! object through its reference counter.
+//!
+//! The constructors panic if tg fails to allocate.
+
+use std::fmt;
+use std::ops::Deref;
+use std::ptr::NonNull;
+
+use crate::LineFuncs::{
+    tg_line_clockwise, tg_line_clone, tg_line_free, tg_line_index_level_num_rects,
+    tg_line_index_level_rect, tg_line_index_num_levels, tg_line_index_spread, tg_line_length,
+    tg_line_memsize, tg_line_new_ix, tg_line_num_points, tg_line_points, tg_line_rect,
+};
+use crate::PolyFuncs::{
+    tg_poly_clockwise, tg_poly_clone, tg_poly_exterior, tg_poly_free, tg_poly_hole_at,
+    tg_poly_memsize, tg_poly_new, tg_poly_num_holes, tg_poly_rect,
+};
+use crate::RingFuncs::{
+    tg_ring_area, tg_ring_clockwise, tg_ring_clone, tg_ring_convex, tg_ring_free,
+    tg_ring_index_level_num_rects, tg_ring_index_level_rect, tg_ring_index_num_levels,
+    tg_ring_index_spread, tg_ring_memsize, tg_ring_new_ix, tg_ring_num_points, tg_ring_perimeter,
+    tg_ring_points, tg_ring_rect,
+};
+use crate::{tg_geom, tg_index, tg_line, tg_point, tg_poly, tg_rect, tg_ring};
+
+/// Wraps a pointer returned by a tg constructor, panicking if it is NULL.
+pub(crate) fn alloc<T>(ptr: *mut T) -> NonNull<T> {
+    NonNull::new(ptr).expect("tg: out of memory")
+}
+
+/// Builds a slice from a tg array and its length.
+///
+/// # Safety
+///
+/// `ptr` must point to `len` values that outlive the returned slice, or `len`
+/// must not be positive.
+pub(crate) unsafe fn slice<'a, T>(ptr: *const T, len: libc::c_int) -> &'a [T] {
+    match usize::try_from(len) {
+        Ok(len) if len > 0 && !ptr.is_null() => std::slice::from_raw_parts(ptr, len),
+        _ => &[],
+    }
+}
+
+pub(crate) fn c_int(len: usize) -> libc::c_int {
+    libc::c_int::try_from(len).expect("too many items for tg")
+}
+
+macro_rules! handle {
+    ($(#[$doc:meta])* $name:ident, $raw:ident, $clone:ident, $free:ident) => {
+        $(#[$doc])*
+        pub struct $name {
+            ptr: NonNull<$raw>,
+        }
+
+        // See the note on `Geom`.
+        #[cfg(feature = "atomics")]
+        unsafe impl Send for $name {}
+        #[cfg(feature = "atomics")]
+        unsafe impl Sync for $name {}
+
+        impl $name {
+            /// Takes ownership of an object allocated by tg.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be a non-NULL object allocated by tg that the
+            /// caller owns.
+            pub unsafe fn from_raw(ptr: *mut $raw) -> $name {
+                $name { ptr: alloc(ptr) }
+            }
+
+            /// Returns the underlying pointer, which stays owned by `self`.
+            pub fn as_ptr(&self) -> *const $raw {
+                self.ptr.as_ptr()
+            }
+
+            /// Releases ownership of the underlying object.
+            pub fn into_raw(self) -> *mut $raw {
+                let ptr = self.ptr.as_ptr();
+                std::mem::forget(self);
+                ptr
+            }
+        }
+
+        impl Clone for $name {
+            fn clone(&self) -> $name {
+                $name {
+                    ptr: alloc(unsafe { $clone(self.as_ptr()) }),
+                }
+            }
+        }
+
+        impl Drop for $name {
+            fn drop(&mut self) {
+                unsafe { $free(self.ptr.as_ptr()) }
+            }
+        }
+
+        impl Deref for $name {
+            type Target = $raw;
+
+            fn deref(&self) -> &$raw {
+                unsafe { self.ptr.as_ref() }
+            }
+        }
+
+        impl AsRef<$raw> for $name {
+            fn as_ref(&self) -> &$raw {
+                self
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_tuple(stringify!($name))
+                    .field(&self.as_geom().to_wkt())
+                    .finish()
+            }
+        }
+    };
+}
+
+handle!(
+    /// An owned line, freed with [`tg_line_free()`][tg_line_free].
+    Line,
+    tg_line,
+    tg_line_clone,
+    tg_line_free
+);
+
+handle!(
+    /// An owned ring, freed with [`tg_ring_free()`][tg_ring_free].
+    Ring,
+    tg_ring,
+    tg_ring_clone,
+    tg_ring_free
+);
+
+handle!(
+    /// An owned polygon, freed with [`tg_poly_free()`][tg_poly_free].
+    Poly,
+    tg_poly,
+    tg_poly_clone,
+    tg_poly_free
+);
+
+impl Line {
+    /// Creates a line from a series of points.
+    pub fn new(points: &[tg_point]) -> Line {
+        Line::new_ix(points, tg_index::TG_DEFAULT)
+    }
+
+    /// Creates a line from a series of points using the provided indexing
+    /// option.
+    pub fn new_ix(points: &[tg_point], ix: tg_index) -> Line {
+        let ptr = unsafe { tg_line_new_ix(points.as_ptr(), c_int(points.len()), ix) };
+        Line { ptr: alloc(ptr) }
+    }
+}
+
+impl Ring {
+    /// Creates a ring from a series of points. The ring is closed
+    /// automatically if the last point doesn't match the first.
+    pub fn new(points: &[tg_point]) -> Ring {
+        Ring::new_ix(points, tg_index::TG_DEFAULT)
+    }
+
+    /// Creates a ring from a series of points using the provided indexing
+    /// option.
+    pub fn new_ix(points: &[tg_point], ix: tg_index) -> Ring {
+        let ptr = unsafe { tg_ring_new_ix(points.as_ptr(), c_int(points.len()), ix) };
+        Ring { ptr: alloc(ptr) }
+    }
+}
+
+impl Poly {
+    /// Creates a polygon from an exterior ring and holes.
+    pub fn new(exterior: &tg_ring, holes: &[&tg_ring]) -> Poly {
+        let holes: Vec<*const tg_ring> = holes.iter().map(|h| *h as *const tg_ring).collect();
+        let ptr = unsafe { tg_poly_new(exterior, holes.as_ptr(), c_int(holes.len())) };
+        Poly { ptr: alloc(ptr) }
+    }
+}
+
+impl tg_line {
+    /// Upcasts the line to a LineString geometry.
+    pub fn as_geom(&self) -> &tg_geom {
+        unsafe { &*(self as *const tg_line).cast::<tg_geom>() }
+    }
+
+    pub fn points(&self) -> &[tg_point] {
+        unsafe { slice(tg_line_points(self), tg_line_num_points(self)) }
+    }
+
+    pub fn rect(&self) -> tg_rect {
+        unsafe { tg_line_rect(self) }
+    }
+
+    pub fn length(&self) -> f64 {
+        unsafe { tg_line_length(self) }
+    }
+
+    pub fn clockwise(&self) -> bool {
+        unsafe { tg_line_clockwise(self) }
+    }
+
+    pub fn memsize(&self) -> usize {
+        unsafe { tg_line_memsize(self) }
+    }
+
+    pub fn index_spread(&self) -> usize {
+        unsafe { tg_line_index_spread(self) }.max(0) as usize
+    }
+
+    pub fn index_num_levels(&self) -> usize {
+        unsafe { tg_line_index_num_levels(self) }.max(0) as usize
+    }
+
+    pub fn index_level_rects(&self, level: usize) -> Vec<tg_rect> {
+        let level = c_int(level);
+        let n = unsafe { tg_line_index_level_num_rects(self, level) };
+        (0..n)
+            .map(|i| unsafe { tg_line_index_level_rect(self, level, i) })
+            .collect()
+    }
+}
+
+impl tg_ring {
+    /// Upcasts the ring to a Polygon geometry.
+    pub fn as_geom(&self) -> &tg_geom {
+        unsafe { &*(self as *const tg_ring).cast::<tg_geom>() }
+    }
+
+    /// Upcasts the ring to a polygon without holes.
+    pub fn as_poly(&self) -> &tg_poly {
+        unsafe { &*(self as *const tg_ring).cast::<tg_poly>() }
+    }
+
+    pub fn points(&self) -> &[tg_point] {
+        unsafe { slice(tg_ring_points(self), tg_ring_num_points(self)) }
+    }
+
+    pub fn rect(&self) -> tg_rect {
+        unsafe { tg_ring_rect(self) }
+    }
+
+    pub fn area(&self) -> f64 {
+        unsafe { tg_ring_area(self) }
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        unsafe { tg_ring_perimeter(self) }
+    }
+
+    pub fn clockwise(&self) -> bool {
+        unsafe { tg_ring_clockwise(self) }
+    }
+
+    pub fn convex(&self) -> bool {
+        unsafe { tg_ring_convex(self) }
+    }
+
+    pub fn memsize(&self) -> usize {
+        unsafe { tg_ring_memsize(self) }
+    }
+
+    pub fn index_spread(&self) -> usize {
+        unsafe { tg_ring_index_spread(self) }.max(0) as usize
+    }
+
+    pub fn index_num_levels(&self) -> usize {
+        unsafe { tg_ring_index_num_levels(self) }.max(0) as usize
+    }
+
+    pub fn index_level_rects(&self, level: usize) -> Vec<tg_rect> {
+        let level = c_int(level);
+        let n = unsafe { tg_ring_index_level_num_rects(self, level) };
+        (0..n)
+            .map(|i| unsafe { tg_ring_index_level_rect(self, level, i) })
+            .collect()
+    }
+}
+
+impl tg_poly {
+    /// Upcasts the polygon to a Polygon geometry.
+    pub fn as_geom(&self) -> &tg_geom {
+        unsafe { &*(self as *const tg_poly).cast::<tg_geom>() }
+    }
+
+    pub fn exterior(&self) -> &tg_ring {
+        unsafe { &*tg_poly_exterior(self) }
+    }
+
+    pub fn holes(&self) -> impl ExactSizeIterator<Item = &tg_ring> + '_ {
+        let n = unsafe { tg_poly_num_holes(self) }.max(0);
+        (0..n).map(move |i| unsafe { &*tg_poly_hole_at(self, i) })
+    }
+
+    pub fn rect(&self) -> tg_rect {
+        unsafe { tg_poly_rect(self) }
+    }
+
+    /// Returns the area of the exterior less the area of the holes.
+    pub fn area(&self) -> f64 {
+        self.exterior().area() - self.holes().map(tg_ring::area).sum::<f64>()
+    }
+
+    /// Returns the length of the boundary, the exterior and the holes.
+    pub fn perimeter(&self) -> f64 {
+        self.exterior().perimeter() + self.holes().map(tg_ring::perimeter).sum::<f64>()
+    }
+
+    pub fn clockwise(&self) -> bool {
+        unsafe { tg_poly_clockwise(self) }
+    }
+
+    pub fn memsize(&self) -> usize {
+        unsafe { tg_poly_memsize(self) }
+    }
+}
@@ -0,0 +1,795 @@
+//! ESRI Shapefile input.
+//!
+//! A shapefile is a set of files sharing a base name: the `.shp` file holds
+//! the geometries, the `.shx` file the offset of each record, and the `.dbf`
+//! file a dBASE table with one row of attributes per record. An optional
+//! `.cpg` file names the code page of the `.dbf` text. See the ESRI Shapefile
+//! Technical Description.
+//!
+//! ```no_run
+//! use tg_sys::ShpReader;
+//!
+//! let mut reader = ShpReader::open("counties.shp")?;
+//! for record in reader.records() {
+//!     let record = record?;
+//!     if let Some(geom) = record.geom() {
+//!         println!("{:?}: {}", record.attribute("NAME"), geom.to_wkt());
+//!     }
+//! }
+//! # Ok::<(), tg_sys::ReadError>(())
+//! ```
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::{
+    tg_geom_type, tg_index, tg_point, tg_rect, Dims, Error, Geom, Line, Poly, ReadError, Ring,
+    Value,
+};
+
+/// The size of the header shared by `.shp` and `.shx` files.
+const HEADER_SIZE: u64 = 100;
+
+/// The file code at the start of `.shp` and `.shx` files.
+const FILE_CODE: i32 = 9994;
+
+/// M values below this mean "no data".
+const NO_DATA: f64 = -1e38;
+
+/// The header of a `.shp` file.
+#[derive(Clone, Debug)]
+pub struct ShpHeader {
+    shape_type: i32,
+    rect: tg_rect,
+    len: u64,
+}
+
+impl ShpHeader {
+    /// Returns the geometry type that the file's shape type maps to, or
+    /// `None` for the null shape type and MultiPatch. Records with a single
+    /// part are read as a LineString or Polygon instead.
+    pub fn geometry_type(&self) -> Option<tg_geom_type> {
+        if self.shape_type == 31 {
+            return None;
+        }
+        match self.shape_type % 10 {
+            1 => Some(tg_geom_type::TG_POINT),
+            3 => Some(tg_geom_type::TG_MULTILINESTRING),
+            5 => Some(tg_geom_type::TG_MULTIPOLYGON),
+            8 => Some(tg_geom_type::TG_MULTIPOINT),
+            _ => None,
+        }
+    }
+
+    /// Returns the raw shape type, such as 5 for Polygon or 15 for
+    /// PolygonZ.
+    pub fn shape_type(&self) -> i32 {
+        self.shape_type
+    }
+
+    pub fn dims(&self) -> Dims {
+        shape_dims(self.shape_type)
+    }
+
+    /// Returns the bounding rectangle of every shape in the file.
+    pub fn rect(&self) -> tg_rect {
+        self.rect
+    }
+}
+
+/// The coordinates stored by a shape type. Z types carry optional M values,
+/// which are kept.
+fn shape_dims(shape_type: i32) -> Dims {
+    match shape_type {
+        11 | 13 | 15 | 18 => Dims::Xyzm,
+        21 | 23 | 25 | 28 => Dims::Xym,
+        _ => Dims::Xy,
+    }
+}
+
+/// The encoding of the text in a `.dbf` file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Encoding {
+    Utf8,
+    Latin1,
+    Windows1252,
+}
+
+/// The characters Windows-1252 puts in place of the C1 controls of
+/// ISO-8859-1, for bytes 0x80 to 0x9F. The five bytes it leaves undefined
+/// keep their control characters.
+const WINDOWS_1252: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+impl Encoding {
+    /// Returns the encoding of the code page named by a `.cpg` file, falling
+    /// back to UTF-8 for code pages that can't be decoded here.
+    fn from_cpg(cpg: &[u8]) -> Encoding {
+        let name: String = String::from_utf8_lossy(cpg)
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        match name.as_str() {
+            "ISO88591" | "88591" | "28591" | "LATIN1" => Encoding::Latin1,
+            "1252" | "CP1252" | "WINDOWS1252" | "ANSI1252" => Encoding::Windows1252,
+            _ => Encoding::Utf8,
+        }
+    }
+
+    /// Returns the encoding named by the language driver byte of a `.dbf`
+    /// header, as GDAL reads it, falling back to UTF-8 for drivers that are
+    /// unset or can't be decoded here.
+    fn from_ldid(ldid: u8) -> Encoding {
+        match ldid {
+            0x03 | 0x58 | 0x59 => Encoding::Windows1252,
+            0x57 => Encoding::Latin1,
+            _ => Encoding::Utf8,
+        }
+    }
+
+    /// Decodes text, replacing invalid UTF-8 sequences.
+    fn decode(self, raw: &[u8]) -> Cow<'_, str> {
+        match self {
+            Encoding::Utf8 => String::from_utf8_lossy(raw),
+            Encoding::Latin1 => raw.iter().map(|&b| char::from(b)).collect(),
+            Encoding::Windows1252 => raw
+                .iter()
+                .map(|&b| match b {
+                    0x80..=0x9f => WINDOWS_1252[usize::from(b - 0x80)],
+                    _ => char::from(b),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A column of the `.dbf` table.
+#[derive(Clone, Debug)]
+struct Field {
+    name: String,
+    field_type: u8,
+    offset: usize,
+    len: usize,
+    decimals: u8,
+}
+
+/// The attribute table of a shapefile.
+struct Dbf<R> {
+    reader: R,
+    fields: Vec<Field>,
+    records: u32,
+    header_len: u64,
+    record_len: usize,
+    encoding: Encoding,
+    buf: Vec<u8>,
+}
+
+impl<R: Read + Seek> Dbf<R> {
+    fn new(mut reader: R) -> Result<Dbf<R>, ReadError> {
+        let mut header = [0; 32];
+        reader.read_exact(&mut header)?;
+        let records = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let header_len = u16::from_le_bytes(header[8..10].try_into().unwrap());
+        let record_len = u16::from_le_bytes(header[10..12].try_into().unwrap());
+        let mut fields = Vec::new();
+        // Each record starts with a deletion flag.
+        let mut offset = 1;
+        loop {
+            let mut descriptor = [0; 32];
+            reader.read_exact(&mut descriptor[..1])?;
+            if descriptor[0] == 0x0d {
+                break;
+            }
+            reader.read_exact(&mut descriptor[1..])?;
+            let name = descriptor[..11].split(|&b| b == 0).next().unwrap();
+            let len = usize::from(descriptor[16]);
+            fields.push(Field {
+                name: String::from_utf8_lossy(name).trim().to_string(),
+                field_type: descriptor[11],
+                offset,
+                len,
+                decimals: descriptor[17],
+            });
+            offset += len;
+        }
+        if offset > usize::from(record_len) {
+            return Err(ReadError::Syntax {
+                offset: 10,
+                message: "dbf: fields overflow the record length".to_string(),
+            });
+        }
+        Ok(Dbf {
+            reader,
+            fields,
+            records,
+            header_len: header_len.into(),
+            record_len: record_len.into(),
+            encoding: Encoding::from_ldid(header[29]),
+            buf: Vec::new(),
+        })
+    }
+
+    /// Reads the attributes of record `n`, counting from zero, and whether
+    /// the row is marked as deleted.
+    fn record(&mut self, n: usize) -> io::Result<(bool, Vec<(String, Value)>)> {
+        if n >= self.records as usize {
+            return Ok((false, Vec::new()));
+        }
+        let pos = self.header_len + (n * self.record_len) as u64;
+        self.reader.seek(SeekFrom::Start(pos))?;
+        self.buf.resize(self.record_len, 0);
+        self.reader.read_exact(&mut self.buf)?;
+        let deleted = self.buf.first() == Some(&b'*');
+        let attributes = self
+            .fields
+            .iter()
+            .map(|field| {
+                let raw = &self.buf[field.offset..field.offset + field.len];
+                (field.name.clone(), field.value(raw, self.encoding))
+            })
+            .collect();
+        Ok((deleted, attributes))
+    }
+}
+
+impl Field {
+    /// Decodes a raw field value.
+    fn value(&self, raw: &[u8], encoding: Encoding) -> Value {
+        let text = encoding.decode(raw);
+        let trimmed = text.trim_matches(|c: char| c == ' ' || c == '\0');
+        match self.field_type {
+            b'C' if trimmed.is_empty() => Value::Null,
+            b'C' => Value::String(text.trim_end_matches([' ', '\0']).to_string()),
+            b'N' | b'F' => {
+                if self.decimals == 0 {
+                    if let Ok(v) = trimmed.parse() {
+                        return Value::Int(v);
+                    }
+                }
+                trimmed.parse().map_or(Value::Null, Value::Float)
+            }
+            b'L' => match trimmed {
+                "Y" | "y" | "T" | "t" => Value::Bool(true),
+                "N" | "n" | "F" | "f" => Value::Bool(false),
+                _ => Value::Null,
+            },
+            b'D' if trimmed.len() == 8 && trimmed.bytes().all(|b| b.is_ascii_digit()) => {
+                Value::DateTime(format!(
+                    "{}-{}-{}",
+                    &trimmed[..4],
+                    &trimmed[4..6],
+                    &trimmed[6..]
+                ))
+            }
+            b'D' => Value::Null,
+            b'I' if raw.len() == 4 => {
+                Value::Int(i32::from_le_bytes(raw.try_into().unwrap()).into())
+            }
+            b'O' if raw.len() == 8 => Value::Float(f64::from_le_bytes(raw.try_into().unwrap())),
+            _ if trimmed.is_empty() => Value::Null,
+            _ => Value::String(trimmed.to_string()),
+        }
+    }
+}
+
+/// A single record read from a shapefile.
+#[derive(Clone, Debug)]
+pub struct ShpRecord {
+    index: usize,
+    geom: Option<Geom>,
+    attributes: Vec<(String, Value)>,
+    deleted: bool,
+}
+
+impl ShpRecord {
+    /// The position of the record within the file, counting from zero.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the record's geometry, or `None` for a null shape.
+    pub fn geom(&self) -> Option<&Geom> {
+        self.geom.as_ref()
+    }
+
+    pub fn into_geom(self) -> Option<Geom> {
+        self.geom
+    }
+
+    /// Returns the record's row of the `.dbf` table, which is empty if the
+    /// shapefile has none.
+    pub fn attributes(&self) -> &[(String, Value)] {
+        &self.attributes
+    }
+
+    /// Returns whether the record's `.dbf` row is marked as deleted. dBASE
+    /// keeps deleted rows until the table is packed, and most readers skip
+    /// them.
+    pub fn is_deleted(&self) -> bool {
+        self.deleted
+    }
+
+    /// Returns the value of the named column, ignoring ASCII case as dBASE
+    /// does.
+    pub fn attribute(&self, name: &str) -> Option<&Value> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+}
+
+/// Reads the records of a shapefile as tg geometries, with their
+/// attributes.
+///
+/// Records are read in order with [`records()`][ShpReader::records], or by
+/// position with [`record()`][ShpReader::record] when the `.shx` file is
+/// available.
+///
+/// Polygon rings are grouped the way the format describes them: clockwise
+/// rings are exteriors, and each counterclockwise ring is a hole in the
+/// smallest exterior that covers it. A counterclockwise ring that no exterior
+/// covers is kept as an exterior of its own. A record with one polygon
+/// becomes a Polygon, and one with several a MultiPolygon. Likewise a
+/// PolyLine with one part becomes a LineString.
+///
+/// M values that the format marks as "no data" are read as NaN. Records
+/// whose `.dbf` row is marked as deleted are still read; see
+/// [`ShpRecord::is_deleted()`].
+///
+/// `.dbf` text is decoded as UTF-8, or as ISO-8859-1 or Windows-1252 when
+/// [`open()`] finds a `.cpg` file naming it. Without a `.cpg` file, the
+/// language driver byte in the `.dbf` header can name either. Other code
+/// pages are read as UTF-8, with invalid sequences replaced.
+///
+/// [`open()`]: ShpReader::open
+pub struct ShpReader<R> {
+    shp: R,
+    shx: Option<R>,
+    dbf: Option<Dbf<R>>,
+    header: ShpHeader,
+    ix: tg_index,
+    buf: Vec<u8>,
+}
+
+impl ShpReader<BufReader<File>> {
+    /// Opens a `.shp` file, along with the `.shx`, `.dbf` and `.cpg` files
+    /// next to it if they exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ReadError> {
+        let path = path.as_ref();
+        let upper = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext == "SHP");
+        let sibling = |ext: &str| -> io::Result<Option<BufReader<File>>> {
+            let ext = match upper {
+                true => ext.to_ascii_uppercase(),
+                false => ext.to_string(),
+            };
+            match File::open(path.with_extension(ext)) {
+                Ok(file) => Ok(Some(BufReader::new(file))),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err),
+            }
+        };
+        let shp = sibling("shp")?.ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+        let mut reader = ShpReader::new(shp, sibling("shx")?, sibling("dbf")?)?;
+        if let (Some(dbf), Some(mut cpg)) = (&mut reader.dbf, sibling("cpg")?) {
+            let mut name = Vec::new();
+            cpg.read_to_end(&mut name)?;
+            dbf.encoding = Encoding::from_cpg(&name);
+        }
+        Ok(reader)
+    }
+}
+
+impl<R: Read + Seek> ShpReader<R> {
+    /// Reads the headers of a shapefile's `.shp` file, and optionally its
+    /// `.shx` and `.dbf` files.
+    pub fn new(shp: R, shx: Option<R>, dbf: Option<R>) -> Result<Self, ReadError> {
+        ShpReader::with_index(shp, shx, dbf, tg_index::TG_DEFAULT)
+    }
+
+    /// Creates a reader that indexes each geometry with the provided indexing
+    /// option.
+    pub fn with_index(
+        mut shp: R,
+        shx: Option<R>,
+        dbf: Option<R>,
+        ix: tg_index,
+    ) -> Result<Self, ReadError> {
+        let mut header = [0; HEADER_SIZE as usize];
+        shp.read_exact(&mut header)?;
+        if i32::from_be_bytes(header[..4].try_into().unwrap()) != FILE_CODE {
+            return Err(ReadError::Syntax {
+                offset: 0,
+                message: "not a shapefile".to_string(),
+            });
+        }
+        let len = u64::from(u32::from_be_bytes(header[24..28].try_into().unwrap())) * 2;
+        let f64_at = |i: usize| f64::from_le_bytes(header[i..i + 8].try_into().unwrap());
+        let header = ShpHeader {
+            shape_type: i32::from_le_bytes(header[32..36].try_into().unwrap()),
+            rect: tg_rect {
+                min: tg_point {
+                    x: f64_at(36),
+                    y: f64_at(44),
+                },
+                max: tg_point {
+                    x: f64_at(52),
+                    y: f64_at(60),
+                },
+            },
+            len,
+        };
+        Ok(ShpReader {
+            shp,
+            shx,
+            dbf: dbf.map(Dbf::new).transpose()?,
+            header,
+            ix,
+            buf: Vec::new(),
+        })
+    }
+
+    pub fn header(&self) -> &ShpHeader {
+        &self.header
+    }
+
+    /// Returns the names of the `.dbf` columns.
+    pub fn fields(&self) -> impl ExactSizeIterator<Item = &str> + '_ {
+        let fields = self.dbf.as_ref().map_or(&[][..], |dbf| &dbf.fields);
+        fields.iter().map(|field| field.name.as_str())
+    }
+
+    /// Returns an iterator over every record, in order.
+    pub fn records(&mut self) -> ShpRecords<'_, R> {
+        ShpRecords {
+            reader: self,
+            offset: HEADER_SIZE,
+            index: 0,
+        }
+    }
+
+    /// Reads record `n`, counting from zero, using the `.shx` file to find
+    /// it. Returns `None` if `n` is past the last record.
+    ///
+    /// Returns an error if the reader has no `.shx` file.
+    pub fn record(&mut self, n: usize) -> Result<Option<ShpRecord>, ReadError> {
+        let shx = self.shx.as_mut().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "record() needs the .shx file")
+        })?;
+        shx.seek(SeekFrom::Start(HEADER_SIZE + 8 * n as u64))?;
+        let mut entry = [0; 8];
+        match shx.read_exact(&mut entry) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        let offset = u64::from(u32::from_be_bytes(entry[..4].try_into().unwrap())) * 2;
+        Ok(self.read_record(offset, n)?.map(|(_, record)| record))
+    }
+
+    /// Reads the record at byte `offset` of the `.shp` file, returning the
+    /// offset of the next one.
+    fn read_record(
+        &mut self,
+        offset: u64,
+        index: usize,
+    ) -> Result<Option<(u64, ShpRecord)>, ReadError> {
+        if offset >= self.header.len {
+            return Ok(None);
+        }
+        self.shp.seek(SeekFrom::Start(offset))?;
+        let mut prefix = [0; 8];
+        match self.shp.read_exact(&mut prefix) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        let len = u64::from(u32::from_be_bytes(prefix[4..].try_into().unwrap())) * 2;
+        self.buf.clear();
+        (&mut self.shp).take(len).read_to_end(&mut self.buf)?;
+        if self.buf.len() as u64 != len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        let geom = Shape {
+            buf: &self.buf,
+            pos: 0,
+            ix: self.ix,
+        }
+        .geom()
+        .map_err(|error| ReadError::Feature { index, error })?;
+        let (deleted, attributes) = match &mut self.dbf {
+            Some(dbf) => dbf.record(index)?,
+            None => (false, Vec::new()),
+        };
+        let record = ShpRecord {
+            index,
+            geom,
+            attributes,
+            deleted,
+        };
+        Ok(Some((offset + 8 + len, record)))
+    }
+}
+
+/// Iterator returned by [`ShpReader::records()`].
+///
+/// A record whose shape can't be decoded is reported as
+/// [`ReadError::Feature`] and reading continues with the next one. I/O
+/// errors end the iteration.
+pub struct ShpRecords<'a, R> {
+    reader: &'a mut ShpReader<R>,
+    offset: u64,
+    index: usize,
+}
+
+impl<R: Read + Seek> Iterator for ShpRecords<'_, R> {
+    type Item = Result<ShpRecord, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.index;
+        match self.reader.read_record(self.offset, index) {
+            Ok(Some((next, record))) => {
+                self.offset = next;
+                self.index += 1;
+                Some(Ok(record))
+            }
+            Ok(None) => None,
+            Err(err @ ReadError::Feature { .. }) => {
+                // The record was read in full, so the next one follows it.
+                self.offset += 8 + self.reader.buf.len() as u64;
+                self.index += 1;
+                Some(Err(err))
+            }
+            Err(err) => {
+                self.offset = u64::MAX;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Decodes the contents of one `.shp` record.
+struct Shape<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    ix: tg_index,
+}
+
+impl Shape<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], Error> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.buf.len())
+            .ok_or_else(|| Error::new("shp: truncated record"))?;
+        let bytes = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn i32(&mut self) -> Result<i32, Error> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f64(&mut self) -> Result<f64, Error> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Reads a count, bounded by the remaining bytes, given the minimum
+    /// size of each item.
+    fn count(&mut self, item_size: usize) -> Result<usize, Error> {
+        let n = usize::try_from(self.i32()?).map_err(|_| Error::new("shp: negative count"))?;
+        if n.saturating_mul(item_size) > self.buf.len() - self.pos {
+            return Err(Error::new("shp: count exceeds record size"));
+        }
+        Ok(n)
+    }
+
+    fn points(&mut self, n: usize) -> Result<Vec<tg_point>, Error> {
+        (0..n)
+            .map(|_| {
+                Ok(tg_point {
+                    x: self.f64()?,
+                    y: self.f64()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Reads an optional range and array of `n` values, as stored for Z and
+    /// M. Returns `None` if the record ends first.
+    fn measures(&mut self, n: usize) -> Result<Option<Vec<f64>>, Error> {
+        if self.pos == self.buf.len() {
+            return Ok(None);
+        }
+        self.take(16)?;
+        let values = (0..n).map(|_| self.f64()).collect::<Result<_, _>>()?;
+        Ok(Some(values))
+    }
+
+    /// Interleaves Z and M values for the points in `range`, as tg expects.
+    fn extra(
+        dims: Dims,
+        z: &Option<Vec<f64>>,
+        m: &Option<Vec<f64>>,
+        range: std::ops::Range<usize>,
+    ) -> Vec<f64> {
+        let get = |values: &Option<Vec<f64>>, i: usize| {
+            let v = values.as_ref().map_or(f64::NAN, |v| v[i]);
+            if v < NO_DATA {
+                f64::NAN
+            } else {
+                v
+            }
+        };
+        let mut extra = Vec::with_capacity(range.len() * dims.extra());
+        for i in range {
+            if dims.has_z() {
+                extra.push(get(z, i));
+            }
+            if dims.has_m() {
+                extra.push(get(m, i));
+            }
+        }
+        extra
+    }
+
+    fn geom(mut self) -> Result<Option<Geom>, Error> {
+        let shape_type = self.i32()?;
+        let mut dims = shape_dims(shape_type);
+        if shape_type == 0 {
+            return Ok(None);
+        }
+        if shape_type == 31 {
+            return Err(Error::new("shp: MultiPatch shapes are not supported"));
+        }
+        if shape_type % 10 == 1 {
+            let point = self.points(1)?[0];
+            let (z, m) = match dims {
+                Dims::Xyzm => (Some(vec![self.f64()?]), self.measures_point()?),
+                Dims::Xym => (None, Some(vec![self.f64()?])),
+                _ => (None, None),
+            };
+            if dims == Dims::Xyzm && m.is_none() {
+                dims = Dims::Xyz;
+            }
+            let extra = Shape::extra(dims, &z, &m, 0..1);
+            return Ok(Some(Geom::new_point_ex(point, dims, &extra)));
+        }
+
+        // Every other shape starts with a bounding box.
+        self.take(32)?;
+        let parts = match shape_type % 10 {
+            3 | 5 => {
+                let nparts = self.count(4)?;
+                let nparts_points = self.count(16)?;
+                let starts = (0..nparts)
+                    .map(|_| self.count(0))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Some((starts, nparts_points))
+            }
+            8 => None,
+            _ => {
+                return Err(Error::new(format!(
+                    "shp: unsupported shape type {}",
+                    shape_type
+                )))
+            }
+        };
+        let n = match &parts {
+            Some((_, n)) => *n,
+            None => self.count(16)?,
+        };
+        let points = self.points(n)?;
+        let z = match dims.has_z() {
+            true => self.measures(n)?,
+            false => None,
+        };
+        let m = match dims.has_m() {
+            true => self.measures(n)?,
+            false => None,
+        };
+        if dims == Dims::Xyzm && m.is_none() {
+            dims = Dims::Xyz;
+        }
+
+        let (starts, _) = match parts {
+            Some(parts) => parts,
+            None => {
+                let extra = Shape::extra(dims, &z, &m, 0..n);
+                return Ok(Some(Geom::new_multipoint_ex(&points, dims, &extra)));
+            }
+        };
+        let mut ranges = Vec::with_capacity(starts.len());
+        for (i, &start) in starts.iter().enumerate() {
+            let end = starts.get(i + 1).copied().unwrap_or(n);
+            if start > end || end > n {
+                return Err(Error::new("shp: invalid part offsets"));
+            }
+            ranges.push(start..end);
+        }
+        if ranges.is_empty() {
+            return Ok(None);
+        }
+
+        if shape_type % 10 == 3 {
+            let lines: Vec<Line> = ranges
+                .iter()
+                .map(|range| Line::new_ix(&points[range.clone()], self.ix))
+                .collect();
+            let extra = Shape::extra(dims, &z, &m, 0..n);
+            return Ok(Some(match &lines[..] {
+                [line] => Geom::new_linestring_ex(line, dims, &extra),
+                lines => {
+                    let lines: Vec<_> = lines.iter().map(|l| &**l).collect();
+                    Geom::new_multilinestring_ex(&lines, dims, &extra)
+                }
+            }));
+        }
+
+        let rings: Vec<Ring> = ranges
+            .iter()
+            .map(|range| Ring::new_ix(&points[range.clone()], self.ix))
+            .collect();
+        let groups = group_rings(&rings);
+        let mut extra = Vec::new();
+        let polys: Vec<Poly> = groups
+            .iter()
+            .map(|group| {
+                for &i in group {
+                    extra.extend(Shape::extra(dims, &z, &m, ranges[i].clone()));
+                }
+                let holes: Vec<_> = group[1..].iter().map(|&i| &*rings[i]).collect();
+                Poly::new(&rings[group[0]], &holes)
+            })
+            .collect();
+        Ok(Some(match &polys[..] {
+            [poly] => Geom::new_polygon_ex(poly, dims, &extra),
+            polys => {
+                let polys: Vec<_> = polys.iter().map(|p| &**p).collect();
+                Geom::new_multipolygon_ex(&polys, dims, &extra)
+            }
+        }))
+    }
+
+    /// Reads the optional M value of a PointZ.
+    fn measures_point(&mut self) -> Result<Option<Vec<f64>>, Error> {
+        if self.pos == self.buf.len() {
+            return Ok(None);
+        }
+        Ok(Some(vec![self.f64()?]))
+    }
+}
+
+/// Groups rings into polygons, returning the ring indexes of each polygon
+/// with its exterior first.
+///
+/// Clockwise rings are exteriors. Each counterclockwise ring is a hole in
+/// the smallest exterior that covers it, or an exterior itself if none does.
+fn group_rings(rings: &[Ring]) -> Vec<Vec<usize>> {
+    let (exteriors, holes): (Vec<usize>, Vec<usize>) =
+        (0..rings.len()).partition(|&i| rings[i].clockwise());
+    let mut groups: Vec<Vec<usize>> = exteriors.iter().map(|&i| vec![i]).collect();
+    for hole in holes {
+        let ring = rings[hole].as_geom();
+        let owner = groups
+            .iter_mut()
+            .filter(|group| rings[group[0]].as_geom().covers(ring))
+            .min_by(|a, b| rings[a[0]].area().total_cmp(&rings[b[0]].area()));
+        match owner {
+            Some(group) => group.push(hole),
+            None => groups.push(vec![hole]),
+        }
+    }
+    groups
+}
@@ -0,0 +1,177 @@
+//! Introspection of the indexes tg builds for lines and rings.
+//!
+//! tg indexes a line or ring by grouping runs of `spread` segments under a
+//! bounding rectangle, then grouping those rectangles again, level by level,
+//! up to a single root. [`IndexStats`] summarizes that structure and what it
+//! costs in memory, and [`recommend_index()`] measures which indexing option
+//! answers point-in-polygon queries fastest for a particular shape.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::{tg_geom, tg_index, tg_line, tg_point, tg_poly, tg_rect, tg_ring, Geom, Line, Ring};
+
+/// The shape of a line or ring index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexStats {
+    /// The number of points in the line or ring.
+    pub points: usize,
+    /// The number of segments grouped under each rectangle of the lowest
+    /// level, or 0 if there is no index.
+    pub spread: usize,
+    /// The levels of the index, from the root down.
+    pub levels: Vec<LevelStats>,
+    /// The memory used by the line or ring, in bytes.
+    pub memsize: usize,
+    /// The memory the same line or ring would use with `TG_NONE`.
+    pub unindexed_memsize: usize,
+}
+
+impl IndexStats {
+    /// Returns the memory taken by the index, in bytes.
+    pub fn index_memsize(&self) -> usize {
+        self.memsize.saturating_sub(self.unindexed_memsize)
+    }
+}
+
+/// One level of a line or ring index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelStats {
+    pub rects: usize,
+    /// The total area shared by pairs of rectangles, relative to the total
+    /// area of the rectangles. A search has to descend into every rectangle
+    /// that contains the query, so the higher this is, the more of the index
+    /// a query visits.
+    pub overlap_ratio: f64,
+}
+
+impl tg_line {
+    /// Summarizes the line's index.
+    pub fn index_stats(&self) -> IndexStats {
+        let levels = (0..self.index_num_levels())
+            .map(|level| LevelStats::new(self.index_level_rects(level)))
+            .collect();
+        IndexStats {
+            points: self.points().len(),
+            spread: self.index_spread(),
+            levels,
+            memsize: self.memsize(),
+            unindexed_memsize: Line::new_ix(self.points(), tg_index::TG_NONE).memsize(),
+        }
+    }
+}
+
+impl tg_ring {
+    /// Summarizes the ring's index.
+    ///
+    /// Only the natural index is made of levels of rectangles, so a ring
+    /// indexed with `TG_YSTRIPES` reports no levels but still reports the
+    /// memory its stripes take.
+    pub fn index_stats(&self) -> IndexStats {
+        let levels = (0..self.index_num_levels())
+            .map(|level| LevelStats::new(self.index_level_rects(level)))
+            .collect();
+        IndexStats {
+            points: self.points().len(),
+            spread: self.index_spread(),
+            levels,
+            memsize: self.memsize(),
+            unindexed_memsize: Ring::new_ix(self.points(), tg_index::TG_NONE).memsize(),
+        }
+    }
+}
+
+impl tg_poly {
+    /// Summarizes the index of each ring of the polygon, exterior first.
+    pub fn index_stats(&self) -> Vec<IndexStats> {
+        std::iter::once(self.exterior())
+            .chain(self.holes())
+            .map(tg_ring::index_stats)
+            .collect()
+    }
+}
+
+impl LevelStats {
+    fn new(mut rects: Vec<tg_rect>) -> LevelStats {
+        let total: f64 = rects.iter().map(|r| r.area()).sum();
+        // Sweep along x so that only rectangles that overlap in x are
+        // compared. Neighbouring segments of a line mostly stay close, so
+        // this stays near linear for natural indexes.
+        rects.sort_by(|a, b| a.min.x.total_cmp(&b.min.x));
+        let mut shared = 0.0;
+        for (i, a) in rects.iter().enumerate() {
+            for b in rects[i + 1..].iter().take_while(|b| b.min.x <= a.max.x) {
+                let w = a.max.x.min(b.max.x) - b.min.x;
+                let h = a.max.y.min(b.max.y) - a.min.y.max(b.min.y);
+                if w > 0.0 && h > 0.0 {
+                    shared += w * h;
+                }
+            }
+        }
+        LevelStats {
+            rects: rects.len(),
+            overlap_ratio: if total > 0.0 { shared / total } else { 0.0 },
+        }
+    }
+}
+
+/// The number of sample points each indexing option is timed on.
+const SAMPLES: usize = 1000;
+
+/// The number of times the samples are run, keeping the fastest.
+const ROUNDS: usize = 5;
+
+/// Returns the indexing option that answers point-in-polygon queries on
+/// `geom` the fastest.
+///
+/// The geometry is rebuilt with each of `TG_NONE`, `TG_NATURAL` and
+/// `TG_YSTRIPES`, and each copy is timed with
+/// [`intersects_xy()`][tg_geom::intersects_xy] on the same points, scattered
+/// deterministically over its bounding box. The result reflects this
+/// machine and this shape, so it is worth calling on a representative
+/// geometry rather than on every one.
+pub fn recommend_index(geom: &tg_geom) -> tg_index {
+    let points = sample_points(geom.rect(), SAMPLES);
+    let wkb = geom.to_wkb();
+    let mut best = (tg_index::TG_NATURAL, Duration::MAX);
+    for ix in [
+        tg_index::TG_NONE,
+        tg_index::TG_NATURAL,
+        tg_index::TG_YSTRIPES,
+    ] {
+        let Ok(candidate) = Geom::parse_wkb_ix(&wkb, ix) else {
+            continue;
+        };
+        let elapsed = (0..ROUNDS)
+            .map(|_| {
+                let start = Instant::now();
+                for p in &points {
+                    black_box(candidate.intersects_xy(p.x, p.y));
+                }
+                start.elapsed()
+            })
+            .min()
+            .unwrap_or(Duration::MAX);
+        if elapsed < best.1 {
+            best = (ix, elapsed);
+        }
+    }
+    best.0
+}
+
+/// Scatters `n` points over `rect` with a fixed xorshift sequence.
+fn sample_points(rect: tg_rect, n: usize) -> Vec<tg_point> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+    (0..n)
+        .map(|_| tg_point {
+            x: rect.min.x + next() * (rect.max.x - rect.min.x),
+            y: rect.min.y + next() * (rect.max.y - rect.min.y),
+        })
+        .collect()
+}
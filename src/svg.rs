@@ -0,0 +1,299 @@
+//! SVG rendering, for looking at geometries while debugging.
+//!
+//! [`to_svg()`] draws points, lines and polygons into a standalone SVG
+//! document. With [`SvgStyle::index_levels`] set it also overlays the
+//! rectangles of each ring's index, one color per level, which shows how tg
+//! has partitioned a ring and why a query against it might be slow.
+//!
+//! ```no_run
+//! use tg_sys::{to_svg, Geom, SvgStyle};
+//!
+//! let geom = Geom::parse_wkt("POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,4 2,4 4,2 2))")?;
+//! let style = SvgStyle {
+//!     index_levels: true,
+//!     ..Default::default()
+//! };
+//! std::fs::write("debug.svg", to_svg(&[geom], &style)).unwrap();
+//! # Ok::<(), tg_sys::Error>(())
+//! ```
+
+use std::fmt::Write;
+
+use crate::{tg_geom, tg_geom_type, tg_point, tg_rect, tg_ring, Geom};
+
+/// Colors for successive index levels, from the root down.
+const LEVEL_COLORS: [&str; 8] = [
+    "#e6194b", "#3cb44b", "#4363d8", "#f58231", "#911eb4", "#42d4f4", "#f032e6", "#9a6324",
+];
+
+/// How [`to_svg()`] draws geometries.
+#[derive(Clone, Debug)]
+pub struct SvgStyle {
+    /// The width of the image in pixels. The height follows from the aspect
+    /// ratio of the geometries' bounding box.
+    pub width: f64,
+    /// Space in pixels around the geometries.
+    pub padding: f64,
+    pub stroke: String,
+    pub stroke_width: f64,
+    /// The fill of polygons, which are drawn with the even-odd rule so that
+    /// holes are left empty.
+    pub fill: String,
+    pub fill_opacity: f64,
+    /// The radius of points in pixels.
+    pub point_radius: f64,
+    /// Whether to overlay each ring's index rectangles, one color per level.
+    /// Rings without an index, such as small ones or those created with
+    /// `TG_NONE`, have nothing to draw.
+    pub index_levels: bool,
+}
+
+impl Default for SvgStyle {
+    fn default() -> SvgStyle {
+        SvgStyle {
+            width: 800.0,
+            padding: 10.0,
+            stroke: "#333333".to_string(),
+            stroke_width: 1.0,
+            fill: "#1f77b4".to_string(),
+            fill_opacity: 0.4,
+            point_radius: 3.0,
+            index_levels: false,
+        }
+    }
+}
+
+/// Renders geometries as an SVG document.
+///
+/// The view is fitted to the bounding box of all the geometries, with y
+/// pointing up as in the source coordinates.
+pub fn to_svg(geoms: &[Geom], style: &SvgStyle) -> String {
+    let bounds = geoms
+        .iter()
+        .filter(|geom| !geom.is_empty())
+        .map(|geom| geom.rect())
+        .reduce(tg_rect::expand);
+    let view = View::new(bounds, style);
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = fmt(view.width),
+        h = fmt(view.height),
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"<g stroke="{}" stroke-width="{}" fill="{}" fill-opacity="{}" fill-rule="evenodd">"#,
+        escape(&style.stroke),
+        fmt(style.stroke_width),
+        escape(&style.fill),
+        fmt(style.fill_opacity),
+    )
+    .unwrap();
+    for geom in geoms {
+        view.geom(&mut svg, geom, style);
+    }
+    svg.push_str("</g>\n");
+
+    if style.index_levels {
+        let mut rings = Vec::new();
+        for geom in geoms {
+            collect_rings(geom, &mut rings);
+        }
+        let levels = rings
+            .iter()
+            .map(|r| r.index_num_levels())
+            .max()
+            .unwrap_or(0);
+        for level in 0..levels {
+            let color = LEVEL_COLORS[level % LEVEL_COLORS.len()];
+            writeln!(
+                svg,
+                r#"<g class="index-level-{}" stroke="{}" stroke-width="{}" fill="none">"#,
+                level,
+                color,
+                fmt(style.stroke_width),
+            )
+            .unwrap();
+            for ring in &rings {
+                if level < ring.index_num_levels() {
+                    for rect in ring.index_level_rects(level) {
+                        view.rect(&mut svg, rect);
+                    }
+                }
+            }
+            svg.push_str("</g>\n");
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Maps source coordinates to pixels.
+struct View {
+    origin: tg_point,
+    scale: f64,
+    padding: f64,
+    width: f64,
+    height: f64,
+}
+
+impl View {
+    fn new(bounds: Option<tg_rect>, style: &SvgStyle) -> View {
+        let inner = (style.width - 2.0 * style.padding).max(1.0);
+        let Some(bounds) = bounds else {
+            return View {
+                origin: tg_point { x: 0.0, y: 0.0 },
+                scale: 1.0,
+                padding: style.padding,
+                width: style.width,
+                height: style.width,
+            };
+        };
+        let span_x = bounds.max.x - bounds.min.x;
+        let span_y = bounds.max.y - bounds.min.y;
+        // A single point, or a run of points along one axis, still needs a
+        // finite scale.
+        let span = match (span_x > 0.0, span_y > 0.0) {
+            (true, _) => span_x,
+            (false, true) => span_y,
+            (false, false) => 1.0,
+        };
+        let scale = inner / span;
+        View {
+            origin: tg_point {
+                x: bounds.min.x,
+                y: bounds.max.y,
+            },
+            scale,
+            padding: style.padding,
+            width: style.width,
+            height: span_y * scale + 2.0 * style.padding,
+        }
+    }
+
+    fn point(&self, p: tg_point) -> (f64, f64) {
+        (
+            (p.x - self.origin.x) * self.scale + self.padding,
+            (self.origin.y - p.y) * self.scale + self.padding,
+        )
+    }
+
+    fn path(&self, svg: &mut String, points: &[tg_point], close: bool) {
+        for (i, &p) in points.iter().enumerate() {
+            let (x, y) = self.point(p);
+            let command = if i == 0 { 'M' } else { 'L' };
+            write!(svg, "{}{} {}", command, fmt(x), fmt(y)).unwrap();
+        }
+        if close {
+            svg.push('Z');
+        }
+    }
+
+    fn rect(&self, svg: &mut String, rect: tg_rect) {
+        let (x, y) = self.point(tg_point {
+            x: rect.min.x,
+            y: rect.max.y,
+        });
+        writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#,
+            fmt(x),
+            fmt(y),
+            fmt((rect.max.x - rect.min.x) * self.scale),
+            fmt((rect.max.y - rect.min.y) * self.scale),
+        )
+        .unwrap();
+    }
+
+    fn geom(&self, svg: &mut String, geom: &tg_geom, style: &SvgStyle) {
+        match geom.geom_type() {
+            _ if geom.is_empty() => {}
+            tg_geom_type::TG_POINT | tg_geom_type::TG_MULTIPOINT => {
+                let points: Vec<tg_point> = match geom.geom_type() {
+                    tg_geom_type::TG_POINT => vec![geom.point()],
+                    _ => geom.points().collect(),
+                };
+                for p in points {
+                    let (x, y) = self.point(p);
+                    writeln!(
+                        svg,
+                        r#"<circle cx="{}" cy="{}" r="{}"/>"#,
+                        fmt(x),
+                        fmt(y),
+                        fmt(style.point_radius),
+                    )
+                    .unwrap();
+                }
+            }
+            tg_geom_type::TG_LINESTRING | tg_geom_type::TG_MULTILINESTRING => {
+                svg.push_str(r#"<path fill="none" d=""#);
+                match geom.line() {
+                    Some(line) => self.path(svg, line.points(), false),
+                    None => {
+                        for line in geom.lines() {
+                            self.path(svg, line.points(), false);
+                        }
+                    }
+                }
+                svg.push_str("\"/>\n");
+            }
+            tg_geom_type::TG_POLYGON | tg_geom_type::TG_MULTIPOLYGON => {
+                svg.push_str(r#"<path d=""#);
+                let mut rings = Vec::new();
+                collect_rings(geom, &mut rings);
+                for ring in rings {
+                    self.path(svg, ring.points(), true);
+                }
+                svg.push_str("\"/>\n");
+            }
+            tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                for member in geom.geometries() {
+                    self.geom(svg, member, style);
+                }
+            }
+        }
+    }
+}
+
+/// Gathers the exterior and hole rings of every polygon in `geom`.
+fn collect_rings<'a>(geom: &'a tg_geom, rings: &mut Vec<&'a tg_ring>) {
+    match geom.geom_type() {
+        tg_geom_type::TG_POLYGON | tg_geom_type::TG_MULTIPOLYGON => {
+            let polys: Vec<_> = match geom.poly() {
+                Some(poly) => vec![poly],
+                None => geom.polys().collect(),
+            };
+            for poly in polys {
+                rings.push(poly.exterior());
+                rings.extend(poly.holes());
+            }
+        }
+        tg_geom_type::TG_GEOMETRYCOLLECTION => {
+            for member in geom.geometries() {
+                collect_rings(member, rings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Formats a pixel coordinate with enough precision for display.
+fn fmt(v: f64) -> String {
+    let s = format!("{:.2}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// Escapes a style value for use in an attribute.
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
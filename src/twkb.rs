@@ -0,0 +1,500 @@
+//! Tiny Well-known binary (TWKB).
+//!
+//! TWKB stores coordinates as varint encoded integers, scaled by a decimal
+//! precision and delta encoded against the previous point, which makes it far
+//! more compact than WKB for transmission. See
+//! <https://github.com/TWKB/Specification>.
+//!
+//! ```no_run
+//! use tg_sys::{Geom, TwkbOptions};
+//!
+//! let geom = Geom::parse_wkt("LINESTRING(-122.4194 37.7749,-122.4184 37.7759)").unwrap();
+//! let options = TwkbOptions {
+//!     precision: 5,
+//!     ..TwkbOptions::default()
+//! };
+//! let twkb = geom.to_twkb(&options).unwrap();
+//! let geom = Geom::parse_twkb(&twkb).unwrap();
+//! ```
+
+use crate::{tg_geom, tg_geom_type, tg_index, tg_point, Dims, Error, Geom, Line, Poly, Ring};
+
+const BBOX: u8 = 0x01;
+const SIZE: u8 = 0x02;
+const IDLIST: u8 = 0x04;
+const EXTENDED: u8 = 0x08;
+const EMPTY: u8 = 0x10;
+
+/// The deepest nesting of collections accepted, so that hostile input can't
+/// overflow the stack.
+const MAX_DEPTH: usize = 1024;
+
+/// Options for [`to_twkb()`][tg_geom::to_twkb].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TwkbOptions {
+    /// The number of decimal digits kept for X and Y, from -7 to 7. Negative
+    /// values round to tens, hundreds, and so on.
+    pub precision: i8,
+    /// The number of decimal digits kept for Z, from 0 to 7.
+    pub z_precision: u8,
+    /// The number of decimal digits kept for M, from 0 to 7.
+    pub m_precision: u8,
+    /// Whether to write the bounding box of each geometry.
+    pub bbox: bool,
+    /// Whether to write the encoded size of each geometry, so readers can
+    /// skip over it.
+    pub size: bool,
+}
+
+impl tg_geom {
+    /// Writes the geometry as Tiny Well-known binary (TWKB).
+    ///
+    /// Coordinates are rounded to the precisions in `options`. Returns an
+    /// error if a precision is out of range.
+    pub fn to_twkb(&self, options: &TwkbOptions) -> Result<Vec<u8>, Error> {
+        if !(-7..=7).contains(&options.precision) {
+            return Err(Error::new("twkb: precision must be between -7 and 7"));
+        }
+        if options.z_precision > 7 || options.m_precision > 7 {
+            return Err(Error::new(
+                "twkb: Z and M precision must be between 0 and 7",
+            ));
+        }
+        let mut out = Vec::new();
+        Encoder { options }.geom(self, &mut out);
+        Ok(out)
+    }
+}
+
+impl Geom {
+    /// Parses Tiny Well-known binary (TWKB).
+    ///
+    /// ID lists are accepted but discarded, as tg has nowhere to keep them.
+    pub fn parse_twkb(twkb: &[u8]) -> Result<Geom, Error> {
+        Geom::parse_twkb_ix(twkb, tg_index::TG_DEFAULT)
+    }
+
+    /// Parses Tiny Well-known binary (TWKB) using the provided indexing
+    /// option.
+    pub fn parse_twkb_ix(twkb: &[u8], ix: tg_index) -> Result<Geom, Error> {
+        let mut decoder = Decoder {
+            src: twkb,
+            pos: 0,
+            ix,
+        };
+        let geom = decoder.geom(0)?;
+        if decoder.pos != twkb.len() {
+            return Err(Error::new("twkb: trailing bytes after geometry"));
+        }
+        Ok(geom)
+    }
+}
+
+fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+fn unzigzag(v: u64) -> i64 {
+    (v >> 1) as i64 ^ -((v & 1) as i64)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+/// The scale factors for X, Y, Z and M.
+fn scales(precision: i8, z_precision: u8, m_precision: u8) -> [f64; 4] {
+    let xy = 10f64.powi(precision.into());
+    [
+        xy,
+        xy,
+        10f64.powi(z_precision.into()),
+        10f64.powi(m_precision.into()),
+    ]
+}
+
+/// The order of coordinates within a point: X, Y, then whichever of Z and M
+/// are present.
+fn axes(dims: Dims) -> impl Iterator<Item = usize> {
+    [0, 1]
+        .into_iter()
+        .chain(dims.has_z().then_some(2))
+        .chain(dims.has_m().then_some(3))
+}
+
+struct Encoder<'a> {
+    options: &'a TwkbOptions,
+}
+
+/// Delta encodes the points of one geometry, tracking their bounds.
+struct Coords<'a> {
+    body: Vec<u8>,
+    axes: Vec<usize>,
+    scales: [f64; 4],
+    extra: &'a [f64],
+    next_extra: usize,
+    prev: [i64; 4],
+    bounds: Option<[(i64, i64); 4]>,
+}
+
+impl Coords<'_> {
+    fn point(&mut self, p: tg_point) {
+        let nextra = self.axes.len() - 2;
+        let mut coords = [p.x, p.y, 0.0, 0.0];
+        for (i, &axis) in self.axes[2..].iter().enumerate() {
+            coords[axis] = self.extra.get(self.next_extra + i).copied().unwrap_or(0.0);
+        }
+        self.next_extra += nextra;
+        let bounds = self.bounds.get_or_insert([(i64::MAX, i64::MIN); 4]);
+        for &axis in &self.axes {
+            let v = (coords[axis] * self.scales[axis]).round() as i64;
+            write_varint(&mut self.body, zigzag(v.wrapping_sub(self.prev[axis])));
+            self.prev[axis] = v;
+            let (min, max) = &mut bounds[axis];
+            *min = (*min).min(v);
+            *max = (*max).max(v);
+        }
+    }
+
+    fn points(&mut self, points: &[tg_point]) {
+        write_varint(&mut self.body, points.len() as u64);
+        for &p in points {
+            self.point(p);
+        }
+    }
+
+    fn count(&mut self, n: usize) {
+        write_varint(&mut self.body, n as u64);
+    }
+}
+
+impl Encoder<'_> {
+    /// Appends one geometry, with its header, to `out` and returns the bounds
+    /// of its scaled coordinates.
+    fn geom(&self, geom: &tg_geom, out: &mut Vec<u8>) -> Option<[(i64, i64); 4]> {
+        let options = self.options;
+        let geom_type = geom.geom_type();
+        let dims = geom.dims();
+        let empty = geom.is_empty();
+
+        let point_extra = [geom.z(), geom.m()];
+        let mut coords = Coords {
+            body: Vec::new(),
+            axes: axes(dims).collect(),
+            scales: scales(options.precision, options.z_precision, options.m_precision),
+            extra: geom.extra_coords(),
+            next_extra: 0,
+            prev: [0; 4],
+            bounds: None,
+        };
+        if !empty {
+            match geom_type {
+                tg_geom_type::TG_POINT => {
+                    let extra = match dims {
+                        Dims::Xym => &point_extra[1..],
+                        _ => &point_extra[..],
+                    };
+                    coords.extra = extra;
+                    coords.point(geom.point());
+                }
+                tg_geom_type::TG_LINESTRING => {
+                    let line = geom.line().expect("linestring has a line");
+                    coords.points(line.points());
+                }
+                tg_geom_type::TG_POLYGON => {
+                    let poly = geom.poly().expect("polygon has a poly");
+                    coords.count(1 + poly.holes().len());
+                    coords.points(poly.exterior().points());
+                    for hole in poly.holes() {
+                        coords.points(hole.points());
+                    }
+                }
+                tg_geom_type::TG_MULTIPOINT => {
+                    let points: Vec<tg_point> = geom.points().collect();
+                    coords.points(&points);
+                }
+                tg_geom_type::TG_MULTILINESTRING => {
+                    coords.count(geom.lines().len());
+                    for line in geom.lines() {
+                        coords.points(line.points());
+                    }
+                }
+                tg_geom_type::TG_MULTIPOLYGON => {
+                    coords.count(geom.polys().len());
+                    for poly in geom.polys() {
+                        coords.count(1 + poly.holes().len());
+                        coords.points(poly.exterior().points());
+                        for hole in poly.holes() {
+                            coords.points(hole.points());
+                        }
+                    }
+                }
+                tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                    coords.count(geom.geometries().len());
+                    for member in geom.geometries() {
+                        let bounds = self.geom(member, &mut coords.body);
+                        coords.bounds = union(coords.bounds, bounds);
+                    }
+                }
+            }
+        }
+
+        let precision = zigzag(options.precision.into()) as u8;
+        out.push(geom_type as u8 | precision << 4);
+        let mut metadata = 0;
+        if options.bbox && coords.bounds.is_some() {
+            metadata |= BBOX;
+        }
+        if options.size && !empty {
+            metadata |= SIZE;
+        }
+        if dims != Dims::Xy {
+            metadata |= EXTENDED;
+        }
+        if empty {
+            metadata |= EMPTY;
+        }
+        out.push(metadata);
+        if dims != Dims::Xy {
+            out.push(
+                u8::from(dims.has_z())
+                    | u8::from(dims.has_m()) << 1
+                    | options.z_precision << 2
+                    | options.m_precision << 5,
+            );
+        }
+
+        let mut rest = Vec::new();
+        if metadata & BBOX != 0 {
+            let bounds = coords.bounds.expect("bbox requires bounds");
+            for &axis in &coords.axes {
+                let (min, max) = bounds[axis];
+                write_varint(&mut rest, zigzag(min));
+                write_varint(&mut rest, zigzag(max.wrapping_sub(min)));
+            }
+        }
+        rest.extend_from_slice(&coords.body);
+        if metadata & SIZE != 0 {
+            write_varint(out, rest.len() as u64);
+        }
+        out.extend_from_slice(&rest);
+        coords.bounds
+    }
+}
+
+fn union(a: Option<[(i64, i64); 4]>, b: Option<[(i64, i64); 4]>) -> Option<[(i64, i64); 4]> {
+    match (a, b) {
+        (Some(mut a), Some(b)) => {
+            for (a, b) in a.iter_mut().zip(b) {
+                *a = (a.0.min(b.0), a.1.max(b.1));
+            }
+            Some(a)
+        }
+        (a, b) => a.or(b),
+    }
+}
+
+struct Decoder<'a> {
+    src: &'a [u8],
+    pos: usize,
+    ix: tg_index,
+}
+
+/// The state for reading the points of one geometry.
+struct Reader {
+    axes: Vec<usize>,
+    scales: [f64; 4],
+    prev: [i64; 4],
+    points: Vec<tg_point>,
+    extra: Vec<f64>,
+}
+
+impl Decoder<'_> {
+    fn byte(&mut self) -> Result<u8, Error> {
+        let b = *self
+            .src
+            .get(self.pos)
+            .ok_or_else(|| Error::new("twkb: unexpected end of input"))?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn varint(&mut self) -> Result<u64, Error> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            v |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err(Error::new("twkb: varint is too long"))
+    }
+
+    /// Reads a count, bounded by the remaining input so that a corrupt count
+    /// can't cause a huge allocation.
+    fn count(&mut self) -> Result<usize, Error> {
+        let n = self.varint()?;
+        if n > (self.src.len() - self.pos) as u64 {
+            return Err(Error::new("twkb: count exceeds input size"));
+        }
+        Ok(n as usize)
+    }
+
+    fn point(&mut self, reader: &mut Reader) -> Result<(), Error> {
+        let mut coords = [0.0; 4];
+        for &axis in &reader.axes {
+            let v = reader.prev[axis].wrapping_add(unzigzag(self.varint()?));
+            reader.prev[axis] = v;
+            coords[axis] = v as f64 / reader.scales[axis];
+        }
+        reader.points.push(tg_point {
+            x: coords[0],
+            y: coords[1],
+        });
+        for &axis in &reader.axes[2..] {
+            reader.extra.push(coords[axis]);
+        }
+        Ok(())
+    }
+
+    /// Reads a point count followed by that many points, returning the
+    /// range of `reader.points` they were read into.
+    fn points(&mut self, reader: &mut Reader) -> Result<std::ops::Range<usize>, Error> {
+        let n = self.count()?;
+        let start = reader.points.len();
+        for _ in 0..n {
+            self.point(reader)?;
+        }
+        Ok(start..reader.points.len())
+    }
+
+    fn poly(&mut self, reader: &mut Reader) -> Result<Option<Poly>, Error> {
+        let nrings = self.count()?;
+        let mut rings = Vec::with_capacity(nrings);
+        for _ in 0..nrings {
+            let range = self.points(reader)?;
+            rings.push(Ring::new_ix(&reader.points[range], self.ix));
+        }
+        Ok(rings.split_first().map(|(exterior, holes)| {
+            let holes: Vec<_> = holes.iter().map(|h| &**h).collect();
+            Poly::new(exterior, &holes)
+        }))
+    }
+
+    fn skip_ids(&mut self, metadata: u8, n: usize) -> Result<(), Error> {
+        if metadata & IDLIST != 0 {
+            for _ in 0..n {
+                self.varint()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads one geometry nested `depth` collections deep.
+    fn geom(&mut self, depth: usize) -> Result<Geom, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::new("twkb: collections nested too deeply"));
+        }
+        let header = self.byte()?;
+        let geom_type = match header & 0x0f {
+            1 => tg_geom_type::TG_POINT,
+            2 => tg_geom_type::TG_LINESTRING,
+            3 => tg_geom_type::TG_POLYGON,
+            4 => tg_geom_type::TG_MULTIPOINT,
+            5 => tg_geom_type::TG_MULTILINESTRING,
+            6 => tg_geom_type::TG_MULTIPOLYGON,
+            7 => tg_geom_type::TG_GEOMETRYCOLLECTION,
+            t => return Err(Error::new(format!("twkb: unsupported geometry type {}", t))),
+        };
+        let precision = unzigzag(u64::from(header >> 4)) as i8;
+        let metadata = self.byte()?;
+        let (dims, z_precision, m_precision) = match metadata & EXTENDED {
+            0 => (Dims::Xy, 0, 0),
+            _ => {
+                let ext = self.byte()?;
+                let dims = Dims::new(ext & 0x01 != 0, ext & 0x02 != 0);
+                (dims, ext >> 2 & 0x07, ext >> 5 & 0x07)
+            }
+        };
+        if metadata & SIZE != 0 {
+            self.varint()?;
+        }
+        if metadata & EMPTY != 0 {
+            return Ok(Geom::new_empty(geom_type));
+        }
+        let mut reader = Reader {
+            axes: axes(dims).collect(),
+            scales: scales(precision, z_precision, m_precision),
+            prev: [0; 4],
+            points: Vec::new(),
+            extra: Vec::new(),
+        };
+        if metadata & BBOX != 0 {
+            for _ in 0..reader.axes.len() * 2 {
+                self.varint()?;
+            }
+        }
+
+        let geom = match geom_type {
+            tg_geom_type::TG_POINT => {
+                self.point(&mut reader)?;
+                Geom::new_point_ex(reader.points[0], dims, &reader.extra)
+            }
+            tg_geom_type::TG_LINESTRING => {
+                self.points(&mut reader)?;
+                let line = Line::new_ix(&reader.points, self.ix);
+                Geom::new_linestring_ex(&line, dims, &reader.extra)
+            }
+            tg_geom_type::TG_POLYGON => match self.poly(&mut reader)? {
+                Some(poly) => Geom::new_polygon_ex(&poly, dims, &reader.extra),
+                None => Geom::new_empty(geom_type),
+            },
+            tg_geom_type::TG_MULTIPOINT => {
+                let n = self.count()?;
+                self.skip_ids(metadata, n)?;
+                for _ in 0..n {
+                    self.point(&mut reader)?;
+                }
+                Geom::new_multipoint_ex(&reader.points, dims, &reader.extra)
+            }
+            tg_geom_type::TG_MULTILINESTRING => {
+                let n = self.count()?;
+                self.skip_ids(metadata, n)?;
+                let mut lines = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let range = self.points(&mut reader)?;
+                    lines.push(Line::new_ix(&reader.points[range], self.ix));
+                }
+                let lines: Vec<_> = lines.iter().map(|l| &**l).collect();
+                Geom::new_multilinestring_ex(&lines, dims, &reader.extra)
+            }
+            tg_geom_type::TG_MULTIPOLYGON => {
+                let n = self.count()?;
+                self.skip_ids(metadata, n)?;
+                let mut polys = Vec::with_capacity(n);
+                for _ in 0..n {
+                    // Empty members can't be represented in a tg multipolygon.
+                    polys.extend(self.poly(&mut reader)?);
+                }
+                let polys: Vec<_> = polys.iter().map(|p| &**p).collect();
+                Geom::new_multipolygon_ex(&polys, dims, &reader.extra)
+            }
+            tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                let n = self.count()?;
+                self.skip_ids(metadata, n)?;
+                let mut members = Vec::with_capacity(n);
+                for _ in 0..n {
+                    members.push(self.geom(depth + 1)?);
+                }
+                let members: Vec<_> = members.iter().map(|g| &**g).collect();
+                Geom::new_geometrycollection(&members)
+            }
+        };
+        Ok(geom)
+    }
+}
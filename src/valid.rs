@@ -0,0 +1,641 @@
+//! OGC validity checks.
+//!
+//! tg builds whatever it is given: rings that cross themselves, holes that
+//! poke out of their polygon, coordinates that are NaN. Predicates on such
+//! geometries are well defined but rarely what anyone expects, so
+//! [`tg_geom::validate()`] reports these defects up front, and
+//! [`tg_geom::make_valid()`] repairs the common ones.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::RingFuncs::tg_ring_ring_search;
+use crate::{
+    tg_geom, tg_geom_type, tg_point, tg_poly, tg_ring, tg_segment, Geom, Line, Poly, Ring,
+};
+
+/// A defect found by [`tg_geom::validate()`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ValidityError {
+    pub kind: ValidityErrorKind,
+    /// The index of the part the defect is in, counting the members of
+    /// multi geometries and collections depth first, or 0 for a single
+    /// geometry.
+    pub part: usize,
+    /// The ring of a polygon the defect is in, 0 for the exterior and 1 on
+    /// for holes, or `None` for points and lines.
+    pub ring: Option<usize>,
+    /// Where the defect is.
+    pub point: tg_point,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValidityErrorKind {
+    /// A coordinate is NaN or infinite.
+    NonFinite,
+    /// A point repeats the one before it.
+    DuplicatePoint,
+    /// A line has fewer than 2 distinct points, or a ring fewer than 3 or
+    /// none off the line through the others, so that it has no area.
+    TooFewPoints,
+    /// A ring's last point differs from its first. [`Ring::new()`] closes
+    /// rings automatically, so this is a safeguard.
+    Unclosed,
+    /// Two segments of a ring that aren't neighbours meet, or neighbours
+    /// double back along each other.
+    SelfIntersection,
+    /// A hole lies outside its polygon's exterior.
+    HoleOutside,
+    /// A hole crosses its polygon's exterior.
+    HoleCrossesExterior,
+    /// A hole lies inside another hole.
+    NestedHoles,
+    /// Two holes cross each other.
+    OverlappingHoles,
+}
+
+impl fmt::Display for ValidityErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValidityErrorKind::NonFinite => "non-finite coordinate",
+            ValidityErrorKind::DuplicatePoint => "duplicate point",
+            ValidityErrorKind::TooFewPoints => "too few points",
+            ValidityErrorKind::Unclosed => "unclosed ring",
+            ValidityErrorKind::SelfIntersection => "self-intersection",
+            ValidityErrorKind::HoleOutside => "hole outside exterior",
+            ValidityErrorKind::HoleCrossesExterior => "hole crosses exterior",
+            ValidityErrorKind::NestedHoles => "nested holes",
+            ValidityErrorKind::OverlappingHoles => "overlapping holes",
+        })
+    }
+}
+
+impl fmt::Display for ValidityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at ({} {}) in part {}",
+            self.kind, self.point.x, self.point.y, self.part
+        )?;
+        if let Some(ring) = self.ring {
+            write!(f, ", ring {}", ring)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidityError {}
+
+impl tg_geom {
+    /// Checks the geometry against the OGC simple features rules.
+    ///
+    /// Returns every defect found. Points and lines are checked for
+    /// non-finite coordinates, repeated points and too few points. Polygon
+    /// rings are also checked for closure and self-intersections, where two
+    /// segments that aren't neighbours touch or cross, and holes for lying
+    /// outside or crossing the exterior, or lying inside or crossing each
+    /// other. A hole may touch the exterior, or another hole, at a point.
+    ///
+    /// The members of a multi geometry aren't checked against each other.
+    pub fn validate(&self) -> Result<(), Vec<ValidityError>> {
+        let mut validator = Validator {
+            errors: Vec::new(),
+            part: 0,
+        };
+        validator.geom(self);
+        if validator.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(validator.errors)
+        }
+    }
+
+    /// Returns true if [`validate()`][tg_geom::validate] finds no defects.
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+}
+
+impl tg_geom {
+    /// Repairs the common defects that [`validate()`][tg_geom::validate]
+    /// reports, returning a new geometry.
+    ///
+    /// Points with non-finite coordinates are dropped, as are repeated
+    /// points, and lines left with fewer than 2 points. Polygon rings are
+    /// closed, lose their collinear points and spikes, and are split where
+    /// they cross themselves, so a bow-tie becomes a MultiPolygon of its two
+    /// halves. Exteriors are then wound counterclockwise and holes
+    /// clockwise. Holes that collapse, or that fall outside every exterior,
+    /// are dropped.
+    ///
+    /// Holes that cross their exterior or each other are left as they are,
+    /// as are polygons of a MultiPolygon that overlap. Z and M coordinates
+    /// are dropped, since points are removed.
+    pub fn make_valid(&self) -> Geom {
+        match self.geom_type() {
+            tg_geom_type::TG_POINT if self.is_empty() || !finite(self.point()) => {
+                Geom::new_empty(tg_geom_type::TG_POINT)
+            }
+            tg_geom_type::TG_POINT => Geom::new_point(self.point()),
+            tg_geom_type::TG_MULTIPOINT => {
+                let points: Vec<tg_point> = self.points().filter(|p| finite(*p)).collect();
+                Geom::new_multipoint(&points)
+            }
+            tg_geom_type::TG_LINESTRING | tg_geom_type::TG_MULTILINESTRING => {
+                let lines: Vec<Line> = match self.line() {
+                    Some(line) => vec![line],
+                    None => self.lines().collect(),
+                }
+                .into_iter()
+                .filter_map(|line| {
+                    let mut points: Vec<tg_point> = Vec::new();
+                    for &p in line.points() {
+                        if finite(p) && points.last() != Some(&p) {
+                            points.push(p);
+                        }
+                    }
+                    (points.len() >= 2).then(|| Line::new(&points))
+                })
+                .collect();
+                match (self.geom_type(), &lines[..]) {
+                    (tg_geom_type::TG_LINESTRING, [line]) => Geom::new_linestring(line),
+                    (tg_geom_type::TG_LINESTRING, []) => {
+                        Geom::new_empty(tg_geom_type::TG_LINESTRING)
+                    }
+                    _ => Geom::new_multilinestring(&lines.iter().map(|l| &**l).collect::<Vec<_>>()),
+                }
+            }
+            tg_geom_type::TG_POLYGON | tg_geom_type::TG_MULTIPOLYGON => {
+                let polys: Vec<&tg_poly> = match self.poly() {
+                    Some(poly) => vec![poly],
+                    None => self.polys().collect(),
+                };
+                let polys: Vec<Poly> = polys.into_iter().flat_map(repair_poly).collect();
+                match (self.geom_type(), &polys[..]) {
+                    (tg_geom_type::TG_POLYGON, [poly]) => Geom::new_polygon(poly),
+                    (tg_geom_type::TG_POLYGON, []) => Geom::new_empty(tg_geom_type::TG_POLYGON),
+                    _ => Geom::new_multipolygon(&polys.iter().map(|p| &**p).collect::<Vec<_>>()),
+                }
+            }
+            tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                let members: Vec<Geom> = self.geometries().map(tg_geom::make_valid).collect();
+                Geom::new_geometrycollection(&members.iter().map(|g| &**g).collect::<Vec<_>>())
+            }
+        }
+    }
+}
+
+/// Repairs a polygon, which may split it into several.
+fn repair_poly(poly: &tg_poly) -> Vec<Poly> {
+    let exteriors: Vec<Ring> = split(&simplify(poly.exterior().points()))
+        .into_iter()
+        .map(|points| wind(&points, false))
+        .collect();
+    let mut holes: Vec<Vec<Ring>> = exteriors.iter().map(|_| Vec::new()).collect();
+    for hole in poly.holes() {
+        for points in split(&simplify(hole.points())) {
+            let hole = wind(&points, true);
+            if let Some(i) = exteriors
+                .iter()
+                .position(|ext| outside(&hole, ext).is_none())
+            {
+                holes[i].push(hole);
+            }
+        }
+    }
+    exteriors
+        .iter()
+        .zip(&holes)
+        .map(|(exterior, holes)| {
+            let holes: Vec<&tg_ring> = holes.iter().map(|h| &**h).collect();
+            Poly::new(exterior, &holes)
+        })
+        .collect()
+}
+
+/// Builds a ring wound clockwise or counterclockwise, as tg sees it.
+fn wind(points: &[tg_point], clockwise: bool) -> Ring {
+    let ring = Ring::new(points);
+    if ring.clockwise() == clockwise {
+        return ring;
+    }
+    let mut points = points.to_vec();
+    points.reverse();
+    Ring::new(&points)
+}
+
+/// Drops non-finite, repeated and collinear points from a ring, along with
+/// its closing point. Spikes, where the ring doubles back on itself, are
+/// collinear too.
+fn simplify(points: &[tg_point]) -> Vec<tg_point> {
+    let mut out: Vec<tg_point> = Vec::with_capacity(points.len());
+    let push = |out: &mut Vec<tg_point>, p: tg_point| {
+        if out.last() == Some(&p) {
+            return;
+        }
+        out.push(p);
+        while out.len() >= 3 {
+            let n = out.len();
+            if orient(out[n - 3], out[n - 2], out[n - 1]) != 0.0 {
+                break;
+            }
+            out.remove(n - 2);
+            if out[n - 3] == out[n - 2] {
+                out.pop();
+            }
+        }
+    };
+    for &p in points.iter().filter(|p| finite(**p)) {
+        push(&mut out, p);
+    }
+    // Then the same around the join between the end and the start.
+    loop {
+        let n = out.len();
+        let closed = n > 1 && out[0] == out[n - 1];
+        if closed || n >= 3 && orient(out[n - 2], out[n - 1], out[0]) == 0.0 {
+            out.pop();
+        } else if n >= 3 && orient(out[n - 1], out[0], out[1]) == 0.0 {
+            out.remove(0);
+        } else {
+            break;
+        }
+    }
+    if out.len() < 3 {
+        out.clear();
+    }
+    out
+}
+
+/// Splits a ring wherever it meets itself into simple loops, dropping any
+/// that have no area.
+fn split(points: &[tg_point]) -> Vec<Vec<tg_point>> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let ring = Ring::new(points);
+    let n = points.len();
+
+    // Add the points where segments meet to both segments, so that the
+    // ring passes through each of them twice.
+    let mut splits: Vec<Vec<tg_point>> = vec![Vec::new(); n];
+    for (a, i, b, j) in search(&ring, &ring) {
+        let (lo, hi) = (i.min(j), i.max(j));
+        if lo == hi || hi == lo + 1 || (lo == 0 && hi == n - 1) {
+            continue;
+        }
+        if let Some(contact) = contact(a, b) {
+            let p = contact.point();
+            for (seg, k) in [(a, i), (b, j)] {
+                if p != seg.a && p != seg.b && !splits[k].contains(&p) {
+                    splits[k].push(p);
+                }
+            }
+        }
+    }
+    let mut walk = Vec::with_capacity(n + 1);
+    for (i, extra) in splits.iter_mut().enumerate() {
+        let start = points[i];
+        let distance = |p: &tg_point| (p.x - start.x).hypot(p.y - start.y);
+        extra.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+        walk.push(start);
+        walk.extend_from_slice(extra);
+    }
+    walk.push(points[0]);
+
+    // Walk the ring, cutting off a loop each time it returns to a point it
+    // has already passed.
+    let key = |p: &tg_point| (p.x.to_bits(), p.y.to_bits());
+    let mut loops = Vec::new();
+    let mut stack: Vec<tg_point> = Vec::with_capacity(walk.len());
+    let mut seen: HashMap<(u64, u64), usize> = HashMap::new();
+    for p in walk {
+        if let Some(&at) = seen.get(&key(&p)) {
+            let cut = stack.split_off(at);
+            for q in &cut {
+                seen.remove(&key(q));
+            }
+            loops.push(cut);
+        }
+        seen.insert(key(&p), stack.len());
+        stack.push(p);
+    }
+    loops
+        .into_iter()
+        .map(|points| simplify(&points))
+        .filter(|points| !points.is_empty())
+        .collect()
+}
+
+struct Validator {
+    errors: Vec<ValidityError>,
+    part: usize,
+}
+
+impl Validator {
+    fn error(&mut self, kind: ValidityErrorKind, ring: Option<usize>, point: tg_point) {
+        self.errors.push(ValidityError {
+            kind,
+            part: self.part,
+            ring,
+            point,
+        });
+    }
+
+    fn geom(&mut self, geom: &tg_geom) {
+        match geom.geom_type() {
+            _ if geom.is_empty() => {}
+            tg_geom_type::TG_POINT => {
+                self.point(geom.point());
+                self.part += 1;
+            }
+            tg_geom_type::TG_MULTIPOINT => {
+                for p in geom.points() {
+                    self.point(p);
+                    self.part += 1;
+                }
+            }
+            tg_geom_type::TG_LINESTRING | tg_geom_type::TG_MULTILINESTRING => {
+                let lines: Vec<_> = match geom.line() {
+                    Some(line) => vec![line],
+                    None => geom.lines().collect(),
+                };
+                for line in lines {
+                    self.path(line.points(), None, 2);
+                    self.part += 1;
+                }
+            }
+            tg_geom_type::TG_POLYGON | tg_geom_type::TG_MULTIPOLYGON => {
+                let polys: Vec<_> = match geom.poly() {
+                    Some(poly) => vec![poly],
+                    None => geom.polys().collect(),
+                };
+                for poly in polys {
+                    self.poly(poly);
+                    self.part += 1;
+                }
+            }
+            tg_geom_type::TG_GEOMETRYCOLLECTION => {
+                for member in geom.geometries() {
+                    self.geom(member);
+                }
+            }
+        }
+    }
+
+    fn point(&mut self, p: tg_point) {
+        if !finite(p) {
+            self.error(ValidityErrorKind::NonFinite, None, p);
+        }
+    }
+
+    /// Checks the points of a line or ring, returning false if they're too
+    /// broken to check any further.
+    fn path(&mut self, points: &[tg_point], ring: Option<usize>, min_points: usize) -> bool {
+        if let Some(&p) = points.iter().find(|p| !finite(**p)) {
+            self.error(ValidityErrorKind::NonFinite, ring, p);
+            return false;
+        }
+        for pair in points.windows(2) {
+            if pair[0] == pair[1] {
+                self.error(ValidityErrorKind::DuplicatePoint, ring, pair[1]);
+            }
+        }
+        let distinct = distinct(points);
+        if distinct.len() < min_points || (ring.is_some() && collinear(&distinct)) {
+            let p = points
+                .first()
+                .copied()
+                .unwrap_or(tg_point { x: 0.0, y: 0.0 });
+            self.error(ValidityErrorKind::TooFewPoints, ring, p);
+            return false;
+        }
+        true
+    }
+
+    fn poly(&mut self, poly: &tg_poly) {
+        let rings: Vec<&tg_ring> = std::iter::once(poly.exterior())
+            .chain(poly.holes())
+            .collect();
+        let mut checked = Vec::with_capacity(rings.len());
+        for (i, ring) in rings.iter().enumerate() {
+            let points = ring.points();
+            if !self.path(points, Some(i), 3) {
+                checked.push(false);
+                continue;
+            }
+            if points.first() != points.last() {
+                self.error(
+                    ValidityErrorKind::Unclosed,
+                    Some(i),
+                    points[points.len() - 1],
+                );
+            }
+            self.self_intersections(points, i);
+            checked.push(true);
+        }
+        if !checked[0] {
+            return;
+        }
+
+        let exterior = rings[0];
+        for (i, hole) in rings.iter().enumerate().skip(1) {
+            if !checked[i] {
+                continue;
+            }
+            if let Some(p) = crossing(exterior, hole) {
+                self.error(ValidityErrorKind::HoleCrossesExterior, Some(i), p);
+            } else if let Some(&p) = outside(hole, exterior) {
+                self.error(ValidityErrorKind::HoleOutside, Some(i), p);
+            }
+        }
+        for i in 1..rings.len() {
+            for j in i + 1..rings.len() {
+                if !checked[i] || !checked[j] || !rings[i].rect().intersects_rect(rings[j].rect()) {
+                    continue;
+                }
+                if let Some(p) = crossing(rings[i], rings[j]) {
+                    self.error(ValidityErrorKind::OverlappingHoles, Some(j), p);
+                } else if outside(rings[j], rings[i]).is_none() {
+                    self.error(
+                        ValidityErrorKind::NestedHoles,
+                        Some(j),
+                        rings[j].points()[0],
+                    );
+                } else if outside(rings[i], rings[j]).is_none() {
+                    self.error(
+                        ValidityErrorKind::NestedHoles,
+                        Some(i),
+                        rings[i].points()[0],
+                    );
+                }
+            }
+        }
+    }
+
+    /// Searches a ring against itself for segments that meet, other than
+    /// neighbours meeting only at their shared point.
+    fn self_intersections(&mut self, points: &[tg_point], ring: usize) {
+        // Repeated points make zero-length segments, which would make the
+        // segments either side of them look like non-neighbours that touch.
+        let ring_ = Ring::new(&distinct(points));
+        let n = ring_.points().len() - 1;
+        let mut found = HashSet::new();
+        for (a, i, b, j) in search(&ring_, &ring_) {
+            let (i, j) = (i.min(j), i.max(j));
+            // Each pair is reported twice, once from each side.
+            if i == j || !found.insert((i, j)) {
+                continue;
+            }
+            let neighbours = j == i + 1 || (i == 0 && j == n - 1);
+            match contact(a, b) {
+                Some(Contact::Touch(_)) if neighbours => {}
+                Some(contact) => self.error(
+                    ValidityErrorKind::SelfIntersection,
+                    Some(ring),
+                    contact.point(),
+                ),
+                None => {}
+            }
+        }
+    }
+}
+
+fn finite(p: tg_point) -> bool {
+    p.x.is_finite() && p.y.is_finite()
+}
+
+/// Returns true if every point lies on the line through the first two,
+/// which must differ.
+fn collinear(points: &[tg_point]) -> bool {
+    points
+        .iter()
+        .all(|&p| orient(points[0], points[1], p) == 0.0)
+}
+
+/// Drops repeated points, and the closing point of a ring.
+fn distinct(points: &[tg_point]) -> Vec<tg_point> {
+    let mut out: Vec<tg_point> = Vec::with_capacity(points.len());
+    for &p in points {
+        if out.last() != Some(&p) {
+            out.push(p);
+        }
+    }
+    if out.len() > 1 && out.first() == out.last() {
+        out.pop();
+    }
+    out
+}
+
+/// Returns every pair of segments of `a` and `b` that meet.
+fn search(a: &tg_ring, b: &tg_ring) -> Vec<(tg_segment, usize, tg_segment, usize)> {
+    extern "C" fn iter(
+        aseg: tg_segment,
+        aidx: libc::c_int,
+        bseg: tg_segment,
+        bidx: libc::c_int,
+        udata: *mut libc::c_void,
+    ) -> bool {
+        let pairs = unsafe { &mut *(udata as *mut Vec<(tg_segment, usize, tg_segment, usize)>) };
+        pairs.push((aseg, aidx as usize, bseg, bidx as usize));
+        true
+    }
+
+    let mut pairs = Vec::new();
+    let udata = &mut pairs as *mut Vec<_> as *mut libc::c_void;
+    unsafe { tg_ring_ring_search(a, b, iter, udata) };
+    pairs
+}
+
+/// Returns a point where the boundaries of two rings cross or overlap,
+/// ignoring places where they only touch.
+fn crossing(a: &tg_ring, b: &tg_ring) -> Option<tg_point> {
+    search(a, b)
+        .into_iter()
+        .find_map(|(s, _, t, _)| match contact(s, t)? {
+            Contact::Touch(_) => None,
+            contact => Some(contact.point()),
+        })
+}
+
+/// Returns a vertex of `inner` outside `outer`, if any. Only meaningful
+/// when the two don't cross.
+fn outside<'a>(inner: &'a tg_ring, outer: &tg_ring) -> Option<&'a tg_point> {
+    let outer = outer.as_geom();
+    inner
+        .points()
+        .iter()
+        .find(|p| !outer.intersects_xy(p.x, p.y))
+}
+
+/// How two segments meet.
+enum Contact {
+    /// Their interiors cross at a single point.
+    Cross(tg_point),
+    /// They're collinear and share more than a point.
+    Overlap(tg_point),
+    /// They meet at an endpoint of one of them.
+    Touch(tg_point),
+}
+
+impl Contact {
+    fn point(&self) -> tg_point {
+        match *self {
+            Contact::Cross(p) | Contact::Overlap(p) | Contact::Touch(p) => p,
+        }
+    }
+}
+
+fn orient(a: tg_point, b: tg_point, c: tg_point) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Returns true if `p`, known to be collinear with `s`, lies within it.
+fn within(p: tg_point, s: tg_segment) -> bool {
+    p.x >= s.a.x.min(s.b.x)
+        && p.x <= s.a.x.max(s.b.x)
+        && p.y >= s.a.y.min(s.b.y)
+        && p.y <= s.a.y.max(s.b.y)
+}
+
+fn contact(s: tg_segment, t: tg_segment) -> Option<Contact> {
+    let d1 = orient(t.a, t.b, s.a);
+    let d2 = orient(t.a, t.b, s.b);
+    let d3 = orient(s.a, s.b, t.a);
+    let d4 = orient(s.a, s.b, t.b);
+    if d1 == 0.0 && d2 == 0.0 {
+        // Collinear: find the shared stretch along the segment's longer axis.
+        let along_x = (s.b.x - s.a.x).abs() >= (s.b.y - s.a.y).abs();
+        let key = |p: tg_point| if along_x { p.x } else { p.y };
+        let (s0, s1) = if key(s.a) <= key(s.b) {
+            (s.a, s.b)
+        } else {
+            (s.b, s.a)
+        };
+        let (t0, t1) = if key(t.a) <= key(t.b) {
+            (t.a, t.b)
+        } else {
+            (t.b, t.a)
+        };
+        let start = if key(s0) >= key(t0) { s0 } else { t0 };
+        let end = if key(s1) <= key(t1) { s1 } else { t1 };
+        let shared = key(end) - key(start);
+        return if shared > 0.0 {
+            Some(Contact::Overlap(start))
+        } else if shared == 0.0 {
+            Some(Contact::Touch(start))
+        } else {
+            None
+        };
+    }
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        let r = d1 / (d1 - d2);
+        return Some(Contact::Cross(tg_point {
+            x: s.a.x + r * (s.b.x - s.a.x),
+            y: s.a.y + r * (s.b.y - s.a.y),
+        }));
+    }
+    [(d1, s.a, t), (d2, s.b, t), (d3, t.a, s), (d4, t.b, s)]
+        .into_iter()
+        .find(|&(d, p, seg)| d == 0.0 && within(p, seg))
+        .map(|(_, p, _)| Contact::Touch(p))
+}
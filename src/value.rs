@@ -0,0 +1,67 @@
+//! Attribute values read alongside geometries from vector file formats.
+
+use std::fmt;
+
+/// A single attribute value, such as a FlatGeobuf property or a column of a
+/// shapefile's `.dbf` table.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    /// A JSON document, kept as text.
+    Json(String),
+    /// A date or date-time, kept as text in the form the file stores it.
+    DateTime(String),
+    Binary(Vec<u8>),
+}
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Returns the value as a string slice if it holds text.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) | Value::Json(s) | Value::DateTime(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an `i64` if it holds an integer that fits.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Int(v) => Some(v),
+            Value::UInt(v) => i64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an `f64` if it holds a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Int(v) => Some(v as f64),
+            Value::UInt(v) => Some(v as f64),
+            Value::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::Int(v) => write!(f, "{}", v),
+            Value::UInt(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{}", v),
+            Value::String(s) | Value::Json(s) | Value::DateTime(s) => f.write_str(s),
+            Value::Binary(b) => write!(f, "<{} bytes>", b.len()),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Finds centroids and points on the surface of geometries.
+
+use tg_sys::tg_point;
+
+mod common;
+
+use common::wkt;
+
+fn assert_close(actual: tg_point, x: f64, y: f64) {
+    assert!(
+        (actual.x - x).abs() <= 1e-9 && (actual.y - y).abs() <= 1e-9,
+        "({} {}) != ({} {})",
+        actual.x,
+        actual.y,
+        x,
+        y
+    );
+}
+
+/// A U opening upwards, 10 wide and tall, with arms 3 wide, whose centroid
+/// falls in the gap between the arms.
+const U: &str = "POLYGON((0 0,10 0,10 10,7 10,7 3,3 3,3 10,0 10,0 0))";
+
+#[test]
+fn concave_polygon() {
+    let geom = wkt(U);
+    // The bar along the bottom, 30 units at y = 1.5, and the two arms, 42
+    // units at y = 6.5.
+    let centroid = geom.centroid().point();
+    assert_close(centroid, 5.0, (30.0 * 1.5 + 42.0 * 6.5) / 72.0);
+    assert!(!geom.intersects_xy(centroid.x, centroid.y));
+
+    // The point on the surface is inside, and about as far from the edges
+    // as any point can be: the widest circle fits where an arm meets the
+    // bar, with a radius of 3√2 / (1 + √2).
+    let p = geom.point_on_surface().point();
+    assert!(geom.intersects_xy(p.x, p.y));
+    let exterior = geom.poly().unwrap().exterior();
+    let nearest = exterior.closest_point(p).unwrap();
+    let clearance = (nearest.x - p.x).hypot(nearest.y - p.y);
+    let best = 3.0 * 2f64.sqrt() / (1.0 + 2f64.sqrt());
+    assert!(clearance <= best + 1e-9, "{}", clearance);
+    assert!(clearance >= best - 10.0 * 1e-3, "{}", clearance);
+}
+
+#[test]
+fn polygon_with_a_hole() {
+    // The hole takes 9 units around (2.5 2.5) from the 100 around (5 5).
+    let expected = (100.0 * 5.0 - 9.0 * 2.5) / 91.0;
+    for input in [
+        "POLYGON((0 0,10 0,10 10,0 10,0 0),(1 1,4 1,4 4,1 4,1 1))",
+        "POLYGON((0 0,10 0,10 10,0 10,0 0),(1 1,1 4,4 4,4 1,1 1))",
+        "POLYGON((0 0,0 10,10 10,10 0,0 0),(1 1,4 1,4 4,1 4,1 1))",
+    ] {
+        assert_close(wkt(input).centroid().point(), expected, expected);
+    }
+
+    // The centre of a ring is in its hole.
+    let ring = wkt("POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,8 2,8 8,2 8,2 2))");
+    assert_close(ring.centroid().point(), 5.0, 5.0);
+    let p = ring.point_on_surface().point();
+    assert!(ring.intersects_xy(p.x, p.y), "{:?}", p);
+}
+
+#[test]
+fn multipolygon() {
+    // A 2 by 2 square around (1 1) and a 4 by 4 one around (12 2).
+    let geom = wkt("MULTIPOLYGON(((0 0,2 0,2 2,0 2,0 0)),((10 0,14 0,14 4,10 4,10 0)))");
+    assert_close(
+        geom.centroid().point(),
+        (4.0 * 1.0 + 16.0 * 12.0) / 20.0,
+        (4.0 * 1.0 + 16.0 * 2.0) / 20.0,
+    );
+    // The point on the surface is in the larger square, at its middle.
+    let p = geom.point_on_surface().point();
+    assert!(
+        (p.x - 12.0).abs() <= 0.02 && (p.y - 2.0).abs() <= 0.02,
+        "{:?}",
+        p
+    );
+}
+
+#[test]
+fn highest_dimension_wins() {
+    let cases = [
+        // Any area outweighs lines and points, however long or many.
+        (
+            "GEOMETRYCOLLECTION(POINT(100 100),LINESTRING(50 50,60 60,500 60),\
+             POLYGON((0 0,2 0,2 2,0 2,0 0)))",
+            (1.0, 1.0),
+        ),
+        (
+            "GEOMETRYCOLLECTION(MULTIPOINT(100 100,200 200),LINESTRING(0 0,10 0))",
+            (5.0, 0.0),
+        ),
+        (
+            "GEOMETRYCOLLECTION(POINT(1 2),GEOMETRYCOLLECTION(POINT(3 4)))",
+            (2.0, 3.0),
+        ),
+    ];
+    for (input, (x, y)) in cases {
+        let geom = wkt(input);
+        assert_close(geom.centroid().point(), x, y);
+        let p = geom.point_on_surface().point();
+        assert!(geom.intersects_xy(p.x, p.y), "{}: {:?}", input, p);
+    }
+
+    // A polygon with no area counts as its boundary.
+    let geom = wkt("GEOMETRYCOLLECTION(POINT(100 100),POLYGON((0 0,4 0,2 0,0 0)))");
+    assert_close(geom.centroid().point(), 2.0, 0.0);
+}
+
+#[test]
+fn empty_input() {
+    for input in ["POINT EMPTY", "POLYGON EMPTY", "GEOMETRYCOLLECTION EMPTY"] {
+        let geom = wkt(input);
+        assert!(geom.centroid().is_empty(), "{}", input);
+        assert!(geom.point_on_surface().is_empty(), "{}", input);
+    }
+}
+
+#[test]
+fn slivers_give_up_in_time() {
+    // A trillion times longer than it is wide, so the search runs out of
+    // cells long before they shrink to its width.
+    let geom = wkt("POLYGON((0 0,1000000 0,1000000 0.000001,0 0))");
+    let p = geom.point_on_surface().point();
+    let rect = geom.rect();
+    assert!(p.x >= rect.min.x && p.x <= rect.max.x, "{:?}", p);
+    assert!(p.y >= rect.min.y && p.y <= rect.max.y, "{:?}", p);
+}
@@ -0,0 +1,151 @@
+//! Runs the `tg` command-line tool, which needs the `cli` feature.
+
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn tg(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tg"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Commands that don't read stdin may exit before it's written.
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn convert() {
+    let output = tg(&["convert", "--to", "geojson", "POINT(1 2)"], "");
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "{\"type\":\"Point\",\"coordinates\":[1,2]}\n"
+    );
+
+    let output = tg(
+        &["convert", "--to", "wkt"],
+        "POINT(1 2)\n\nLINESTRING(0 0,1 1)\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "POINT(1 2)\nLINESTRING(0 0,1 1)\n");
+
+    let hex = tg(&["convert", "--to", "hex", "POINT(1 2)"], "");
+    let hex = stdout(&hex);
+    let output = tg(&["convert", "--to", "wkt", hex.trim()], "");
+    assert_eq!(stdout(&output), "POINT(1 2)\n");
+}
+
+#[test]
+fn convert_reports_bad_lines_and_keeps_going() {
+    let output = tg(
+        &["convert", "--to", "wkt"],
+        "POINT(1 2)\nPOINT(\nPOINT(3 4)\n",
+    );
+    assert!(!output.status.success());
+    assert_eq!(stdout(&output), "POINT(1 2)\nPOINT(3 4)\n");
+    assert!(stderr(&output).starts_with("tg: stdin:2: "));
+}
+
+#[test]
+fn wkb_is_not_an_alias_for_hex() {
+    let output = tg(&["convert", "--to", "wkb", "POINT(1 2)"], "");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("hex"));
+}
+
+#[test]
+fn predicate() {
+    let square = "POLYGON((0 0,10 0,10 10,0 10,0 0))";
+    let output = tg(&["predicate", "contains", square, "POINT(5 5)"], "");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "true\n");
+
+    // Each left-hand geometry is tested against the right-hand side.
+    let output = tg(
+        &["predicate", "intersects", "-", square],
+        "POINT(5 5)\nPOINT(50 50)\n",
+    );
+    assert_eq!(stdout(&output), "true\nfalse\n");
+
+    let output = tg(&["predicate", "touches", square, square], "");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn stdin_is_read_once() {
+    for args in [
+        &["predicate", "equals", "-", "-"][..],
+        &["convert", "--to", "wkt", "-", "-"],
+        &["info", "-", "POINT(3 4)", "-"],
+        &["validate", "-", "-"],
+    ] {
+        let output = tg(args, "POINT(1 2)\n");
+        assert!(!output.status.success(), "{:?}", args);
+        assert!(stdout(&output).is_empty(), "{:?}", args);
+        assert!(stderr(&output).contains("stdin"), "{:?}", args);
+    }
+}
+
+#[test]
+fn info() {
+    let output = tg(
+        &[
+            "info",
+            "POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,3 2,3 3,2 2))",
+        ],
+        "",
+    );
+    assert!(output.status.success());
+    let info = stdout(&output);
+    assert!(info.starts_with("type=Polygon dims=2 z=false m=false empty=false feature=false"));
+    assert!(info.contains(" rect=0,0,10,10 points=9 "));
+
+    let output = tg(&["info", "POINT Z(1 2 3)"], "");
+    assert!(stdout(&output).starts_with("type=Point dims=3 z=true m=false"));
+}
+
+#[test]
+fn validate() {
+    let output = tg(&["validate", "POLYGON((0 0,1 0,1 1,0 1,0 0))"], "");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "argument: valid\n");
+
+    let output = tg(&["validate", "POLYGON((0 0,1 1,1 0,0 1,0 0))"], "");
+    assert!(!output.status.success());
+    assert!(stdout(&output).starts_with("argument: invalid: "));
+}
+
+#[test]
+fn validate_reports_unreadable_files_and_keeps_going() {
+    let output = tg(
+        &[
+            "validate",
+            "@does/not/exist.wkt",
+            "POLYGON((0 0,1 0,1 1,0 1,0 0))",
+        ],
+        "",
+    );
+    assert!(!output.status.success());
+    assert_eq!(stdout(&output), "argument: valid\n");
+    assert!(stderr(&output).contains("does/not/exist.wkt"));
+}
+
+#[test]
+fn usage() {
+    assert!(tg(&["--help"], "").status.success());
+    assert!(!tg(&[], "").status.success());
+    assert!(!tg(&["frobnicate"], "").status.success());
+}
@@ -0,0 +1,93 @@
+//! Deterministic inputs shared by the tests and the benchmarks, so they run
+//! offline, failures reproduce, and benchmarks give comparable numbers
+//! across tg upgrades. The benchmarks include this module by path.
+
+#![allow(dead_code)]
+
+use tg_sys::{tg_index, tg_point, Geom};
+
+/// The seed most inputs start from.
+pub const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// The indexing options worth comparing.
+pub const INDEXES: [(&str, tg_index); 3] = [
+    ("none", tg_index::TG_NONE),
+    ("natural", tg_index::TG_NATURAL),
+    ("ystripes", tg_index::TG_YSTRIPES),
+];
+
+/// A xorshift generator.
+pub struct Rng(u64);
+
+impl Rng {
+    /// Starts from a nonzero seed.
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    /// Returns a value in [0, 1).
+    pub fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a value in [min, max).
+    pub fn range(&mut self, min: f64, max: f64) -> f64 {
+        min + self.next() * (max - min)
+    }
+
+    /// Returns a point in the square from (min, min) to (max, max).
+    pub fn point(&mut self, min: f64, max: f64) -> tg_point {
+        tg_point {
+            x: self.range(min, max),
+            y: self.range(min, max),
+        }
+    }
+}
+
+/// Parses WKT that the test knows to be valid.
+pub fn wkt(wkt: &str) -> Geom {
+    Geom::parse_wkt(wkt).unwrap()
+}
+
+/// A wobbly circle with `n` vertices around `center`, so the ring index has
+/// real work to do. The closing point is repeated.
+pub fn ring(n: usize, center: tg_point, radius: f64) -> Vec<tg_point> {
+    (0..=n)
+        .map(|i| {
+            let theta = (i % n) as f64 / n as f64 * std::f64::consts::TAU;
+            let r = radius * (1.0 + 0.1 * (theta * 17.0).sin());
+            tg_point {
+                x: center.x + r * theta.cos(),
+                y: center.y + r * theta.sin(),
+            }
+        })
+        .collect()
+}
+
+/// The wobbly circle as Polygon WKT, centered on the origin with radius 100.
+pub fn polygon_wkt(n: usize) -> String {
+    polygon_wkt_at(n, tg_point { x: 0.0, y: 0.0 }, 100.0)
+}
+
+pub fn polygon_wkt_at(n: usize, center: tg_point, radius: f64) -> String {
+    let coords: Vec<String> = ring(n, center, radius)
+        .iter()
+        .map(|p| format!("{} {}", p.x, p.y))
+        .collect();
+    format!("POLYGON(({}))", coords.join(","))
+}
+
+/// The wobbly circle as a Polygon, centered on the origin with radius 100.
+pub fn polygon(n: usize, ix: tg_index) -> Geom {
+    Geom::parse_wkt_ix(&polygon_wkt(n), ix).unwrap()
+}
+
+/// Uniformly scattered points over a square slightly larger than the
+/// polygon.
+pub fn points(n: usize) -> Vec<tg_point> {
+    let mut rng = Rng::new(SEED);
+    (0..n).map(|_| rng.point(-120.0, 120.0)).collect()
+}
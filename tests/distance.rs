@@ -0,0 +1,264 @@
+//! Finds closest points and distances between geometries.
+
+use tg_sys::{tg_index, tg_point, Geom, Line, PathDistance, Ring};
+
+mod common;
+
+use common::{wkt, Rng, SEED};
+
+fn pt(x: f64, y: f64) -> tg_point {
+    tg_point { x, y }
+}
+
+/// The distance from `p` to the nearest segment, comparing every one.
+fn brute_force(points: &[tg_point], p: tg_point) -> f64 {
+    points
+        .windows(2)
+        .map(|seg| {
+            let (a, b) = (seg[0], seg[1]);
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            let len2 = dx * dx + dy * dy;
+            let t = if len2 > 0.0 {
+                (((p.x - a.x) * dx + (p.y - a.y) * dy) / len2).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            (a.x + t * dx - p.x).hypot(a.y + t * dy - p.y)
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+#[test]
+fn closest_point_on_each_type() {
+    let p = pt(13.0, 4.0);
+    let cases = [
+        ("POINT(1 2)", pt(1.0, 2.0)),
+        ("MULTIPOINT(0 0,12 5,20 20)", pt(12.0, 5.0)),
+        ("LINESTRING(0 0,10 0,10 10)", pt(10.0, 4.0)),
+        ("MULTILINESTRING((0 0,0 10),(20 0,20 10))", pt(20.0, 4.0)),
+        ("POLYGON((0 0,10 0,10 10,0 10,0 0))", pt(10.0, 4.0)),
+        (
+            "MULTIPOLYGON(((0 0,10 0,10 10,0 10,0 0)),((12 6,14 6,14 8,12 6)))",
+            pt(13.0, 6.0),
+        ),
+        (
+            "GEOMETRYCOLLECTION(POINT(30 30),LINESTRING(14 0,14 10))",
+            pt(14.0, 4.0),
+        ),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(wkt(input).closest_point(p), Some(expected), "{}", input);
+    }
+}
+
+#[test]
+fn closest_point_of_a_polygon() {
+    let geom = wkt("POLYGON((0 0,10 0,10 10,0 10,0 0),(4 4,6 4,6 6,4 6,4 4))");
+    let poly = geom.poly().unwrap();
+    // Inside the polygon, the point is its own closest point, but the ring
+    // on its own is only a boundary.
+    assert_eq!(geom.closest_point(pt(2.0, 3.0)), Some(pt(2.0, 3.0)));
+    assert_eq!(poly.closest_point(pt(2.0, 3.0)), Some(pt(2.0, 3.0)));
+    assert_eq!(
+        poly.exterior().closest_point(pt(2.0, 3.0)),
+        Some(pt(0.0, 3.0))
+    );
+    // Inside the hole, the hole's edge is nearer than the exterior.
+    assert_eq!(geom.closest_point(pt(5.0, 4.5)), Some(pt(5.0, 4.0)));
+    // On the boundary.
+    assert_eq!(geom.closest_point(pt(10.0, 5.0)), Some(pt(10.0, 5.0)));
+}
+
+#[test]
+fn closest_point_of_degenerate_input() {
+    let line = Line::new(&[pt(3.0, 4.0)]);
+    assert_eq!(line.closest_point(pt(0.0, 0.0)), Some(pt(3.0, 4.0)));
+    assert_eq!(Line::new(&[]).closest_point(pt(0.0, 0.0)), None);
+
+    for input in [
+        "POINT EMPTY",
+        "LINESTRING EMPTY",
+        "POLYGON EMPTY",
+        "GEOMETRYCOLLECTION EMPTY",
+        "GEOMETRYCOLLECTION(POINT EMPTY)",
+    ] {
+        assert_eq!(wkt(input).closest_point(pt(0.0, 0.0)), None, "{}", input);
+    }
+}
+
+#[test]
+fn distance_to() {
+    let square = wkt("POLYGON((0 0,10 0,10 10,0 10,0 0))");
+    // Intersecting, including a line wholly inside the polygon.
+    for input in ["POINT(5 5)", "LINESTRING(-5 5,5 5)", "LINESTRING(2 2,8 8)"] {
+        assert_eq!(square.distance_to(&wkt(input)), 0.0, "{}", input);
+    }
+
+    // Disjoint, with the nearest points on segments of both.
+    let cases = [
+        ("POINT(13 14)", 5.0),
+        ("LINESTRING(12 -5,12 20)", 2.0),
+        ("POLYGON((15 5,20 0,20 10,15 5))", 5.0),
+        ("MULTIPOINT(-3 -4,30 30)", 5.0),
+    ];
+    for (input, expected) in cases {
+        let other = wkt(input);
+        assert_eq!(square.distance_to(&other), expected, "{}", input);
+        assert_eq!(other.distance_to(&square), expected, "{}", input);
+    }
+    // A point in a hole is outside the polygon.
+    let holed = wkt("POLYGON((0 0,10 0,10 10,0 10,0 0),(4 4,6 4,6 6,4 6,4 4))");
+    assert_eq!(holed.distance_to(&wkt("POINT(5 4.5)")), 0.5);
+
+    for input in ["POINT EMPTY", "POLYGON EMPTY", "GEOMETRYCOLLECTION EMPTY"] {
+        let empty = wkt(input);
+        assert_eq!(square.distance_to(&empty), f64::INFINITY, "{}", input);
+        assert_eq!(empty.distance_to(&square), f64::INFINITY, "{}", input);
+        assert_eq!(empty.distance_to(&empty), f64::INFINITY, "{}", input);
+    }
+}
+
+#[test]
+fn matches_a_brute_force_scan() {
+    // A random walk, so segments cross and double back.
+    let mut rng = Rng::new(SEED);
+    let mut at = pt(0.0, 0.0);
+    let points: Vec<tg_point> = (0..1000)
+        .map(|_| {
+            let step = rng.point(-10.0, 10.0);
+            at = pt(at.x + step.x, at.y + step.y);
+            at
+        })
+        .collect();
+    let queries: Vec<tg_point> = (0..200).map(|_| rng.point(-150.0, 150.0)).collect();
+
+    for ix in [tg_index::TG_NONE, tg_index::TG_NATURAL] {
+        let line = Line::new_ix(&points, ix);
+        for &p in &queries {
+            let closest = line.closest_point(p).unwrap();
+            let expected = brute_force(&points, p);
+            let actual = (closest.x - p.x).hypot(closest.y - p.y);
+            assert!(
+                (actual - expected).abs() <= 1e-9,
+                "{:?}: {} != {}",
+                p,
+                actual,
+                expected
+            );
+        }
+    }
+
+    // Between two disjoint lines, the distance is the nearest any vertex of
+    // one comes to the other.
+    let line = Geom::new_linestring(&Line::new(&points));
+    let far: Vec<tg_point> = points.iter().map(|p| pt(p.x + 5000.0, p.y)).collect();
+    let other = Geom::new_linestring(&Line::new(&far));
+    let expected = points
+        .iter()
+        .map(|&p| brute_force(&far, p))
+        .chain(far.iter().map(|&p| brute_force(&points, p)))
+        .fold(f64::INFINITY, f64::min);
+    assert!((line.distance_to(&other) - expected).abs() <= 1e-9);
+}
+
+fn line(coords: &[(f64, f64)]) -> Line {
+    let points: Vec<tg_point> = coords.iter().map(|&(x, y)| pt(x, y)).collect();
+    Line::new(&points)
+}
+
+/// Checks that the witnesses are the distance apart.
+fn assert_witnessed(d: PathDistance) {
+    assert_eq!((d.a.x - d.b.x).hypot(d.a.y - d.b.y), d.distance, "{:?}", d);
+}
+
+#[test]
+fn hausdorff_and_frechet_known_values() {
+    let road = line(&[(0.0, 0.0), (5.0, 0.0), (10.0, 0.0)]);
+    let track = line(&[(0.0, 1.0), (5.0, 3.0), (10.0, 1.0)]);
+
+    // The track strays farthest at its middle vertex, and from the road's
+    // side the witnesses come out in the same order.
+    let hausdorff = road.hausdorff_distance(&track).unwrap();
+    assert_eq!(
+        hausdorff,
+        PathDistance {
+            distance: 3.0,
+            a: pt(5.0, 0.0),
+            b: pt(5.0, 3.0),
+        }
+    );
+    let swapped = track.hausdorff_distance(&road).unwrap();
+    assert_eq!((swapped.a, swapped.b), (hausdorff.b, hausdorff.a));
+
+    let frechet = road.frechet_distance(&track).unwrap();
+    assert_eq!(frechet, hausdorff);
+
+    // Measured against segments rather than vertices, a witness can fall
+    // between vertices.
+    let straight = line(&[(0.0, 0.0), (10.0, 0.0)]);
+    let hausdorff = straight.hausdorff_distance(&track).unwrap();
+    assert_eq!(hausdorff.distance, 3.0);
+    assert_eq!(hausdorff.a, pt(5.0, 0.0));
+    assert_witnessed(hausdorff);
+}
+
+#[test]
+fn frechet_notices_reversed_order() {
+    let road = line(&[(0.0, 0.0), (5.0, 0.0), (10.0, 0.0)]);
+    let backwards = line(&[(10.0, 1.0), (5.0, 3.0), (0.0, 1.0)]);
+
+    // The same places, so the Hausdorff distance doesn't change, but the
+    // walkers now start at opposite ends.
+    let hausdorff = road.hausdorff_distance(&backwards).unwrap();
+    assert_eq!(hausdorff.distance, 3.0);
+    let frechet = road.frechet_distance(&backwards).unwrap();
+    assert_eq!(frechet.distance, 10f64.hypot(1.0));
+    assert_witnessed(frechet);
+    assert!(frechet.distance >= hausdorff.distance);
+
+    // Against itself reversed, a line is at Hausdorff distance zero.
+    let reversed = line(&[(10.0, 0.0), (5.0, 0.0), (0.0, 0.0)]);
+    assert_eq!(road.hausdorff_distance(&reversed).unwrap().distance, 0.0);
+    assert_eq!(road.frechet_distance(&reversed).unwrap().distance, 10.0);
+    assert_eq!(road.frechet_distance(&road).unwrap().distance, 0.0);
+}
+
+#[test]
+fn ring_distances() {
+    let square = |size: f64| {
+        let points = [
+            pt(0.0, 0.0),
+            pt(size, 0.0),
+            pt(size, size),
+            pt(0.0, size),
+            pt(0.0, 0.0),
+        ];
+        Ring::new(&points)
+    };
+    let (small, large) = (square(10.0), square(12.0));
+    // The far corners are the farthest apart.
+    let hausdorff = small.hausdorff_distance(&large).unwrap();
+    assert_eq!(hausdorff.distance, 2f64.hypot(2.0));
+    assert_eq!((hausdorff.a, hausdorff.b), (pt(10.0, 10.0), pt(12.0, 12.0)));
+    let frechet = small.frechet_distance(&large).unwrap();
+    assert_eq!(frechet, hausdorff);
+}
+
+#[test]
+fn empty_lines_have_no_distance() {
+    let empty = Line::new(&[]);
+    let road = line(&[(0.0, 0.0), (10.0, 0.0)]);
+    assert_eq!(empty.hausdorff_distance(&road), None);
+    assert_eq!(road.hausdorff_distance(&empty), None);
+    assert_eq!(empty.hausdorff_distance(&empty), None);
+    assert_eq!(empty.frechet_distance(&road), None);
+    assert_eq!(road.frechet_distance(&empty), None);
+
+    // A single point is enough, and the ends of the road are farthest from
+    // it.
+    let point = line(&[(5.0, 2.0)]);
+    let d = point.hausdorff_distance(&road).unwrap();
+    assert_eq!(d.distance, 5f64.hypot(2.0));
+    assert_eq!((d.a, d.b), (pt(5.0, 2.0), pt(0.0, 0.0)));
+    assert_eq!(point.frechet_distance(&road).unwrap().distance, d.distance);
+}
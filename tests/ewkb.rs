@@ -0,0 +1,163 @@
+//! Converts between PostGIS Extended WKB/WKT and tg geometries.
+
+use tg_sys::{tg_geom_type, Geom};
+
+const Z: u32 = 0x8000_0000;
+const M: u32 = 0x4000_0000;
+const SRID: u32 = 0x2000_0000;
+
+/// Builds a little-endian EWKB geometry header, followed by the SRID if there
+/// is one.
+fn header(code: u32, srid: Option<i32>) -> Vec<u8> {
+    let mut out = vec![1];
+    out.extend_from_slice(&(code | srid.map_or(0, |_| SRID)).to_le_bytes());
+    if let Some(srid) = srid {
+        out.extend_from_slice(&srid.to_le_bytes());
+    }
+    out
+}
+
+fn point(flags: u32, srid: Option<i32>, coords: &[f64]) -> Vec<u8> {
+    let mut out = header(1 | flags, srid);
+    for c in coords {
+        out.extend_from_slice(&c.to_le_bytes());
+    }
+    out
+}
+
+fn collection(srid: Option<i32>, members: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = header(7, srid);
+    out.extend_from_slice(&(members.len() as u32).to_le_bytes());
+    for member in members {
+        out.extend_from_slice(member);
+    }
+    out
+}
+
+fn code(ewkb: &[u8]) -> u32 {
+    u32::from_le_bytes(ewkb[1..5].try_into().unwrap())
+}
+
+#[test]
+fn point_dimensions_round_trip() {
+    for (flags, coords, wkt) in [
+        (0, &[1.0, 2.0][..], "POINT(1 2)"),
+        (Z, &[1.0, 2.0, 3.0][..], "POINT Z(1 2 3)"),
+        (M, &[1.0, 2.0, 4.0][..], "POINT M(1 2 4)"),
+        (Z | M, &[1.0, 2.0, 3.0, 4.0][..], "POINT ZM(1 2 3 4)"),
+    ] {
+        for srid in [None, Some(4326), Some(-1)] {
+            let ewkb = point(flags, srid, coords);
+            let geom = Geom::parse_ewkb(&ewkb).unwrap();
+            assert_eq!(geom.srid(), srid);
+            assert_eq!(geom.dims().has_z(), flags & Z != 0);
+            assert_eq!(geom.dims().has_m(), flags & M != 0);
+            assert!(geom.equals(&Geom::parse_wkt(wkt).unwrap()));
+            assert_eq!(geom.to_ewkb(), ewkb, "{} {:?}", wkt, srid);
+
+            let hex = geom.to_ewkb_hex();
+            let back = Geom::parse_ewkb_hex(&hex.to_lowercase()).unwrap();
+            assert_eq!(back.srid(), srid);
+            assert_eq!(back.to_ewkb(), ewkb);
+
+            let ewkt = geom.to_ewkt();
+            match srid {
+                Some(srid) => assert!(ewkt.starts_with(&format!("SRID={};", srid))),
+                None => assert!(!ewkt.starts_with("SRID")),
+            }
+            let back = Geom::parse_ewkt(&ewkt).unwrap();
+            assert_eq!(back.srid(), srid);
+            assert_eq!(back.to_ewkb(), ewkb);
+        }
+    }
+}
+
+#[test]
+fn iso_and_big_endian_input() {
+    // ISO WKB codes the dimensions as 1000s rather than flag bits.
+    let mut iso = vec![1];
+    iso.extend_from_slice(&3001u32.to_le_bytes());
+    for c in [1.0f64, 2.0, 3.0, 4.0] {
+        iso.extend_from_slice(&c.to_le_bytes());
+    }
+    let geom = Geom::parse_ewkb(&iso).unwrap();
+    assert_eq!(geom.srid(), None);
+    assert_eq!(geom.to_ewkb(), point(Z | M, None, &[1.0, 2.0, 3.0, 4.0]));
+
+    let mut big = vec![0];
+    big.extend_from_slice(&(1 | Z | SRID).to_be_bytes());
+    big.extend_from_slice(&3857i32.to_be_bytes());
+    for c in [1.0f64, 2.0, 3.0] {
+        big.extend_from_slice(&c.to_be_bytes());
+    }
+    let geom = Geom::parse_ewkb(&big).unwrap();
+    assert_eq!(geom.srid(), Some(3857));
+    assert_eq!(geom.to_ewkb(), point(Z, Some(3857), &[1.0, 2.0, 3.0]));
+}
+
+#[test]
+fn nested_collection_srid() {
+    let inner = collection(None, &[point(0, None, &[1.0, 2.0])]);
+    let ewkb = collection(Some(4326), &[point(0, None, &[5.0, 6.0]), inner]);
+    let geom = Geom::parse_ewkb(&ewkb).unwrap();
+    assert_eq!(geom.srid(), Some(4326));
+    assert_eq!(geom.geom_type(), tg_geom_type::TG_GEOMETRYCOLLECTION);
+    assert_eq!(geom.geometries().len(), 2);
+
+    // Only the outermost geometry carries the SRID on the way out.
+    let out = geom.to_ewkb();
+    assert_eq!(code(&out), 7 | SRID);
+    let first = &out[1 + 4 + 4 + 4..];
+    assert_eq!(code(first), 1);
+    let second = &first[1 + 4 + 2 * 8..];
+    assert_eq!(code(second), 7);
+    assert_eq!(code(&second[1 + 4 + 4..]), 1);
+    assert_eq!(out, ewkb);
+
+    // SRIDs on members are accepted, but only the outermost one is kept.
+    let tagged = collection(
+        Some(4326),
+        &[collection(Some(3857), &[point(0, Some(3857), &[1.0, 2.0])])],
+    );
+    let geom = Geom::parse_ewkb(&tagged).unwrap();
+    assert_eq!(geom.srid(), Some(4326));
+    assert_eq!(
+        geom.to_ewkb(),
+        collection(
+            Some(4326),
+            &[collection(None, &[point(0, None, &[1.0, 2.0])])]
+        )
+    );
+    let untagged = collection(None, &[point(0, Some(3857), &[1.0, 2.0])]);
+    assert_eq!(Geom::parse_ewkb(&untagged).unwrap().srid(), None);
+}
+
+#[test]
+fn malformed_input() {
+    for ewkb in [
+        &[][..],
+        &[2, 1, 0, 0, 0][..],
+        &point(0, None, &[1.0])[..],
+        &header(1 | SRID, None)[..],
+        &header(99, None)[..],
+    ] {
+        assert!(Geom::parse_ewkb(ewkb).is_err(), "{:?}", ewkb);
+    }
+    assert!(Geom::parse_ewkb_hex("0101").is_err());
+    assert!(Geom::parse_ewkb_hex("010").is_err());
+    assert!(Geom::parse_ewkb_hex("zz").is_err());
+    assert!(Geom::parse_ewkt("SRID=4326 POINT(1 2)").is_err());
+    assert!(Geom::parse_ewkt("SRID=x;POINT(1 2)").is_err());
+}
+
+#[test]
+fn deeply_nested_collections_are_rejected() {
+    let mut ewkb = Vec::new();
+    for _ in 0..2000 {
+        ewkb.extend_from_slice(&header(7, None));
+        ewkb.extend_from_slice(&1u32.to_le_bytes());
+    }
+    ewkb.extend_from_slice(&collection(None, &[]));
+    let err = Geom::parse_ewkb(&ewkb).unwrap_err();
+    assert!(err.to_string().contains("nested too deeply"), "{}", err);
+}
@@ -0,0 +1,305 @@
+//! Reads the FlatGeobuf files in `tests/fixtures`:
+//!
+//! - `points.fgb`: a 10x10 grid of points at integer coordinates, in row
+//!   order, with `name` and `id` columns, EPSG:4326, and a spatial index.
+//! - `mixed.fgb`: a polygon with a hole, a two-part multipolygon and a
+//!   linestring, with mixed geometry types and no spatial index.
+//! - `linez.fgb`: a single LineString Z, with a spatial index.
+
+use std::fs::File;
+use std::io::{Cursor, Read};
+
+use tg_sys::{
+    tg_geom, tg_geom_type, tg_point, tg_rect, Dims, FgbFeature, FgbReader, ReadError, Value,
+};
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn rect(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> tg_rect {
+    tg_rect {
+        min: tg_point { x: min_x, y: min_y },
+        max: tg_point { x: max_x, y: max_y },
+    }
+}
+
+#[test]
+fn header() {
+    let reader = FgbReader::open(fixture("points.fgb")).unwrap();
+    let header = reader.header();
+    assert_eq!(header.name(), Some("points"));
+    assert_eq!(header.geometry_type(), Some(tg_geom_type::TG_POINT));
+    assert_eq!(header.dims(), Dims::Xy);
+    assert_eq!(header.columns().collect::<Vec<_>>(), ["name", "id"]);
+    assert_eq!(header.features_count(), 100);
+    assert_eq!(header.index_node_size(), 16);
+    assert_eq!(header.srid(), Some(4326));
+    assert_eq!(header.envelope(), Some(rect(0.0, 0.0, 9.0, 9.0)));
+}
+
+#[test]
+fn read_all_features() {
+    let mut reader = FgbReader::open(fixture("points.fgb")).unwrap();
+    let features: Vec<_> = reader.features().map(Result::unwrap).collect();
+    assert_eq!(features.len(), 100);
+    for (i, feature) in features.iter().enumerate() {
+        assert_eq!(feature.index(), i);
+        let p = feature.geom().point();
+        assert_eq!((p.x, p.y), ((i % 10) as f64, (i / 10) as f64));
+        assert_eq!(feature.geom().srid(), Some(4326));
+        assert_eq!(
+            feature.property("name"),
+            Some(&Value::String(format!("p{}", i)))
+        );
+        assert_eq!(feature.property("id"), Some(&Value::Int(i as i64)));
+    }
+}
+
+#[test]
+fn select_with_index() {
+    let mut reader = FgbReader::open(fixture("points.fgb")).unwrap();
+    let selected: Vec<usize> = reader
+        .select_rect(rect(2.0, 4.0, 3.5, 5.0))
+        .unwrap()
+        .map(|feature| feature.unwrap().index())
+        .collect();
+    assert_eq!(selected, [42, 43, 52, 53]);
+
+    let outside = reader.select_rect(rect(20.0, 20.0, 30.0, 30.0)).unwrap();
+    assert_eq!(outside.count(), 0);
+}
+
+#[test]
+fn select_from_memory() {
+    let mut bytes = Vec::new();
+    File::open(fixture("points.fgb"))
+        .unwrap()
+        .read_to_end(&mut bytes)
+        .unwrap();
+    let mut reader = FgbReader::new(Cursor::new(bytes)).unwrap();
+    let selected = reader.select_rect(rect(9.0, 9.0, 9.0, 9.0)).unwrap();
+    let selected: Vec<usize> = selected.map(|f| f.unwrap().index()).collect();
+    assert_eq!(selected, [99]);
+}
+
+#[test]
+fn mixed_types_without_index() {
+    let mut reader = FgbReader::open(fixture("mixed.fgb")).unwrap();
+    assert_eq!(reader.header().geometry_type(), None);
+    assert_eq!(reader.header().index_node_size(), 0);
+
+    let features: Vec<_> = reader.features().map(Result::unwrap).collect();
+    assert_eq!(features.len(), 3);
+
+    let poly = features[0].geom().poly().unwrap();
+    assert_eq!(features[0].geom().geom_type(), tg_geom_type::TG_POLYGON);
+    assert_eq!(poly.holes().len(), 1);
+    assert_eq!(features[0].property("area"), Some(&Value::Float(96.0)));
+    assert_eq!(features[0].property("water"), Some(&Value::Bool(false)));
+
+    assert_eq!(
+        features[1].geom().geom_type(),
+        tg_geom_type::TG_MULTIPOLYGON
+    );
+    assert_eq!(features[1].geom().polys().len(), 2);
+    assert_eq!(features[1].property("water"), Some(&Value::Bool(true)));
+
+    assert_eq!(features[2].geom().geom_type(), tg_geom_type::TG_LINESTRING);
+    assert!(features[2].properties().is_empty());
+
+    // Without an index, features are filtered by their bounding boxes.
+    let selected: Vec<usize> = reader
+        .select_rect(rect(21.0, 1.0, 22.0, 2.0))
+        .unwrap()
+        .map(|f| f.unwrap().index())
+        .collect();
+    assert_eq!(selected, [1]);
+}
+
+#[test]
+fn z_coordinates() {
+    let mut reader = FgbReader::open(fixture("linez.fgb")).unwrap();
+    assert_eq!(reader.header().dims(), Dims::Xyz);
+    let features: Vec<_> = reader
+        .select_rect(rect(0.0, 0.0, 1.0, 1.0))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(features.len(), 1);
+    let geom = features[0].geom();
+    assert_eq!(geom.dims(), Dims::Xyz);
+    assert_eq!(geom.line().unwrap().points().len(), 3);
+    assert_eq!(geom.extra_coords(), [10.0, 20.0, 30.0]);
+}
+
+#[test]
+fn not_flatgeobuf() {
+    let err = FgbReader::new(Cursor::new(b"{\"type\":\"FeatureCollection\"}".to_vec()));
+    assert!(matches!(err, Err(ReadError::Syntax { offset: 0, .. })));
+}
+
+/// A FlatGeobuf file whose header holds only a geometry type, a feature
+/// count and an index node size, followed by `rest`.
+fn file(geometry_type: u8, features_count: u64, index_node_size: u16, rest: &[u8]) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend(28u32.to_le_bytes());
+    // The vtable, at 4, for fields 0 to 9.
+    header.extend(24u16.to_le_bytes());
+    header.extend(16u16.to_le_bytes());
+    for field in 0..10 {
+        let offset: u16 = match field {
+            2 => 14,
+            8 => 4,
+            9 => 12,
+            _ => 0,
+        };
+        header.extend(offset.to_le_bytes());
+    }
+    // The table, at 28.
+    header.extend(24i32.to_le_bytes());
+    header.extend(features_count.to_le_bytes());
+    header.extend(index_node_size.to_le_bytes());
+    header.extend([geometry_type, 0]);
+
+    let mut file = b"fgb\x03fgb\x00".to_vec();
+    file.extend((header.len() as u32).to_le_bytes());
+    file.extend(header);
+    file.extend(rest);
+    file
+}
+
+/// A feature holding `depth` nested GeometryCollections, each listing the
+/// next one `parts` times, around an empty one.
+fn nested_feature(depth: usize, parts: usize) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend(32u32.to_le_bytes());
+    // The vtable shared by every Geometry table, at 4: the parts offset, then
+    // the type.
+    buf.extend(20u16.to_le_bytes());
+    buf.extend(12u16.to_le_bytes());
+    for field in 0..8 {
+        let offset: u16 = match field {
+            6 => 8,
+            7 => 4,
+            _ => 0,
+        };
+        buf.extend(offset.to_le_bytes());
+    }
+    // The Feature's vtable, at 24, and the Feature, at 32.
+    buf.extend([6, 0, 8, 0, 4, 0, 0, 0]);
+    buf.extend(8i32.to_le_bytes());
+    buf.extend(4u32.to_le_bytes());
+    for level in 0..=depth {
+        let table = buf.len();
+        let parts = if level == depth { 0 } else { parts };
+        buf.extend(((table - 4) as i32).to_le_bytes());
+        buf.extend(8u32.to_le_bytes());
+        buf.extend([7, 0, 0, 0]);
+        buf.extend((parts as u32).to_le_bytes());
+        let next = table + 16 + 4 * parts;
+        for i in 0..parts {
+            buf.extend(((next - (table + 16 + 4 * i)) as u32).to_le_bytes());
+        }
+    }
+    let mut feature = (buf.len() as u32).to_le_bytes().to_vec();
+    feature.extend(buf);
+    feature
+}
+
+fn syntax_error(bytes: Vec<u8>) -> String {
+    match FgbReader::new(Cursor::new(bytes)) {
+        Err(ReadError::Syntax { offset: 8, message }) => message,
+        Err(err) => panic!("{}", err),
+        Ok(_) => panic!("accepted a corrupt header"),
+    }
+}
+
+#[test]
+fn corrupt_header() {
+    // Indexes too large to address.
+    for count in [u64::MAX, u64::MAX / 40] {
+        assert!(syntax_error(file(1, count, 16, &[])).contains("too large"));
+    }
+    // An index larger than the file.
+    assert!(syntax_error(file(1, 1 << 40, 2, &[0; 64])).contains("end of the file"));
+    // More features than there are bytes for, without an index.
+    assert!(syntax_error(file(1, 1000, 0, &[0; 64])).contains("don't fit"));
+
+    // A count of zero means unknown, and the features run to the end.
+    let empty = FgbReader::new(Cursor::new(file(1, 0, 16, &[])));
+    assert_eq!(empty.unwrap().features().count(), 0);
+}
+
+#[test]
+fn corrupt_index() {
+    // One feature has a root node and a leaf, each a box and an offset.
+    let index = |root: u64, leaf: u64| {
+        let mut index = Vec::new();
+        for offset in [root, leaf] {
+            for v in [0.0f64, 0.0, 1.0, 1.0] {
+                index.extend(v.to_le_bytes());
+            }
+            index.extend(offset.to_le_bytes());
+        }
+        index.extend([0; 4]);
+        index
+    };
+    let everywhere = rect(-1.0, -1.0, 2.0, 2.0);
+    for (root, leaf, message) in [
+        (5, 0, "index node out of range"),
+        (0, 0, "index node out of range"),
+        (1, u64::MAX, "feature offset out of range"),
+    ] {
+        let mut reader = FgbReader::new(Cursor::new(file(1, 1, 16, &index(root, leaf)))).unwrap();
+        match reader.select_rect(everywhere) {
+            Err(ReadError::Syntax { message: m, .. }) => assert_eq!(m, message),
+            Err(err) => panic!("{}", err),
+            Ok(_) => panic!("accepted a corrupt index"),
+        }
+    }
+
+    // A leaf past the end of the file is reported when it's read.
+    let mut reader = FgbReader::new(Cursor::new(file(1, 1, 16, &index(1, 1000)))).unwrap();
+    let mut features = reader.select_rect(everywhere).unwrap();
+    assert!(matches!(features.next(), Some(Err(ReadError::Io(_)))));
+    assert!(features.next().is_none());
+}
+
+fn read_nested(depth: usize, parts: usize) -> Result<FgbFeature, ReadError> {
+    let bytes = file(0, 1, 0, &nested_feature(depth, parts));
+    let mut reader = FgbReader::new(Cursor::new(bytes)).unwrap();
+    reader.features().next().unwrap()
+}
+
+#[test]
+fn nested_collections() {
+    let feature = read_nested(10, 1).unwrap();
+    let mut geom: &tg_geom = feature.geom();
+    for _ in 0..10 {
+        assert_eq!(geom.geom_type(), tg_geom_type::TG_GEOMETRYCOLLECTION);
+        assert_eq!(geom.geometries().len(), 1);
+        geom = geom.geometries().next().unwrap();
+    }
+    assert!(geom.is_empty());
+
+    // Deep enough to overflow the stack, if the decoder let it.
+    match read_nested(100_000, 1) {
+        Err(ReadError::Feature { index: 0, error }) => {
+            assert!(error.message().contains("nested too deeply"), "{}", error)
+        }
+        other => panic!("{:?}", other.map(|f| f.geom().to_wkt())),
+    }
+
+    // Each collection lists the next twice, so decoding every part would
+    // take 2^64 steps.
+    match read_nested(64, 2) {
+        Err(ReadError::Feature { index: 0, error }) => {
+            assert!(error.message().contains("overlap"), "{}", error)
+        }
+        other => panic!("{:?}", other.map(|f| f.geom().to_wkt())),
+    }
+    // Sharing a part a few times is harmless.
+    let feature = read_nested(2, 2).unwrap();
+    assert_eq!(feature.geom().geometries().len(), 2);
+}
@@ -0,0 +1,148 @@
+//! Checks the geodesic measures against published reference values for the
+//! WGS84 ellipsoid.
+
+use tg_sys::{distance_m, tg_point, Geom, Line};
+
+fn point(lon: f64, lat: f64) -> tg_point {
+    tg_point { x: lon, y: lat }
+}
+
+fn dms(degrees: f64, minutes: f64, seconds: f64) -> f64 {
+    degrees + minutes / 60.0 + seconds / 3600.0
+}
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{} differs from {} by more than {}",
+        actual,
+        expected,
+        tolerance
+    );
+}
+
+/// The total surface area of the WGS84 ellipsoid, from GeographicLib.
+const ELLIPSOID_AREA: f64 = 510_065_621_724_088.5;
+
+#[test]
+fn vincenty_flinders_peak_to_buninyong() {
+    // The worked example from Vincenty (1975).
+    let flinders_peak = point(dms(144.0, 25.0, 29.5244), -dms(37.0, 57.0, 3.7203));
+    let buninyong = point(dms(143.0, 55.0, 35.3839), -dms(37.0, 39.0, 10.1561));
+    assert_close(distance_m(flinders_peak, buninyong), 54_972.271, 0.001);
+}
+
+#[test]
+fn jfk_to_heathrow() {
+    // The example from the GeodSolve documentation.
+    assert_close(
+        distance_m(point(-73.8, 40.6), point(-0.5, 51.6)),
+        5_551_759.400,
+        0.001,
+    );
+}
+
+#[test]
+fn coincident_points() {
+    assert_eq!(distance_m(point(3.0, 4.0), point(3.0, 4.0)), 0.0);
+}
+
+#[test]
+fn nearly_antipodal_points() {
+    // Vincenty's formula doesn't converge here; half the meridian
+    // circumference is 20,003,931 m, and the fallback is within half a
+    // percent of it.
+    let d = distance_m(point(0.0, 0.0), point(179.7, 0.0));
+    assert_close(d, 20_003_931.0, 20_003_931.0 * 0.005);
+}
+
+#[test]
+fn quadrant_lengths() {
+    let equator = Line::new(&[point(0.0, 0.0), point(45.0, 0.0), point(90.0, 0.0)]);
+    assert_close(equator.length_m(), 10_018_754.171, 0.001);
+    let meridian = Line::new(&[point(0.0, 0.0), point(0.0, 90.0)]);
+    assert_close(meridian.length_m(), 10_001_965.729, 0.001);
+}
+
+#[test]
+fn octant_area() {
+    // An eighth of the ellipsoid, bounded by the equator and two meridians.
+    let octant = Geom::parse_wkt("POLYGON((0 0,90 0,90 90,0 90,0 0))").unwrap();
+    assert_close(octant.area_m2(), ELLIPSOID_AREA / 8.0, 1.0);
+
+    // Winding doesn't matter.
+    let octant = Geom::parse_wkt("POLYGON((0 0,0 90,90 90,90 0,0 0))").unwrap();
+    assert_close(octant.area_m2(), ELLIPSOID_AREA / 8.0, 1.0);
+}
+
+#[test]
+fn holes_and_multipolygons() {
+    let cell = Geom::parse_wkt("POLYGON((0 0,1 0,1 1,0 1,0 0))").unwrap();
+    let area = cell.area_m2();
+    let holed = Geom::parse_wkt("POLYGON((0 0,2 0,2 1,0 1,0 0),(1 0,2 0,2 1,1 1,1 0))").unwrap();
+    assert_close(holed.area_m2(), 2.0 * area - area, 1e-3 * area);
+    let multi = Geom::parse_wkt("MULTIPOLYGON(((0 0,1 0,1 1,0 1,0 0)),((0 -1,1 -1,1 0,0 0,0 -1)))")
+        .unwrap();
+    assert_close(multi.area_m2(), 2.0 * area, 1e-6 * area);
+}
+
+#[test]
+fn antimeridian() {
+    let cell = Geom::parse_wkt("POLYGON((0 0,1 0,1 1,0 1,0 0))").unwrap();
+    let across = Geom::parse_wkt("POLYGON((179.5 0,-179.5 0,-179.5 1,179.5 1,179.5 0))").unwrap();
+    assert_close(across.area_m2(), cell.area_m2(), 1.0);
+
+    let line = Line::new(&[point(179.5, 0.0), point(-179.5, 0.0)]);
+    assert_close(line.length_m(), 10_018_754.171 / 90.0, 0.001);
+}
+
+#[test]
+fn distance_to_geometry() {
+    // One degree of latitude at the equator is 110,574.389 m.
+    let line = Geom::parse_wkt("LINESTRING(-1 0,1 0)").unwrap();
+    assert_close(line.distance_m(point(0.0, 1.0)), 110_574.389, 0.001);
+    assert_eq!(line.distance_m(point(0.5, 0.0)), 0.0);
+
+    let square = Geom::parse_wkt("POLYGON((-1 -1,1 -1,1 1,-1 1,-1 -1))").unwrap();
+    assert_eq!(square.distance_m(point(0.0, 0.0)), 0.0);
+    assert_close(square.distance_m(point(0.0, 2.0)), 110_575.0, 10.0);
+
+    let points = Geom::parse_wkt("MULTIPOINT(10 10,0 1)").unwrap();
+    assert_close(points.distance_m(point(0.0, 0.0)), 110_574.389, 0.001);
+}
+
+#[test]
+fn diagonal_edges() {
+    // Areas with geodesic edges from GeographicLib's PolygonArea, and how
+    // far the great-circle edges on the authalic sphere are allowed to
+    // stray from them. Straight edges in an equal-area cylindrical
+    // projection were off by 1e-2 for the one-degree triangle at 60°N.
+    let cases = [
+        ("POLYGON((0 0,0.1 0,0 0.1,0 0))", 61_545_392.271, 1e-8),
+        ("POLYGON((0 0,1 0,0 1,0 0))", 6_154_854_786.721, 1e-6),
+        ("POLYGON((0 60,1 60,0 61,0 60))", 3_108_617_674.812, 1e-5),
+        (
+            "POLYGON((0 44,1 45,0 46,-1 45,0 44))",
+            17_524_999_670.587,
+            1e-6,
+        ),
+        ("POLYGON((0 0,10 0,0 10,0 0))", 618_642_563_592.717, 1e-4),
+        ("POLYGON((0 40,10 40,0 50,0 40))", 475_251_396_006.607, 1e-4),
+        (
+            "POLYGON((0 35,10 45,0 55,-10 45,0 35))",
+            1_754_674_524_634.082,
+            1e-4,
+        ),
+    ];
+    for (wkt, expected, relative) in cases {
+        let area = Geom::parse_wkt(wkt).unwrap().area_m2();
+        assert!(
+            ((area - expected) / expected).abs() <= relative,
+            "{}: {} differs from {} by more than {:e}",
+            wkt,
+            area,
+            expected,
+            relative
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Reads GeoJSON FeatureCollections with the streaming `FeatureReader`.
+
+use std::io::Read;
+
+use tg_sys::{tg_geom_type, Feature, FeatureReader, ReadError};
+
+/// A reader that returns one byte per call, so every token straddles the
+/// buffer boundary.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match (self.0.split_first(), buf.first_mut()) {
+            (Some((&b, rest)), Some(out)) => {
+                *out = b;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+fn read(json: &str) -> Vec<Result<Feature, ReadError>> {
+    let all: Vec<_> = FeatureReader::new(json.as_bytes()).collect();
+    let trickled: Vec<_> = FeatureReader::new(Trickle(json.as_bytes())).collect();
+    assert_eq!(format!("{:?}", all), format!("{:?}", trickled));
+    all
+}
+
+fn syntax_offset(result: &Result<Feature, ReadError>) -> u64 {
+    match result {
+        Err(ReadError::Syntax { offset, .. }) => *offset,
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+}
+
+const POINT: &str =
+    r#"{"type":"Feature","geometry":{"type":"Point","coordinates":[1,2]},"properties":{}}"#;
+
+#[test]
+fn features_in_order() {
+    let json = format!(
+        r#"{{"type":"FeatureCollection","features":[{},
+            {{"type":"Feature","geometry":{{"type":"LineString","coordinates":[[0,0],[1,1]]}},"properties":null}}
+        ],"bbox":[0,0,1,2]}}"#,
+        POINT
+    );
+    let features = read(&json);
+    assert_eq!(features.len(), 2);
+    let first = features[0].as_ref().unwrap();
+    assert_eq!(first.index(), 0);
+    assert_eq!(first.geom().to_wkt(), "POINT(1 2)");
+    let second = features[1].as_ref().unwrap();
+    assert_eq!(second.index(), 1);
+    assert_eq!(second.geom().geom_type(), tg_geom_type::TG_LINESTRING);
+}
+
+#[test]
+fn members_around_features_are_skipped() {
+    let json = format!(
+        r#" {{ "name" : "a \"features\": [ name" , "crs" : {{"type":"name","properties":{{"name":"x]}}"}}}},
+            "features" : [ {} ] , "type" : "FeatureCollection" , "count" : 1 }} "#,
+        POINT
+    );
+    let features = read(&json);
+    assert_eq!(features.len(), 1);
+    assert!(features[0].is_ok());
+}
+
+#[test]
+fn escapes_and_nested_properties() {
+    let properties =
+        r#"{"name":"a \"quoted\" } ] name","slash":"\\","nested":{"a":[1,{"b":"]}"}],"c":{}}}"#;
+    let json = format!(
+        r#"{{"type":"FeatureCollection","features":[{{"type":"Feature","properties":{},"geometry":{{"type":"Point","coordinates":[3,4]}}}}]}}"#,
+        properties
+    );
+    let features = read(&json);
+    assert_eq!(features.len(), 1);
+    let feature = features[0].as_ref().unwrap();
+    assert_eq!(feature.geom().to_wkt(), "POINT(3 4)");
+    let extra = feature.geom().extra_json().unwrap();
+    assert!(extra.contains(r#""a \"quoted\" } ] name""#), "{}", extra);
+    assert!(
+        extra.contains(r#""nested":{"a":[1,{"b":"]}"}],"c":{}}"#),
+        "{}",
+        extra
+    );
+}
+
+#[test]
+fn rejected_feature_reports_its_index() {
+    let bad = r#"{"type":"Feature","geometry":{"type":"Nope","coordinates":[]},"properties":{}}"#;
+    let json = format!(
+        r#"{{"type":"FeatureCollection","features":[{},{},{}]}}"#,
+        POINT, bad, POINT
+    );
+    let features = read(&json);
+    assert_eq!(features.len(), 3);
+    assert!(features[0].is_ok());
+    match &features[1] {
+        Err(ReadError::Feature { index: 1, .. }) => {}
+        other => panic!("expected feature 1 to fail, got {:?}", other),
+    }
+    assert_eq!(features[2].as_ref().unwrap().index(), 2);
+}
+
+#[test]
+fn truncated_stream() {
+    let json = format!(
+        r#"{{"type":"FeatureCollection","features":[{},{{"type":"Fea"#,
+        POINT
+    );
+    let features = read(&json);
+    assert_eq!(features.len(), 2);
+    assert!(features[0].is_ok());
+    assert_eq!(syntax_offset(&features[1]), json.len() as u64);
+
+    for json in ["", "{", r#"{"type":"FeatureCollection","features":"#] {
+        let features = read(json);
+        assert_eq!(features.len(), 1);
+        assert_eq!(syntax_offset(&features[0]), json.len() as u64);
+    }
+}
+
+#[test]
+fn wrong_top_level_type() {
+    let offset = r#"{"type":"#.len() as u64;
+    for json in [
+        POINT,
+        r#"{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,0]]]}"#,
+        r#"{"type":"Foo"}"#,
+    ] {
+        let features = read(json);
+        assert_eq!(features.len(), 1, "{}", json);
+        assert_eq!(syntax_offset(&features[0]), offset);
+    }
+
+    // Without a type at all, the error comes at the end of the object.
+    let features = read(r#"{"features":[]}"#);
+    assert_eq!(features.len(), 1);
+    assert_eq!(syntax_offset(&features[0]), 14);
+
+    let features = read("[]");
+    assert_eq!(syntax_offset(&features[0]), 0);
+}
+
+#[test]
+fn malformed_separators() {
+    let json = format!(
+        r#"{{"type":"FeatureCollection","features":[{} {}]}}"#,
+        POINT, POINT
+    );
+    let features = read(&json);
+    assert_eq!(features.len(), 2);
+    assert!(features[0].is_ok());
+    let offset = r#"{"type":"FeatureCollection","features":["#.len() + POINT.len() + 1;
+    assert_eq!(syntax_offset(&features[1]), offset as u64);
+}
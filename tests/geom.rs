@@ -0,0 +1,44 @@
+//! Checks the safe geometry handle against the raw tg functions it wraps.
+
+use tg_sys::{tg_index, tg_point, Geom};
+
+mod common;
+
+use common::{polygon, Rng, SEED};
+
+/// Pseudo-random points over a square larger than the polygon, so some fall
+/// outside its bounding rectangle, plus a few exactly on its vertices.
+fn points(geom: &Geom, n: usize) -> Vec<tg_point> {
+    let mut rng = Rng::new(SEED);
+    let mut points: Vec<tg_point> = (0..n).map(|_| rng.point(-150.0, 150.0)).collect();
+    let exterior = geom.poly().unwrap().exterior().points();
+    points.extend(exterior.iter().step_by(97));
+    points
+}
+
+#[test]
+fn batch_point_queries_match_intersects_xy() {
+    for ix in [
+        tg_index::TG_NONE,
+        tg_index::TG_NATURAL,
+        tg_index::TG_YSTRIPES,
+    ] {
+        let geom = polygon(1000, ix);
+        let points = points(&geom, 10_000);
+        let expected: Vec<bool> = points
+            .iter()
+            .map(|p| geom.intersects_xy(p.x, p.y))
+            .collect();
+        assert!(expected.iter().any(|&inside| inside));
+        assert!(expected.iter().any(|&inside| !inside));
+        assert_eq!(geom.contains_points(&points), expected);
+        assert_eq!(geom.intersects_xy_batch(&points), expected);
+    }
+}
+
+#[test]
+fn batch_point_queries_on_empty_input() {
+    let geom = polygon(16, tg_index::TG_DEFAULT);
+    assert!(geom.contains_points(&[]).is_empty());
+    assert!(geom.intersects_xy_batch(&[]).is_empty());
+}
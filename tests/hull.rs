@@ -0,0 +1,77 @@
+//! Checks that convex hulls are convex, cover their input, and degrade to
+//! points and lines for degenerate input.
+
+use tg_sys::{tg_geom_type, tg_point, Geom};
+
+mod common;
+
+use common::Rng;
+
+/// Returns `n` pseudo-random points in a 1000x1000 square.
+fn random_points(n: usize, seed: u64) -> Vec<tg_point> {
+    let mut rng = Rng::new(seed);
+    (0..n).map(|_| rng.point(0.0, 1000.0)).collect()
+}
+
+fn assert_convex_cover(input: &Geom) {
+    let hull = input.convex_hull();
+    assert_eq!(hull.geom_type(), tg_geom_type::TG_POLYGON);
+    let exterior = hull.poly().unwrap().exterior();
+    assert!(exterior.convex(), "{} is not convex", hull.to_wkt());
+    assert!(!exterior.clockwise());
+    assert!(
+        hull.covers(input),
+        "{} doesn't cover its input",
+        hull.to_wkt()
+    );
+}
+
+#[test]
+fn random_multipoints() {
+    for seed in 1..=20 {
+        let input = Geom::new_multipoint(&random_points(500, seed));
+        assert_convex_cover(&input);
+    }
+}
+
+#[test]
+fn geometry_types() {
+    for wkt in [
+        "LINESTRING(0 0,5 5,10 0,15 5)",
+        "POLYGON((0 0,10 0,10 10,5 5,0 10,0 0))",
+        "POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,8 2,8 8,2 8,2 2))",
+        "MULTIPOLYGON(((0 0,1 0,1 1,0 1,0 0)),((5 5,6 5,6 6,5 6,5 5)))",
+        "GEOMETRYCOLLECTION(POINT(-3 4),LINESTRING(0 0,2 -7),POLYGON((1 1,4 1,4 4,1 1)))",
+    ] {
+        assert_convex_cover(&Geom::parse_wkt(wkt).unwrap());
+    }
+}
+
+#[test]
+fn collinear_points_are_dropped() {
+    let input = Geom::parse_wkt("MULTIPOINT(0 0,1 0,2 0,2 1,2 2,1 2,0 2,0 1,1 1)").unwrap();
+    let hull = input.convex_hull();
+    assert_eq!(hull.poly().unwrap().exterior().points().len(), 5);
+    assert_convex_cover(&input);
+}
+
+#[test]
+fn degenerate_input() {
+    let hull = Geom::parse_wkt("MULTIPOINT(3 4,3 4)")
+        .unwrap()
+        .convex_hull();
+    assert_eq!(hull.geom_type(), tg_geom_type::TG_POINT);
+    assert_eq!(hull.point(), tg_point { x: 3.0, y: 4.0 });
+
+    let hull = Geom::parse_wkt("LINESTRING(0 0,3 3,1 1,2 2)")
+        .unwrap()
+        .convex_hull();
+    assert_eq!(hull.geom_type(), tg_geom_type::TG_LINESTRING);
+    assert_eq!(
+        hull.line().unwrap().points(),
+        &[tg_point { x: 0.0, y: 0.0 }, tg_point { x: 3.0, y: 3.0 }]
+    );
+
+    let hull = Geom::parse_wkt("POINT EMPTY").unwrap().convex_hull();
+    assert!(hull.is_empty());
+}
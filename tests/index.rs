@@ -0,0 +1,146 @@
+//! Checks `GeomIndex` queries against a brute-force scan of the same
+//! geometries.
+
+use std::collections::BTreeSet;
+
+use tg_sys::{tg_point, tg_rect, Geom, GeomIndex, Predicate};
+
+mod common;
+
+use common::{Rng, SEED};
+
+/// A point in the 1000x1000 square the geometries are scattered over.
+fn point(rng: &mut Rng) -> tg_point {
+    rng.point(0.0, 1000.0)
+}
+
+fn rect(rng: &mut Rng, size: f64) -> tg_rect {
+    let min = point(rng);
+    let max = tg_point {
+        x: min.x + rng.next() * size,
+        y: min.y + rng.next() * size,
+    };
+    tg_rect { min, max }
+}
+
+/// Small squares, triangles and points scattered over a 1000x1000 square.
+fn geoms(n: usize, rng: &mut Rng) -> Vec<Geom> {
+    (0..n)
+        .map(|i| {
+            let r = rect(rng, 20.0);
+            let (a, b) = (r.min, r.max);
+            let wkt = match i % 3 {
+                0 => format!(
+                    "POLYGON(({} {},{} {},{} {},{} {},{} {}))",
+                    a.x, a.y, b.x, a.y, b.x, b.y, a.x, b.y, a.x, a.y
+                ),
+                1 => format!(
+                    "POLYGON(({} {},{} {},{} {},{} {}))",
+                    a.x, a.y, b.x, a.y, a.x, b.y, a.x, a.y
+                ),
+                _ => format!("POINT({} {})", a.x, a.y),
+            };
+            Geom::parse_wkt(&wkt).unwrap()
+        })
+        .collect()
+}
+
+fn ids<'a>(results: impl Iterator<Item = (usize, &'a Geom)>) -> BTreeSet<usize> {
+    results.map(|(id, _)| id).collect()
+}
+
+/// Checks every kind of query against a scan of `expected`, which holds the
+/// geometry for each live id.
+fn check(index: &GeomIndex, expected: &[Option<Geom>], rng: &mut Rng) {
+    let live = || {
+        expected
+            .iter()
+            .enumerate()
+            .filter_map(|(id, geom)| Some((id, geom.as_ref()?)))
+    };
+    assert_eq!(index.len(), live().count());
+    assert_eq!(ids(index.iter()), ids(live()));
+
+    for _ in 0..50 {
+        let rect = rect(rng, 100.0);
+        let scan = ids(live().filter(|(_, g)| g.intersects_rect(rect)));
+        assert_eq!(ids(index.query_rect(rect)), scan);
+        let scan = ids(live().filter(|(_, g)| g.rect().intersects_rect(rect)));
+        assert_eq!(ids(index.candidates(rect)), scan);
+
+        let point = point(rng);
+        let scan = ids(live().filter(|(_, g)| g.intersects_xy(point.x, point.y)));
+        assert_eq!(ids(index.query_point(point)), scan);
+
+        let query = Geom::new_point(point);
+        let scan = ids(live().filter(|(_, g)| g.disjoint(&query)));
+        assert_eq!(ids(index.query(&query, Predicate::Disjoint)), scan);
+
+        let nearest: Vec<(usize, f64)> = index
+            .nearest(point)
+            .map(|(id, _, dist)| (id, dist))
+            .collect();
+        assert_eq!(nearest.len(), index.len());
+        assert!(nearest.windows(2).all(|w| w[0].1 <= w[1].1));
+        for (id, dist) in nearest {
+            let rect = expected[id].as_ref().unwrap().rect();
+            assert_eq!(dist, rect.distance_to_point(point));
+        }
+    }
+}
+
+#[test]
+fn bulk_load() {
+    let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+    for n in [0, 1, 16, 17, 300, 5000] {
+        let geoms = geoms(n, &mut rng);
+        let index = GeomIndex::bulk_load(geoms.iter().cloned());
+        for (id, geom) in geoms.iter().enumerate() {
+            assert!(index.get(id).unwrap().equals(geom));
+        }
+        let expected: Vec<Option<Geom>> = geoms.into_iter().map(Some).collect();
+        check(&index, &expected, &mut rng);
+    }
+}
+
+#[test]
+fn insert_and_remove() {
+    let mut rng = Rng::new(SEED);
+    let mut index = GeomIndex::new();
+    let mut expected: Vec<Option<Geom>> = Vec::new();
+    for geom in geoms(1000, &mut rng) {
+        let id = index.insert(geom.clone());
+        assert_eq!(id, expected.len());
+        expected.push(Some(geom));
+    }
+    check(&index, &expected, &mut rng);
+
+    // Remove most of the geometries, which empties and shrinks the tree.
+    for id in (0..1000).filter(|id| id % 7 != 0) {
+        let removed = index.remove(id).unwrap();
+        assert!(removed.equals(expected[id].as_ref().unwrap()));
+        expected[id] = None;
+    }
+    assert!(index.remove(1).is_none());
+    assert!(index.remove(5000).is_none());
+    check(&index, &expected, &mut rng);
+
+    // New geometries take the ids that were freed.
+    for geom in geoms(200, &mut rng) {
+        let id = index.insert(geom.clone());
+        assert!(expected[id].is_none());
+        expected[id] = Some(geom);
+    }
+    check(&index, &expected, &mut rng);
+
+    // Emptying the index entirely leaves it usable.
+    for (id, geom) in expected.iter_mut().enumerate() {
+        assert_eq!(index.remove(id).is_some(), geom.take().is_some());
+    }
+    assert!(index.is_empty());
+    assert_eq!(index.nearest(point(&mut rng)).count(), 0);
+    let geom = Geom::parse_wkt("POINT(1 1)").unwrap();
+    let id = index.insert(geom.clone());
+    expected[id] = Some(geom);
+    check(&index, &expected, &mut rng);
+}
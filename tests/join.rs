@@ -0,0 +1,86 @@
+//! Checks spatial joins against a nested-loop join.
+
+use tg_sys::{spatial_join, Geom, Predicate};
+
+const PREDICATES: [Predicate; 7] = [
+    Predicate::Equals,
+    Predicate::Intersects,
+    Predicate::Disjoint,
+    Predicate::Contains,
+    Predicate::Within,
+    Predicate::Covers,
+    Predicate::CoveredBy,
+];
+
+fn parse(wkts: &[&str]) -> Vec<Geom> {
+    wkts.iter()
+        .map(|wkt| Geom::parse_wkt(wkt).unwrap())
+        .collect()
+}
+
+/// Points on a grid, some inside, on the edge of, or outside the zones.
+fn points() -> Vec<Geom> {
+    (0..15)
+        .flat_map(|x| (0..15).map(move |y| (x as f64 * 2.5 - 5.0, y as f64 * 2.5 - 5.0)))
+        .map(|(x, y)| Geom::parse_wkt(&format!("POINT({} {})", x, y)).unwrap())
+        .collect()
+}
+
+fn zones() -> Vec<Geom> {
+    parse(&[
+        "POLYGON((0 0,10 0,10 10,0 10,0 0))",
+        "POLYGON((5 5,20 5,20 20,5 20,5 5),(10 10,15 10,15 15,10 15,10 10))",
+        "POLYGON((0 0,10 0,10 10,0 10,0 0))",
+        "LINESTRING(-5 -5,25 25)",
+        "POINT(10 10)",
+        "POLYGON((100 100,101 100,101 101,100 100))",
+    ])
+}
+
+fn nested_loop(left: &[Geom], right: &[Geom], predicate: Predicate) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, l) in left.iter().enumerate() {
+        for (j, r) in right.iter().enumerate() {
+            if predicate.eval(l, r) {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+#[test]
+fn matches_nested_loop() {
+    let mut left = points();
+    left.extend(zones());
+    let right = zones();
+    for predicate in PREDICATES {
+        let expected = nested_loop(&left, &right, predicate);
+        let pairs: Vec<_> = spatial_join(&left, &right, predicate).collect();
+        assert_eq!(pairs, expected, "{:?}", predicate);
+    }
+}
+
+#[test]
+fn empty_sides() {
+    let zones = zones();
+    assert_eq!(
+        spatial_join(&[] as &[Geom], &zones, Predicate::Intersects).count(),
+        0
+    );
+    assert_eq!(spatial_join(&zones, &[], Predicate::Intersects).count(), 0);
+    assert_eq!(spatial_join(&zones, &[], Predicate::Disjoint).count(), 0);
+}
+
+#[cfg(all(feature = "rayon", feature = "atomics"))]
+#[test]
+fn parallel_matches_nested_loop() {
+    let mut left = points();
+    left.extend(zones());
+    let right = zones();
+    for predicate in PREDICATES {
+        let expected = nested_loop(&left, &right, predicate);
+        let pairs = tg_sys::par_spatial_join(&left, &right, predicate);
+        assert_eq!(pairs, expected, "{:?}", predicate);
+    }
+}
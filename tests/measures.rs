@@ -0,0 +1,65 @@
+//! Sums area, length and perimeter over the parts of a geometry.
+
+mod common;
+
+use common::wkt;
+
+/// Returns the area, length and perimeter of the geometry.
+fn measures(input: &str) -> (f64, f64, f64) {
+    let geom = wkt(input);
+    (geom.area(), geom.length(), geom.perimeter())
+}
+
+#[test]
+fn polygon_with_holes() {
+    // The holes are wound both ways, which makes no difference.
+    let geom =
+        wkt("POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,4 2,4 4,2 4,2 2),(6 6,6 9,9 9,9 6,6 6))");
+    let poly = geom.poly().unwrap();
+    assert_eq!(poly.exterior().area(), 100.0);
+    assert_eq!(poly.area(), 100.0 - 4.0 - 9.0);
+    assert_eq!(poly.perimeter(), 40.0 + 8.0 + 12.0);
+    assert_eq!(geom.area(), poly.area());
+    assert_eq!(geom.perimeter(), poly.perimeter());
+    assert_eq!(geom.length(), 0.0);
+}
+
+#[test]
+fn multipolygon() {
+    let (area, length, perimeter) = measures(
+        "MULTIPOLYGON(((0 0,10 0,10 10,0 10,0 0)),\
+         ((20 0,25 0,25 5,20 5,20 0),(21 1,22 1,22 2,21 2,21 1)))",
+    );
+    assert_eq!(area, 100.0 + 25.0 - 1.0);
+    assert_eq!(length, 0.0);
+    assert_eq!(perimeter, 40.0 + 20.0 + 4.0);
+}
+
+#[test]
+fn mixed_collection() {
+    let (area, length, perimeter) = measures(
+        "GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(0 0,3 4),\
+         POLYGON((0 0,10 0,10 10,0 10,0 0)),MULTILINESTRING((0 0,0 1),(0 0,2 0)),\
+         GEOMETRYCOLLECTION(POLYGON((20 0,22 0,22 2,20 2,20 0)),MULTIPOINT(0 0,1 1)),\
+         POLYGON EMPTY)",
+    );
+    assert_eq!(area, 100.0 + 4.0);
+    assert_eq!(length, 5.0 + 1.0 + 2.0);
+    assert_eq!(perimeter, 40.0 + 8.0);
+}
+
+#[test]
+fn each_measure_ignores_the_other_dimension() {
+    // A line that closes on itself still has no area or perimeter, and a
+    // polygon's boundary isn't length.
+    assert_eq!(measures("LINESTRING(0 0,3 0,3 4,0 0)"), (0.0, 12.0, 0.0));
+    assert_eq!(measures("POLYGON((0 0,3 0,3 4,0 0))"), (6.0, 0.0, 12.0));
+    for input in [
+        "POINT(1 2)",
+        "MULTIPOINT(0 0,1 1)",
+        "LINESTRING EMPTY",
+        "GEOMETRYCOLLECTION EMPTY",
+    ] {
+        assert_eq!(measures(input), (0.0, 0.0, 0.0), "{}", input);
+    }
+}
@@ -0,0 +1,196 @@
+//! Encodes vector tiles and decodes them back.
+
+use tg_sys::{decode_mvt, encode_mvt_layer, tg_geom_type, Geom, MvtFeature, MvtLayer, Value};
+
+mod common;
+
+use common::wkt;
+
+fn feature<'a>(geom: &'a Geom, properties: &'a [(String, Value)]) -> MvtFeature<'a> {
+    MvtFeature {
+        id: None,
+        geom,
+        properties,
+    }
+}
+
+fn round_trip(features: &[MvtFeature], z: u32, x: u32, y: u32) -> MvtLayer {
+    let tile = encode_mvt_layer("test", features, z, x, y, 4096).unwrap();
+    let mut layers = decode_mvt(&tile).unwrap();
+    assert_eq!(layers.len(), 1);
+    layers.pop().unwrap()
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> usize {
+    haystack
+        .windows(needle.len())
+        .filter(|w| *w == needle)
+        .count()
+}
+
+#[test]
+fn zigzag_command_stream() {
+    // At zoom 0, (0, 0) is the middle of the tile and 90°E three quarters of
+    // the way across.
+    let point = wkt("POINT(0 0)");
+    let tile = encode_mvt_layer("test", &[feature(&point, &[])], 0, 0, 0, 4096).unwrap();
+    // MoveTo(1), then zigzag(2048) twice.
+    let geometry = [0x22, 5, 9, 0x80, 0x20, 0x80, 0x20];
+    assert_eq!(contains(&tile, &geometry), 1);
+
+    let line = wkt("LINESTRING(0 0,90 0,0 0)");
+    let tile = encode_mvt_layer("test", &[feature(&line, &[])], 0, 0, 0, 4096).unwrap();
+    // MoveTo(1) to (2048, 2048), then LineTo(2) by (+1024, 0) and (-1024, 0).
+    let geometry = [
+        0x22, 12, 9, 0x80, 0x20, 0x80, 0x20, 18, 0x80, 0x10, 0, 0xff, 0x0f, 0,
+    ];
+    assert_eq!(contains(&tile, &geometry), 1);
+
+    let layer = &decode_mvt(&tile).unwrap()[0];
+    assert_eq!(layer.name(), "test");
+    assert_eq!(layer.extent(), 4096);
+    assert_eq!(
+        layer.features()[0].geom().to_wkt(),
+        "LINESTRING(2048 2048,3072 2048,2048 2048)"
+    );
+}
+
+#[test]
+fn winding() {
+    // Both windings of the input come out the same, with the exterior
+    // clockwise on screen and the hole counterclockwise.
+    for input in [
+        "POLYGON((-90 -45,90 -45,90 45,-90 45,-90 -45),(-45 -20,-45 20,45 20,45 -20,-45 -20))",
+        "POLYGON((-90 -45,-90 45,90 45,90 -45,-90 -45),(-45 -20,45 -20,45 20,-45 20,-45 -20))",
+    ] {
+        let geom = wkt(input);
+        let layer = round_trip(&[feature(&geom, &[])], 0, 0, 0);
+        assert_eq!(layer.features().len(), 1);
+        let geom = layer.features()[0].geom();
+        assert_eq!(geom.geom_type(), tg_geom_type::TG_POLYGON, "{}", input);
+        let poly = geom.poly().unwrap();
+        assert_eq!(poly.holes().len(), 1);
+        // y points down in tile coordinates, which mirrors the winding.
+        assert!(!poly.exterior().clockwise());
+        assert!(poly.holes().next().unwrap().clockwise());
+        assert!(geom.intersects_xy(2048.0 - 1000.0, 2048.0));
+        assert!(!geom.intersects_xy(2048.0, 2048.0));
+    }
+}
+
+#[test]
+fn clipped_to_the_buffer() {
+    // Tile 2/1/1 spans 90°W to 0°, with a buffer of 256 units around it.
+    let (lo, hi) = (-256.0, 4096.0 + 256.0);
+
+    let line = wkt("LINESTRING(-120 30,-45 30,60 30)");
+    let layer = round_trip(&[feature(&line, &[])], 2, 1, 1);
+    let clipped = layer.features()[0].geom();
+    let points = clipped.line().unwrap().points();
+    assert_eq!(points.len(), 3);
+    assert_eq!(points[0].x, lo);
+    assert_eq!(points[1].x, 2048.0);
+    assert_eq!(points[2].x, hi);
+
+    let world = wkt("POLYGON((-170 -80,170 -80,170 80,-170 80,-170 -80))");
+    let layer = round_trip(&[feature(&world, &[])], 2, 1, 1);
+    let rect = layer.features()[0].geom().rect();
+    assert_eq!((rect.min.x, rect.min.y), (lo, lo));
+    assert_eq!((rect.max.x, rect.max.y), (hi, hi));
+
+    // A line that leaves the tile and comes back is split in two.
+    let line = wkt("LINESTRING(-80 30,60 30,60 40,-80 40)");
+    let layer = round_trip(&[feature(&line, &[])], 2, 1, 1);
+    let geom = layer.features()[0].geom();
+    assert_eq!(geom.geom_type(), tg_geom_type::TG_MULTILINESTRING);
+    assert_eq!(geom.lines().len(), 2);
+}
+
+#[test]
+fn quick_reject() {
+    let inside = wkt("POINT(-45 30)");
+    let in_buffer = wkt("POINT(3 30)");
+    let outside = wkt("POINT(8 30)");
+    let far = wkt("POLYGON((100 -60,120 -60,120 -50,100 -60))");
+    let layer = round_trip(
+        &[
+            feature(&inside, &[]),
+            feature(&in_buffer, &[]),
+            feature(&outside, &[]),
+            feature(&far, &[]),
+        ],
+        2,
+        1,
+        1,
+    );
+    assert_eq!(layer.features().len(), 2);
+    assert!(layer.features()[1].geom().point().x > 4096.0);
+
+    // Beyond the Web Mercator limit, points are clamped onto the edge tiles.
+    let pole = wkt("POINT(0 89)");
+    let layer = round_trip(&[feature(&pole, &[])], 0, 0, 0);
+    assert_eq!(layer.features()[0].geom().to_wkt(), "POINT(2048 0)");
+}
+
+#[test]
+fn tags_are_deduplicated() {
+    let a = [
+        ("name".to_string(), Value::String("a".to_string())),
+        ("kind".to_string(), Value::Int(1)),
+        ("gone".to_string(), Value::Null),
+    ];
+    let b = [
+        ("kind".to_string(), Value::Int(1)),
+        ("name".to_string(), Value::String("b".to_string())),
+    ];
+    let point = wkt("POINT(0 0)");
+    let features = [
+        feature(&point, &a),
+        feature(&point, &b),
+        feature(&point, &a),
+    ];
+    let tile = encode_mvt_layer("test", &features, 0, 0, 0, 4096).unwrap();
+    assert_eq!(contains(&tile, b"\x1a\x04name"), 1);
+    assert_eq!(contains(&tile, b"\x1a\x04kind"), 1);
+    assert_eq!(contains(&tile, b"gone"), 0);
+    // The Int(1) value, as a zigzag sint.
+    assert_eq!(contains(&tile, &[0x22, 2, 0x30, 2]), 1);
+    assert_eq!(contains(&tile, &[0x22, 3, 0x0a, 1, b'a']), 1);
+
+    let layer = &decode_mvt(&tile).unwrap()[0];
+    assert_eq!(layer.features().len(), 3);
+    assert_eq!(layer.features()[0].properties(), &a[..2]);
+    assert_eq!(layer.features()[1].properties(), &b[..]);
+    assert_eq!(layer.features()[2].properties(), &a[..2]);
+}
+
+#[test]
+fn collections_share_id_and_properties() {
+    let properties = [("name".to_string(), Value::String("pair".to_string()))];
+    let geom = wkt("GEOMETRYCOLLECTION(POINT(0 0),LINESTRING(0 0,90 0))");
+    let layer = round_trip(
+        &[MvtFeature {
+            id: Some(7),
+            geom: &geom,
+            properties: &properties,
+        }],
+        0,
+        0,
+        0,
+    );
+    assert_eq!(layer.features().len(), 2);
+    for feature in layer.features() {
+        assert_eq!(feature.id(), Some(7));
+        assert_eq!(feature.property("name"), Some(&properties[0].1));
+    }
+    assert_eq!(
+        layer.features()[1].geom().geom_type(),
+        tg_geom_type::TG_LINESTRING
+    );
+}
+
+#[test]
+fn zero_extent_is_rejected() {
+    let point = wkt("POINT(0 0)");
+    assert!(encode_mvt_layer("test", &[feature(&point, &[])], 0, 0, 0, 0).is_err());
+}
@@ -0,0 +1,125 @@
+//! Rewinds polygon rings with `oriented` and `to_geojson_rfc7946`.
+
+use tg_sys::{Geom, Orientation};
+
+mod common;
+
+use common::wkt;
+
+/// Counterclockwise, with a clockwise hole.
+const RFC7946: &str = "POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,2 4,4 4,4 2,2 2))";
+
+/// Clockwise, with a counterclockwise hole.
+const ESRI: &str = "POLYGON((0 0,0 10,10 10,10 0,0 0),(2 2,4 2,4 4,2 4,2 2))";
+
+#[test]
+fn polygons_are_rewound() {
+    let rfc = wkt(RFC7946);
+    let esri = wkt(ESRI);
+    assert!(rfc.is_oriented(Orientation::Rfc7946));
+    assert!(!rfc.is_oriented(Orientation::Esri));
+    assert!(esri.is_oriented(Orientation::Esri));
+
+    assert_eq!(esri.oriented(Orientation::Rfc7946).to_wkt(), RFC7946);
+    assert_eq!(rfc.oriented(Orientation::Esri).to_wkt(), ESRI);
+    assert_eq!(rfc.oriented(Orientation::Rfc7946).to_wkt(), RFC7946);
+
+    // Only the rings wound the wrong way are reversed.
+    let mixed = wkt("MULTIPOLYGON(((0 0,10 0,10 10,0 0)),((20 0,20 10,30 10,20 0)))");
+    let oriented = mixed.oriented(Orientation::Rfc7946);
+    assert_eq!(
+        oriented.to_wkt(),
+        "MULTIPOLYGON(((0 0,10 0,10 10,0 0)),((20 0,30 10,20 10,20 0)))"
+    );
+    assert_eq!(oriented.area(), mixed.area());
+
+    let collection = wkt(&format!("GEOMETRYCOLLECTION(POINT(1 2),{})", ESRI));
+    let oriented = collection.oriented(Orientation::Rfc7946);
+    assert!(oriented.is_oriented(Orientation::Rfc7946));
+    assert_eq!(oriented.geometries().count(), 2);
+}
+
+#[test]
+fn z_follows_its_point() {
+    // The exterior is reversed and the hole kept, so only the exterior's Z
+    // values change order.
+    let geom =
+        wkt("POLYGON Z((0 0 1,0 10 2,10 10 3,10 0 4,0 0 1),(2 2 5,2 4 6,4 4 7,4 2 8,2 2 5))");
+    let oriented = geom.oriented(Orientation::Rfc7946);
+    assert!(oriented.is_oriented(Orientation::Rfc7946));
+    assert!(oriented.dims().has_z());
+    assert_eq!(
+        oriented.extra_coords(),
+        [1.0, 4.0, 3.0, 2.0, 1.0, 5.0, 6.0, 7.0, 8.0, 5.0]
+    );
+    let exterior = oriented.poly().unwrap().exterior().points();
+    assert_eq!((exterior[1].x, exterior[1].y), (10.0, 0.0));
+}
+
+#[test]
+fn feature_keeps_its_members() {
+    let feature = Geom::parse_geojson(&format!(
+        r#"{{"type":"Feature","id":7,"geometry":{},"properties":{{"name":"a"}}}}"#,
+        wkt(ESRI).to_geojson()
+    ))
+    .unwrap();
+    assert!(!feature.is_oriented(Orientation::Rfc7946));
+
+    let json = feature.to_geojson_rfc7946();
+    assert!(
+        json.starts_with(r#"{"type":"Feature","geometry":"#),
+        "{}",
+        json
+    );
+    let back = Geom::parse_geojson(&json).unwrap();
+    assert!(back.is_feature());
+    assert!(back.is_oriented(Orientation::Rfc7946));
+    let extra = back.extra_json().unwrap();
+    assert!(extra.contains(r#""id":7"#), "{}", extra);
+    assert!(extra.contains(r#""properties":{"name":"a"}"#), "{}", extra);
+
+    // Already oriented, it's written as it is.
+    assert_eq!(back.to_geojson_rfc7946(), back.to_geojson());
+}
+
+#[test]
+fn feature_collection_keeps_its_features() {
+    let feature = |geometry: &str, name: &str| {
+        format!(
+            r#"{{"type":"Feature","geometry":{},"properties":{{"name":"{}"}}}}"#,
+            wkt(geometry).to_geojson(),
+            name
+        )
+    };
+    let collection = Geom::parse_geojson(&format!(
+        r#"{{"type":"FeatureCollection","features":[{},{},{}]}}"#,
+        feature(ESRI, "esri"),
+        feature(RFC7946, "rfc"),
+        feature("POINT(1 2)", "point"),
+    ))
+    .unwrap();
+
+    let json = collection.to_geojson_rfc7946();
+    assert!(
+        json.starts_with(r#"{"type":"FeatureCollection","features":["#),
+        "{}",
+        json
+    );
+    let back = Geom::parse_geojson(&json).unwrap();
+    assert!(back.is_oriented(Orientation::Rfc7946));
+    let extras: Vec<&str> = back
+        .geometries()
+        .map(|member| {
+            assert!(member.is_feature());
+            member.extra_json().unwrap()
+        })
+        .collect();
+    assert_eq!(extras.len(), 3);
+    for (extra, name) in extras.iter().zip(["esri", "rfc", "point"]) {
+        assert!(
+            extra.contains(&format!(r#""name":"{}""#, name)),
+            "{}",
+            extra
+        );
+    }
+}
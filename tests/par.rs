@@ -0,0 +1,80 @@
+//! Checks the parallel helpers against the sequential calls they fan out,
+//! which needs the `rayon` and `atomics` features.
+
+#![cfg(all(feature = "rayon", feature = "atomics"))]
+
+use tg_sys::{par_contains_points, par_intersects, par_parse_wkt, tg_index, Geom};
+
+mod common;
+
+use common::{points, polygon, Rng, SEED};
+
+/// Points, small squares and lines scattered around the wobbly polygon,
+/// with every tenth input malformed.
+fn wkts(n: usize) -> Vec<String> {
+    let mut rng = Rng::new(SEED);
+    (0..n)
+        .map(|i| {
+            let p = rng.point(-150.0, 150.0);
+            let size = rng.range(1.0, 20.0);
+            match i % 10 {
+                0 => format!("POLYGON(({} {},{} {}", p.x, p.y, p.x + size, p.y),
+                1..=3 => format!("POINT({} {})", p.x, p.y),
+                4..=6 => format!(
+                    "POLYGON(({x} {y},{x2} {y},{x2} {y2},{x} {y2},{x} {y}))",
+                    x = p.x,
+                    y = p.y,
+                    x2 = p.x + size,
+                    y2 = p.y + size
+                ),
+                _ => format!("LINESTRING({} {},{} {})", p.x, p.y, p.x + size, p.y - size),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn parse_wkt_matches_sequential() {
+    let wkts = wkts(5000);
+    let wkts: Vec<&str> = wkts.iter().map(String::as_str).collect();
+    let results = par_parse_wkt(&wkts);
+    assert_eq!(results.len(), wkts.len());
+    for (wkt, result) in wkts.iter().zip(&results) {
+        match (result, Geom::parse_wkt(wkt)) {
+            (Ok(geom), Ok(expected)) => assert_eq!(geom.to_wkt(), expected.to_wkt()),
+            (Err(err), Err(expected)) => assert_eq!(*err, expected),
+            (result, expected) => panic!("{}: {:?} != {:?}", wkt, result, expected),
+        }
+    }
+    assert!(results.iter().any(|result| result.is_err()));
+    assert!(par_parse_wkt(&[]).is_empty());
+}
+
+#[test]
+fn intersects_matches_sequential() {
+    let fence = polygon(1000, tg_index::TG_NATURAL);
+    let others: Vec<Geom> = wkts(5000)
+        .iter()
+        .filter_map(|wkt| Geom::parse_wkt(wkt).ok())
+        .collect();
+    let expected: Vec<bool> = others.iter().map(|other| fence.intersects(other)).collect();
+    assert!(expected.iter().any(|&hit| hit));
+    assert!(expected.iter().any(|&hit| !hit));
+    assert_eq!(par_intersects(&fence, &others), expected);
+    assert!(par_intersects(&fence, &[]).is_empty());
+}
+
+#[test]
+fn contains_points_matches_sequential() {
+    let fence = polygon(1000, tg_index::TG_NATURAL);
+    // Enough points for several tasks, with a partial one at the end.
+    for n in [0, 1, 4096, 10_000] {
+        let points = points(n);
+        let expected: Vec<bool> = points
+            .iter()
+            .map(|p| fence.intersects_xy(p.x, p.y))
+            .collect();
+        assert_eq!(fence.contains_points(&points), expected);
+        assert_eq!(par_contains_points(&fence, &points), expected, "{}", n);
+    }
+}
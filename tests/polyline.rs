@@ -0,0 +1,87 @@
+//! Encodes and decodes Google's Encoded Polyline Algorithm Format.
+
+use tg_sys::{tg_point, Geom, Line};
+
+/// The example from Google's documentation of the format.
+const GOOGLE: &str = "_p~iF~ps|U_ulLnnqC_mqNvxq`@";
+
+/// The example's points as (latitude, longitude).
+const GOOGLE_POINTS: [(f64, f64); 3] = [(38.5, -120.2), (40.7, -120.95), (43.252, -126.453)];
+
+fn assert_close(a: f64, b: f64) {
+    assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+}
+
+fn google_line() -> Line {
+    let points: Vec<tg_point> = GOOGLE_POINTS
+        .iter()
+        .map(|&(lat, lng)| tg_point { x: lng, y: lat })
+        .collect();
+    Line::new(&points)
+}
+
+#[test]
+fn google_reference_decode() {
+    let line = Line::from_polyline(GOOGLE, 5).unwrap();
+    assert_eq!(line.points().len(), GOOGLE_POINTS.len());
+    for (p, &(lat, lng)) in line.points().iter().zip(&GOOGLE_POINTS) {
+        assert_close(p.x, lng);
+        assert_close(p.y, lat);
+    }
+}
+
+#[test]
+fn google_reference_encode() {
+    assert_eq!(google_line().to_polyline(5), GOOGLE);
+}
+
+#[test]
+fn precision() {
+    let line = google_line();
+    let encoded = line.to_polyline(6);
+    assert_ne!(encoded, GOOGLE);
+    let decoded = Line::from_polyline(&encoded, 6).unwrap();
+    assert_eq!(decoded.points(), line.points());
+
+    // Reading at the wrong precision scales every coordinate.
+    let decoded = Line::from_polyline(&encoded, 5).unwrap();
+    assert_close(decoded.points()[0].y, 385.0);
+
+    let decoded = Line::from_polyline(&line.to_polyline(1), 1).unwrap();
+    assert_close(decoded.points()[2].x, -126.5);
+    assert_close(decoded.points()[2].y, 43.3);
+}
+
+#[test]
+fn z_round_trip() {
+    let geom = Geom::parse_wkt("LINESTRING Z(-120.2 38.5 10,-120.95 40.7 12.5,-126.453 43.252 -3)")
+        .unwrap();
+    let encoded = geom.to_polyline_z(5, 1).unwrap();
+    let back = Geom::from_polyline_z(&encoded, 5, 1).unwrap();
+    assert!(back.dims().has_z());
+    assert_eq!(back.to_wkt(), geom.to_wkt());
+    assert_eq!(back.to_polyline_z(5, 1).unwrap(), encoded);
+
+    // Without a Z coordinate, the third value is zero.
+    let encoded = google_line().as_geom().to_polyline_z(5, 2).unwrap();
+    let back = Geom::from_polyline_z(&encoded, 5, 2).unwrap();
+    assert_eq!(back.extra_coords(), [0.0, 0.0, 0.0]);
+    assert_eq!(back.line().unwrap().points(), google_line().points());
+
+    // Two points of two values each don't divide into triples.
+    assert!(Geom::from_polyline_z("_p~iF~ps|U_ulLnnqC", 5, 0).is_err());
+
+    let point = Geom::parse_wkt("POINT(1 2)").unwrap();
+    assert!(point.to_polyline_z(5, 0).is_err());
+}
+
+#[test]
+fn empty_and_invalid_input() {
+    assert!(Line::from_polyline("", 5).unwrap().points().is_empty());
+    assert_eq!(Line::new(&[]).to_polyline(5), "");
+    for encoded in ["_p~iF", "_", "_p~iF~ps|U!", "_p~iF ~ps|U", "é"] {
+        assert!(Line::from_polyline(encoded, 5).is_err(), "{}", encoded);
+    }
+    // A value longer than 64 bits.
+    assert!(Line::from_polyline(&"_".repeat(20), 5).is_err());
+}
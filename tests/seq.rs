@@ -0,0 +1,153 @@
+//! Reads and writes GeoJSON text sequences and WKT lines with
+//! `RecordReader`.
+
+use tg_sys::{
+    read_geojsonseq, read_wkt_lines, write_geojsonseq, write_geojsonseq_rfc7946, write_wkt_lines,
+    Geom, Orientation, ReadError,
+};
+
+fn wkts(results: Vec<Result<Geom, ReadError>>) -> Vec<String> {
+    results.into_iter().map(|r| r.unwrap().to_wkt()).collect()
+}
+
+fn record_line(result: &Result<Geom, ReadError>) -> usize {
+    match result {
+        Err(ReadError::Record { line, .. }) => *line,
+        other => panic!("expected a record error, got {:?}", other),
+    }
+}
+
+#[test]
+fn separated_records_span_lines() {
+    let input = "\x1e{\"type\":\"Point\",\n  \"coordinates\":[1,2]}\n\
+                 \x1e{\"type\":\"LineString\",\n\"coordinates\":[\n[0,0],\n[1,1]\n]}\n\
+                 \x1e\x1e {\"type\":\"Point\",\"coordinates\":[3,4]}\n";
+    let geoms: Vec<_> = read_geojsonseq(input.as_bytes()).collect();
+    assert_eq!(
+        wkts(geoms),
+        ["POINT(1 2)", "LINESTRING(0 0,1 1)", "POINT(3 4)"]
+    );
+}
+
+#[test]
+fn newline_delimited_without_separators() {
+    let input = "{\"type\":\"Point\",\"coordinates\":[1,2]}\n\
+                 {\"type\":\"Point\",\"coordinates\":[3,4]}";
+    let geoms: Vec<_> = read_geojsonseq(input.as_bytes()).collect();
+    assert_eq!(wkts(geoms), ["POINT(1 2)", "POINT(3 4)"]);
+}
+
+#[test]
+fn blank_lines_and_records_are_skipped() {
+    let input = "\n  \n\x1e{\"type\":\"Point\",\"coordinates\":[1,2]}\n\n\
+                 \x1e\n\x1e  \n\r\n\x1e{\"type\":\"Point\",\"coordinates\":[3,4]}\n\n";
+    let geoms: Vec<_> = read_geojsonseq(input.as_bytes()).collect();
+    assert_eq!(wkts(geoms), ["POINT(1 2)", "POINT(3 4)"]);
+
+    let input = "\nPOINT(1 2)\n \t\r\n\nPOINT(3 4)\n\n";
+    let geoms: Vec<_> = read_wkt_lines(input.as_bytes()).collect();
+    assert_eq!(wkts(geoms), ["POINT(1 2)", "POINT(3 4)"]);
+
+    assert_eq!(read_wkt_lines("\n\n".as_bytes()).count(), 0);
+    assert_eq!(read_geojsonseq("".as_bytes()).count(), 0);
+}
+
+#[test]
+fn errors_report_the_first_line_of_the_record() {
+    let input = "POINT(1 2)\n\nPOINT(1\nLINESTRING(0 0,1 1)\nNOPE\n";
+    let geoms: Vec<_> = read_wkt_lines(input.as_bytes()).collect();
+    assert_eq!(geoms.len(), 4);
+    assert_eq!(geoms[0].as_ref().unwrap().to_wkt(), "POINT(1 2)");
+    assert_eq!(record_line(&geoms[1]), 3);
+    assert_eq!(geoms[2].as_ref().unwrap().to_wkt(), "LINESTRING(0 0,1 1)");
+    assert_eq!(record_line(&geoms[3]), 5);
+
+    let input = "\n\x1e{\"type\":\"Point\",\"coordinates\":[1,2]}\n\
+                 \x1e{\"type\":\"Point\",\n\"coordinates\":[1]}\n\
+                 \x1e{\"type\":\"Point\",\"coordinates\":[3,4]}\n\
+                 \x1e{\"type\":\n";
+    let geoms: Vec<_> = read_geojsonseq(input.as_bytes()).collect();
+    assert_eq!(geoms.len(), 4);
+    assert!(geoms[0].is_ok());
+    assert_eq!(record_line(&geoms[1]), 3);
+    assert!(geoms[2].is_ok());
+    assert_eq!(record_line(&geoms[3]), 6);
+    assert!(geoms[3]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .starts_with("line 6: "));
+}
+
+#[test]
+fn wkt_lines_round_trip() {
+    let geoms: Vec<Geom> = [
+        "POINT(1 2)",
+        "POINT Z(1 2 3)",
+        "LINESTRING(0 0,1 1,2 0)",
+        "POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,4 2,4 4,2 2))",
+        "MULTIPOINT(0 0,1 1)",
+        "GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(0 0,1 1))",
+        "POLYGON EMPTY",
+    ]
+    .iter()
+    .map(|wkt| Geom::parse_wkt(wkt).unwrap())
+    .collect();
+
+    let mut out = Vec::new();
+    write_wkt_lines(&mut out, &geoms).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(text.lines().count(), geoms.len());
+    assert!(text.ends_with('\n'));
+
+    let read: Vec<Geom> = read_wkt_lines(text.as_bytes())
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(read.len(), geoms.len());
+    for (a, b) in geoms.iter().zip(&read) {
+        assert_eq!(a.to_wkt(), b.to_wkt());
+    }
+}
+
+#[test]
+fn geojsonseq_round_trip() {
+    let geoms: Vec<Geom> = [
+        "POINT(1 2)",
+        "LINESTRING(0 0,1 1,2 0)",
+        "POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,4 2,4 4,2 2))",
+        "GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(0 0,1 1))",
+    ]
+    .iter()
+    .map(|wkt| Geom::parse_wkt(wkt).unwrap())
+    .collect();
+
+    let mut out = Vec::new();
+    write_geojsonseq(&mut out, &geoms).unwrap();
+    assert_eq!(out.iter().filter(|&&b| b == 0x1e).count(), geoms.len());
+    assert_eq!(out[0], 0x1e);
+
+    let read: Vec<Geom> = read_geojsonseq(&out[..]).map(Result::unwrap).collect();
+    assert_eq!(read.len(), geoms.len());
+    for (a, b) in geoms.iter().zip(&read) {
+        assert_eq!(a.to_wkt(), b.to_wkt());
+    }
+}
+
+#[test]
+fn geojsonseq_rfc7946_rewinds_polygons() {
+    // Wound clockwise.
+    let geoms = [Geom::parse_wkt("POLYGON((0 0,0 10,10 10,10 0,0 0))").unwrap()];
+
+    let mut out = Vec::new();
+    write_geojsonseq(&mut out, &geoms).unwrap();
+    let read: Vec<Geom> = read_geojsonseq(&out[..]).map(Result::unwrap).collect();
+    assert!(read[0].is_oriented(Orientation::Esri));
+
+    let mut out = Vec::new();
+    write_geojsonseq_rfc7946(&mut out, &geoms).unwrap();
+    assert_eq!(out[0], 0x1e);
+    let read: Vec<Geom> = read_geojsonseq(&out[..]).map(Result::unwrap).collect();
+    assert_eq!(read.len(), 1);
+    assert!(read[0].is_oriented(Orientation::Rfc7946));
+    assert_eq!(read[0].area(), 100.0);
+}
@@ -0,0 +1,237 @@
+//! Reads the shapefiles in `tests/fixtures`:
+//!
+//! - `points`: three points with `NAME`, `POP` and `AREA` columns. The second
+//!   row is marked as deleted, the third is blank, and the `.cpg` file names
+//!   ISO-8859-1.
+//! - `lines`: a one-part PolyLine, a two-part PolyLine and a null shape, with
+//!   UTF-8 text and no `.cpg` file.
+//! - `polygons`: a polygon with a hole and a record of two polygons, with no
+//!   `.dbf` file.
+//! - `multipatch`: a MultiPatch followed by a null shape.
+//! - `windows`: one point whose name uses the 0x80-0x9F range of
+//!   Windows-1252, with a `.cpg` file naming code page 1252.
+
+use std::fs;
+use std::io::{Cursor, Read, Seek};
+
+use tg_sys::{tg_geom_type, tg_point, tg_rect, ReadError, ShpReader, ShpRecord, Value};
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn records(name: &str) -> Vec<Result<ShpRecord, ReadError>> {
+    ShpReader::open(fixture(name)).unwrap().records().collect()
+}
+
+fn rect(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> tg_rect {
+    tg_rect {
+        min: tg_point { x: min_x, y: min_y },
+        max: tg_point { x: max_x, y: max_y },
+    }
+}
+
+#[test]
+fn points() {
+    let reader = ShpReader::open(fixture("points.shp")).unwrap();
+    assert_eq!(reader.header().shape_type(), 1);
+    assert_eq!(
+        reader.header().geometry_type(),
+        Some(tg_geom_type::TG_POINT)
+    );
+    assert_eq!(reader.header().rect(), rect(-5.5, 2.0, 3.0, 6.25));
+    assert_eq!(reader.fields().collect::<Vec<_>>(), ["NAME", "POP", "AREA"]);
+
+    let records: Vec<ShpRecord> = records("points.shp")
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    let wkts: Vec<String> = records.iter().map(|r| r.geom().unwrap().to_wkt()).collect();
+    assert_eq!(wkts, ["POINT(1 2)", "POINT(3 4)", "POINT(-5.5 6.25)"]);
+
+    let first = &records[0];
+    assert_eq!(first.index(), 0);
+    assert!(!first.is_deleted());
+    assert_eq!(
+        first.attribute("name"),
+        Some(&Value::String("Zürich".to_string()))
+    );
+    assert_eq!(first.attribute("POP"), Some(&Value::Int(421878)));
+    assert_eq!(first.attribute("Area"), Some(&Value::Float(87.88)));
+    assert_eq!(first.attribute("missing"), None);
+
+    assert!(records[1].is_deleted());
+    assert_eq!(
+        records[1].attribute("NAME"),
+        Some(&Value::String("Gone".to_string()))
+    );
+
+    let blank = &records[2];
+    assert!(!blank.is_deleted());
+    assert!(blank.attributes().iter().all(|(_, v)| *v == Value::Null));
+}
+
+#[test]
+fn cpg_names_the_encoding() {
+    // Without the .cpg file, the Latin-1 text isn't valid UTF-8.
+    let read = |ext: &str| Cursor::new(fs::read(fixture(&format!("points.{}", ext))).unwrap());
+    let mut reader = ShpReader::new(read("shp"), None, Some(read("dbf"))).unwrap();
+    let record = reader.records().next().unwrap().unwrap();
+    assert_eq!(
+        record.attribute("NAME"),
+        Some(&Value::String("Z\u{fffd}rich".to_string()))
+    );
+
+    // And UTF-8 text without a .cpg file is read as UTF-8.
+    let records = records("lines.shp");
+    assert_eq!(
+        records[0].as_ref().unwrap().attribute("NAME"),
+        Some(&Value::String("Zürich".to_string()))
+    );
+}
+
+#[test]
+fn windows_1252() {
+    fn name<R: Read + Seek>(reader: &mut ShpReader<R>) -> Option<Value> {
+        let record = reader.records().next().unwrap().unwrap();
+        record.attribute("NAME").cloned()
+    }
+    let expected = Some(Value::String("“Café” €5".to_string()));
+    let mut reader = ShpReader::open(fixture("windows.shp")).unwrap();
+    assert_eq!(name(&mut reader), expected);
+
+    // Without the .cpg file, the language driver byte names the code page.
+    let read = |ext: &str| Cursor::new(fs::read(fixture(&format!("windows.{}", ext))).unwrap());
+    let mut reader = ShpReader::new(read("shp"), None, Some(read("dbf"))).unwrap();
+    assert_eq!(
+        name(&mut reader),
+        Some(Value::String(
+            "\u{fffd}Caf\u{fffd} \u{fffd}5".to_string()
+        ))
+    );
+    for (ldid, expected) in [(0x03, "“Café” €5"), (0x57, "\u{93}Café\u{94} \u{80}5")] {
+        let mut dbf = fs::read(fixture("windows.dbf")).unwrap();
+        dbf[29] = ldid;
+        let mut reader = ShpReader::new(read("shp"), None, Some(Cursor::new(dbf))).unwrap();
+        assert_eq!(
+            name(&mut reader),
+            Some(Value::String(expected.to_string())),
+            "{:#x}",
+            ldid
+        );
+    }
+}
+
+#[test]
+fn polylines() {
+    let reader = ShpReader::open(fixture("lines.shp")).unwrap();
+    assert_eq!(
+        reader.header().geometry_type(),
+        Some(tg_geom_type::TG_MULTILINESTRING)
+    );
+
+    let records: Vec<ShpRecord> = records("lines.shp")
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records.len(), 3);
+    assert_eq!(
+        records[0].geom().unwrap().to_wkt(),
+        "LINESTRING(0 0,1 1,2 0)"
+    );
+    assert_eq!(
+        records[1].geom().unwrap().to_wkt(),
+        "MULTILINESTRING((10 10,11 11),(20 20,21 20,22 22))"
+    );
+    assert!(records[2].geom().is_none());
+    assert_eq!(
+        records[2].attribute("NAME"),
+        Some(&Value::String("null".to_string()))
+    );
+}
+
+#[test]
+fn polygon_with_a_hole() {
+    let reader = ShpReader::open(fixture("polygons.shp")).unwrap();
+    assert_eq!(reader.header().rect(), rect(0.0, 0.0, 35.0, 10.0));
+    assert_eq!(reader.fields().len(), 0);
+
+    let records: Vec<ShpRecord> = records("polygons.shp")
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records.len(), 2);
+
+    let geom = records[0].geom().unwrap();
+    assert_eq!(geom.geom_type(), tg_geom_type::TG_POLYGON);
+    let poly = geom.poly().unwrap();
+    assert_eq!(poly.holes().len(), 1);
+    assert_eq!(geom.area(), 96.0);
+    assert!(geom.intersects_xy(1.0, 1.0));
+    assert!(!geom.intersects_xy(3.0, 3.0));
+    assert!(records[0].attributes().is_empty());
+    assert!(!records[0].is_deleted());
+
+    let geom = records[1].geom().unwrap();
+    assert_eq!(geom.geom_type(), tg_geom_type::TG_MULTIPOLYGON);
+    assert_eq!(geom.polys().len(), 2);
+    assert_eq!(geom.area(), 50.0);
+}
+
+#[test]
+fn records_by_position() {
+    let mut reader = ShpReader::open(fixture("lines.shp")).unwrap();
+    let record = reader.record(1).unwrap().unwrap();
+    assert_eq!(record.index(), 1);
+    assert_eq!(
+        record.attribute("NAME"),
+        Some(&Value::String("two parts".to_string()))
+    );
+    assert_eq!(reader.record(0).unwrap().unwrap().index(), 0);
+    assert!(reader.record(3).unwrap().is_none());
+
+    // Without the .shx file there's no way to find a record.
+    let shp = Cursor::new(fs::read(fixture("lines.shp")).unwrap());
+    let mut reader = ShpReader::new(shp, None, None).unwrap();
+    assert!(reader.record(0).is_err());
+    assert_eq!(reader.records().count(), 3);
+}
+
+#[test]
+fn multipatch_is_rejected() {
+    let reader = ShpReader::open(fixture("multipatch.shp")).unwrap();
+    assert_eq!(reader.header().shape_type(), 31);
+    assert_eq!(reader.header().geometry_type(), None);
+
+    // The MultiPatch is reported, and reading carries on past it.
+    let records = records("multipatch.shp");
+    assert_eq!(records.len(), 2);
+    match &records[0] {
+        Err(ReadError::Feature { index: 0, error }) => {
+            assert!(error.to_string().contains("MultiPatch"), "{}", error)
+        }
+        other => panic!("expected a feature error, got {:?}", other),
+    }
+    let record = records[1].as_ref().unwrap();
+    assert_eq!(record.index(), 1);
+    assert!(record.geom().is_none());
+}
+
+#[test]
+fn malformed_files() {
+    let header = |bytes: Vec<u8>| ShpReader::new(Cursor::new(bytes), None, None);
+    match header(vec![0; 100]) {
+        Err(ReadError::Syntax { offset: 0, .. }) => {}
+        other => panic!("expected a syntax error, got {:?}", other.err()),
+    }
+    assert!(matches!(header(vec![0; 20]), Err(ReadError::Io(_))));
+
+    // A record that claims more bytes than the file holds.
+    let mut shp = fs::read(fixture("points.shp")).unwrap();
+    shp.truncate(shp.len() - 4);
+    let mut reader = header(shp).unwrap();
+    let records: Vec<_> = reader.records().collect();
+    assert_eq!(records.len(), 3);
+    assert!(records[..2].iter().all(Result::is_ok));
+    assert!(matches!(records[2], Err(ReadError::Io(_))));
+}
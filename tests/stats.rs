@@ -0,0 +1,151 @@
+//! Summarizes line and ring indexes with `IndexStats`.
+
+use tg_sys::{recommend_index, tg_index, tg_point, tg_rect, Geom, Line, Ring};
+
+/// A zigzag that runs right along the x axis and back again, slightly
+/// higher, so that the rectangles of the way back cover those of the way
+/// out.
+fn doubled_back(n: usize) -> Vec<tg_point> {
+    let out = (0..=n).map(|i| tg_point {
+        x: i as f64,
+        y: (i % 2) as f64,
+    });
+    let back = (0..=n).rev().map(|i| tg_point {
+        x: i as f64,
+        y: (i % 2) as f64 + 0.5,
+    });
+    out.chain(back).collect()
+}
+
+/// The overlap ratio of a set of rectangles, comparing every pair.
+fn overlap_ratio(rects: &[tg_rect]) -> f64 {
+    let total: f64 = rects
+        .iter()
+        .map(|r| (r.max.x - r.min.x) * (r.max.y - r.min.y))
+        .sum();
+    let mut shared = 0.0;
+    for (i, a) in rects.iter().enumerate() {
+        for b in &rects[i + 1..] {
+            let w = a.max.x.min(b.max.x) - a.min.x.max(b.min.x);
+            let h = a.max.y.min(b.max.y) - a.min.y.max(b.min.y);
+            if w > 0.0 && h > 0.0 {
+                shared += w * h;
+            }
+        }
+    }
+    if total > 0.0 {
+        shared / total
+    } else {
+        0.0
+    }
+}
+
+#[test]
+fn unindexed() {
+    let points = doubled_back(500);
+    let stats = Line::new_ix(&points, tg_index::TG_NONE).index_stats();
+    assert_eq!(stats.points, points.len());
+    assert!(stats.levels.is_empty());
+    assert_eq!(stats.memsize, stats.unindexed_memsize);
+    assert_eq!(stats.index_memsize(), 0);
+
+    let stats = Ring::new_ix(&points, tg_index::TG_NONE).index_stats();
+    assert!(stats.levels.is_empty());
+    assert_eq!(stats.index_memsize(), 0);
+}
+
+#[test]
+fn natural_index_levels() {
+    let points = doubled_back(500);
+    let line = Line::new_ix(&points, tg_index::TG_NATURAL);
+    let stats = line.index_stats();
+    assert!(stats.spread > 0);
+    assert!(stats.index_memsize() > 0);
+    assert_eq!(stats.levels.len(), line.index_num_levels());
+    // Levels go from a few rectangles at the root to many at the bottom.
+    assert!(stats.levels.windows(2).all(|w| w[0].rects <= w[1].rects));
+    let bottom = stats.levels.last().unwrap();
+    assert_eq!(bottom.rects, (points.len() - 1).div_ceil(stats.spread));
+
+    for (level, summary) in stats.levels.iter().enumerate() {
+        let rects = line.index_level_rects(level);
+        assert_eq!(summary.rects, rects.len());
+        let expected = overlap_ratio(&rects);
+        assert!(
+            (summary.overlap_ratio - expected).abs() <= 1e-12 * expected.max(1.0),
+            "level {}: {} != {}",
+            level,
+            summary.overlap_ratio,
+            expected
+        );
+    }
+    // The way back covers the way out.
+    assert!(bottom.overlap_ratio > 0.1);
+}
+
+#[test]
+fn overlap_ratio_of_a_monotonic_line() {
+    // Each rectangle only touches the next at a corner, so nothing overlaps.
+    let points: Vec<tg_point> = (0..1000)
+        .map(|i| tg_point {
+            x: i as f64,
+            y: i as f64,
+        })
+        .collect();
+    let stats = Line::new_ix(&points, tg_index::TG_NATURAL).index_stats();
+    assert!(!stats.levels.is_empty());
+    for level in &stats.levels {
+        assert_eq!(level.overlap_ratio, 0.0);
+    }
+}
+
+#[test]
+fn ystripes_report_memory_only() {
+    let points: Vec<tg_point> = (0..=1000)
+        .map(|i| {
+            let theta = f64::from(i % 1000) / 1000.0 * std::f64::consts::TAU;
+            tg_point {
+                x: theta.cos() * 100.0,
+                y: theta.sin() * 100.0,
+            }
+        })
+        .collect();
+    let stats = Ring::new_ix(&points, tg_index::TG_YSTRIPES).index_stats();
+    assert!(stats.levels.is_empty());
+    assert!(stats.index_memsize() > 0);
+
+    let hole: Vec<tg_point> = points
+        .iter()
+        .map(|p| tg_point {
+            x: p.x / 2.0,
+            y: p.y / 2.0,
+        })
+        .collect();
+    let wkt = |points: &[tg_point]| {
+        let coords: Vec<String> = points.iter().map(|p| format!("{} {}", p.x, p.y)).collect();
+        format!("({})", coords.join(","))
+    };
+    let geom = Geom::parse_wkt(&format!("POLYGON({},{})", wkt(&points), wkt(&hole))).unwrap();
+    let stats = geom.poly().unwrap().index_stats();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0].points, points.len());
+    assert_eq!(stats[1].points, hole.len());
+}
+
+#[test]
+fn recommend_index_picks_an_option() {
+    let options = [
+        tg_index::TG_NONE,
+        tg_index::TG_NATURAL,
+        tg_index::TG_YSTRIPES,
+    ];
+    for wkt in [
+        "POINT(1 2)",
+        "POLYGON((0 0,10 0,10 10,0 10,0 0))",
+        "LINESTRING(0 0,10 10,20 0)",
+        "POLYGON EMPTY",
+    ] {
+        let geom = Geom::parse_wkt(wkt).unwrap();
+        assert!(options.contains(&recommend_index(&geom)), "{}", wkt);
+    }
+}
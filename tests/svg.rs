@@ -0,0 +1,118 @@
+//! Renders geometries with `to_svg`.
+
+use tg_sys::{tg_index, to_svg, Geom, SvgStyle};
+
+/// A 120 pixel wide image with 10 pixels of padding, so a geometry 10 units
+/// wide is drawn at 10 pixels per unit.
+fn style() -> SvgStyle {
+    SvgStyle {
+        width: 120.0,
+        padding: 10.0,
+        ..SvgStyle::default()
+    }
+}
+
+fn render(wkts: &[&str], style: &SvgStyle) -> String {
+    let geoms: Vec<Geom> = wkts
+        .iter()
+        .map(|wkt| Geom::parse_wkt(wkt).unwrap())
+        .collect();
+    to_svg(&geoms, style)
+}
+
+#[test]
+fn y_axis_is_flipped() {
+    let svg = render(&["LINESTRING(0 0,10 5)"], &style());
+    assert!(svg.starts_with(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="120" height="70" viewBox="0 0 120 70">"#
+    ));
+    // The lowest point is at the bottom of the image.
+    assert!(
+        svg.contains(r#"<path fill="none" d="M10 60L110 10"/>"#),
+        "{}",
+        svg
+    );
+    assert!(svg.ends_with("</svg>\n"));
+}
+
+#[test]
+fn polygon_with_a_hole() {
+    let svg = render(
+        &["POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,4 2,4 4,2 2))"],
+        &style(),
+    );
+    assert!(svg.contains(r#"height="120""#));
+    assert!(svg.contains(r#"fill-rule="evenodd""#));
+    let start = svg.find(r#"<path d=""#).unwrap();
+    let path = &svg[start..svg[start..].find("/>").unwrap() + start];
+    // Both rings go in one path, so the even-odd rule leaves the hole empty.
+    assert_eq!(path.matches('M').count(), 2);
+    assert_eq!(path.matches('Z').count(), 2);
+    assert!(path.contains("M10 110L110 110L110 10L10 10"), "{}", path);
+    assert!(path.contains("ZM30 90L50 90L50 70"), "{}", path);
+}
+
+#[test]
+fn points() {
+    let svg = render(&["MULTIPOINT(0 0,10 10)"], &style());
+    assert!(
+        svg.contains(r#"<circle cx="10" cy="110" r="3"/>"#),
+        "{}",
+        svg
+    );
+    assert!(
+        svg.contains(r#"<circle cx="110" cy="10" r="3"/>"#),
+        "{}",
+        svg
+    );
+
+    // A lone point has no extent, but is still drawn inside the padding.
+    let style = SvgStyle {
+        point_radius: 2.5,
+        ..style()
+    };
+    let svg = render(&["POINT(5 5)"], &style);
+    assert!(svg.contains(r#"height="20""#), "{}", svg);
+    assert!(
+        svg.contains(r#"<circle cx="10" cy="10" r="2.5"/>"#),
+        "{}",
+        svg
+    );
+}
+
+#[test]
+fn empty_input() {
+    let svg = render(&[], &style());
+    assert!(svg.contains(r#"width="120" height="120""#));
+    assert!(!svg.contains("<path") && !svg.contains("<circle"));
+    let svg = render(&["POLYGON EMPTY", "POINT(0 0)"], &style());
+    assert_eq!(svg.matches("<circle").count(), 1);
+    assert!(!svg.contains("<path"));
+}
+
+#[test]
+fn index_levels_and_escaping() {
+    let points: Vec<String> = (0..=256)
+        .map(|i| {
+            let theta = f64::from(i % 256) / 256.0 * std::f64::consts::TAU;
+            format!("{} {}", theta.cos() * 10.0, theta.sin() * 10.0)
+        })
+        .collect();
+    let wkt = format!("POLYGON(({}))", points.join(","));
+    let geoms = [Geom::parse_wkt_ix(&wkt, tg_index::TG_NATURAL).unwrap()];
+    let levels = geoms[0].poly().unwrap().exterior().index_num_levels();
+    assert!(levels > 0);
+
+    let svg = to_svg(&geoms, &style());
+    assert!(!svg.contains("index-level"));
+
+    let style = SvgStyle {
+        index_levels: true,
+        stroke: r#"a"b<c&d"#.to_string(),
+        ..style()
+    };
+    let svg = to_svg(&geoms, &style);
+    assert_eq!(svg.matches("class=\"index-level-").count(), levels);
+    assert!(svg.contains("<rect "));
+    assert!(svg.contains(r#"stroke="a&quot;b&lt;c&amp;d""#), "{}", svg);
+}
@@ -0,0 +1,230 @@
+//! Encodes geometries as TWKB and decodes them back.
+
+use tg_sys::{tg_geom_type, Geom, TwkbOptions};
+
+mod common;
+
+use common::wkt;
+
+fn round_trip(geom: &Geom, options: &TwkbOptions) -> Geom {
+    Geom::parse_twkb(&geom.to_twkb(options).unwrap()).unwrap()
+}
+
+fn assert_close(a: f64, b: f64) {
+    assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+}
+
+fn precision(precision: i8) -> TwkbOptions {
+    TwkbOptions {
+        precision,
+        ..TwkbOptions::default()
+    }
+}
+
+#[test]
+fn lossless_round_trips() {
+    for input in [
+        "POINT(1 2)",
+        "POINT(-1000000 2000000)",
+        "LINESTRING(0 0,10 -10,20 0)",
+        "POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,4 2,4 4,2 2))",
+        "MULTIPOINT(0 0,1 1,-5 3)",
+        "MULTILINESTRING((0 0,1 1),(2 2,3 1,4 4))",
+        "MULTIPOLYGON(((0 0,1 0,1 1,0 0)),((5 5,9 5,9 9,5 9,5 5),(6 6,7 6,7 7,6 6)))",
+        "GEOMETRYCOLLECTION(POINT(1 2),GEOMETRYCOLLECTION(LINESTRING(0 0,1 1)))",
+        "LINESTRING Z(0 0 1,1 1 2)",
+        "POLYGON M((0 0 1,1 0 2,1 1 3,0 0 4))",
+        "MULTIPOINT ZM(0 0 1 2,3 4 5 6)",
+    ] {
+        let geom = wkt(input);
+        let back = round_trip(&geom, &TwkbOptions::default());
+        assert_eq!(back.to_wkt(), geom.to_wkt(), "{}", input);
+    }
+}
+
+#[test]
+fn coordinates_are_rounded_to_precision() {
+    let geom = wkt("LINESTRING(-122.41941 37.77492,-122.41839 37.77593,-122.40001 37.8)");
+    for p in [0, 1, 3, 5, 7] {
+        let back = round_trip(&geom, &precision(p));
+        let scale = 10f64.powi(p.into());
+        let expected = geom.line().unwrap().points();
+        let actual = back.line().unwrap().points();
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert_close(a.x, (e.x * scale).round() / scale);
+            assert_close(a.y, (e.y * scale).round() / scale);
+        }
+    }
+
+    // Negative precisions round to tens, hundreds and so on.
+    let back = round_trip(&wkt("POINT(1234 5678)"), &precision(-2));
+    assert_close(back.point().x, 1200.0);
+    assert_close(back.point().y, 5700.0);
+
+    // Fewer digits make for smaller output.
+    let coarse = geom.to_twkb(&precision(1)).unwrap();
+    let fine = geom.to_twkb(&precision(7)).unwrap();
+    assert!(coarse.len() < fine.len());
+}
+
+#[test]
+fn z_and_m_precision() {
+    let options = TwkbOptions {
+        precision: 2,
+        z_precision: 1,
+        m_precision: 3,
+        ..TwkbOptions::default()
+    };
+    let geom = wkt("POINT ZM(1.23456 2.34567 3.45678 4.56789)");
+    let twkb = geom.to_twkb(&options).unwrap();
+    // Type and precision, metadata with the extended flag, then the
+    // extended dimensions byte.
+    assert_eq!(twkb[0], 1 | 4 << 4);
+    assert_eq!(twkb[1], 0x08);
+    assert_eq!(twkb[2], 0x03 | 1 << 2 | 3 << 5);
+    let back = Geom::parse_twkb(&twkb).unwrap();
+    assert!(back.dims().has_z() && back.dims().has_m());
+    assert_close(back.point().x, 1.23);
+    assert_close(back.point().y, 2.35);
+    assert_close(back.z(), 3.5);
+    assert_close(back.m(), 4.568);
+
+    let back = round_trip(&wkt("POINT M(1 2 5.43216)"), &options);
+    assert!(!back.dims().has_z() && back.dims().has_m());
+    assert_close(back.m(), 5.432);
+
+    let back = round_trip(&wkt("LINESTRING Z(0 0 1.26,1 1 2.34,2 0 -0.05)"), &options);
+    assert!(back.dims().has_z() && !back.dims().has_m());
+    let expected = [1.3, 2.3, -0.1];
+    assert_eq!(back.extra_coords().len(), expected.len());
+    for (&a, e) in back.extra_coords().iter().zip(expected) {
+        assert_close(a, e);
+    }
+}
+
+#[test]
+fn bbox_and_size() {
+    let geom = wkt("LINESTRING(1 2,4 6)");
+    let encode = |bbox, size| {
+        geom.to_twkb(&TwkbOptions {
+            bbox,
+            size,
+            ..TwkbOptions::default()
+        })
+        .unwrap()
+    };
+    let body = [2, 2, 4, 6, 8];
+    let bbox = [2, 6, 4, 8];
+    assert_eq!(encode(false, false), [&[0x02, 0x00][..], &body].concat());
+    assert_eq!(
+        encode(true, false),
+        [&[0x02, 0x01][..], &bbox, &body].concat()
+    );
+    assert_eq!(encode(false, true), [&[0x02, 0x02, 5][..], &body].concat());
+    assert_eq!(
+        encode(true, true),
+        [&[0x02, 0x03, 9][..], &bbox, &body].concat()
+    );
+
+    let options = TwkbOptions {
+        precision: 3,
+        z_precision: 2,
+        bbox: true,
+        size: true,
+        ..TwkbOptions::default()
+    };
+    for input in [
+        "POINT Z(1.5 2.5 3.25)",
+        "POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,4 2,4 4,2 2))",
+        "GEOMETRYCOLLECTION(POINT(1 2),MULTIPOINT(3 4,5 6),POINT EMPTY)",
+    ] {
+        let geom = wkt(input);
+        assert_eq!(round_trip(&geom, &options).to_wkt(), geom.to_wkt());
+    }
+}
+
+#[test]
+fn id_lists_are_skipped() {
+    // MULTIPOINT with ids 10 and 11.
+    let twkb = [0x04, 0x04, 2, 20, 22, 2, 4, 6, 8];
+    assert_eq!(
+        Geom::parse_twkb(&twkb).unwrap().to_wkt(),
+        "MULTIPOINT(1 2,4 6)"
+    );
+
+    // MULTILINESTRING with ids -1 and 7.
+    let twkb = [0x05, 0x04, 2, 1, 14, 2, 0, 0, 2, 2, 2, 2, 2, 2, 2];
+    assert_eq!(
+        Geom::parse_twkb(&twkb).unwrap().to_wkt(),
+        "MULTILINESTRING((0 0,1 1),(2 2,3 3))"
+    );
+
+    // GEOMETRYCOLLECTION with a bbox, size and an id list.
+    let twkb = [0x07, 0x07, 10, 0, 2, 0, 2, 1, 6, 0x01, 0x00, 2, 2];
+    let geom = Geom::parse_twkb(&twkb).unwrap();
+    assert_eq!(geom.geom_type(), tg_geom_type::TG_GEOMETRYCOLLECTION);
+    assert_eq!(geom.to_wkt(), "GEOMETRYCOLLECTION(POINT(1 1))");
+}
+
+#[test]
+fn empty_geometries() {
+    for (input, geom_type) in [
+        ("POINT EMPTY", tg_geom_type::TG_POINT),
+        ("LINESTRING EMPTY", tg_geom_type::TG_LINESTRING),
+        ("POLYGON EMPTY", tg_geom_type::TG_POLYGON),
+        ("MULTIPOINT EMPTY", tg_geom_type::TG_MULTIPOINT),
+        ("MULTILINESTRING EMPTY", tg_geom_type::TG_MULTILINESTRING),
+        ("MULTIPOLYGON EMPTY", tg_geom_type::TG_MULTIPOLYGON),
+        (
+            "GEOMETRYCOLLECTION EMPTY",
+            tg_geom_type::TG_GEOMETRYCOLLECTION,
+        ),
+    ] {
+        let options = TwkbOptions {
+            bbox: true,
+            size: true,
+            ..TwkbOptions::default()
+        };
+        let twkb = wkt(input).to_twkb(&options).unwrap();
+        // No bounds and no size, just the empty flag.
+        assert_eq!(twkb, [geom_type as u8, 0x10], "{}", input);
+        let back = Geom::parse_twkb(&twkb).unwrap();
+        assert!(back.is_empty());
+        assert_eq!(back.geom_type(), geom_type);
+    }
+}
+
+#[test]
+fn invalid_input() {
+    let geom = wkt("POINT(1 2)");
+    assert!(geom.to_twkb(&precision(8)).is_err());
+    assert!(geom.to_twkb(&precision(-8)).is_err());
+    let options = TwkbOptions {
+        z_precision: 8,
+        ..TwkbOptions::default()
+    };
+    assert!(geom.to_twkb(&options).is_err());
+
+    for twkb in [
+        &[][..],
+        &[0x01][..],
+        &[0x01, 0x00, 2][..],
+        &[0x01, 0x00, 2, 4, 0][..],
+        &[0x08, 0x00][..],
+        &[0x02, 0x00, 0x7f][..],
+        &[
+            0x01, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01,
+        ][..],
+    ] {
+        assert!(Geom::parse_twkb(twkb).is_err(), "{:?}", twkb);
+    }
+}
+
+#[test]
+fn deeply_nested_collections_are_rejected() {
+    let mut twkb = [0x07, 0x00, 1].repeat(2000);
+    twkb.extend_from_slice(&[0x07, 0x10]);
+    let err = Geom::parse_twkb(&twkb).unwrap_err();
+    assert!(err.to_string().contains("nested too deeply"), "{}", err);
+}
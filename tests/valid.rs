@@ -0,0 +1,304 @@
+//! Checks geometries against the OGC rules with `validate`.
+
+use tg_sys::{tg_geom_type, tg_point, Geom, Line, Poly, Ring, ValidityError, ValidityErrorKind};
+
+fn pt(x: f64, y: f64) -> tg_point {
+    tg_point { x, y }
+}
+
+fn points(coords: &[(f64, f64)]) -> Vec<tg_point> {
+    coords.iter().map(|&(x, y)| pt(x, y)).collect()
+}
+
+/// Builds a polygon from its exterior and holes, without the checks a
+/// parser might make.
+fn polygon(rings: &[&[(f64, f64)]]) -> Geom {
+    let rings: Vec<Ring> = rings.iter().map(|r| Ring::new(&points(r))).collect();
+    let holes: Vec<_> = rings[1..].iter().map(|h| &**h).collect();
+    Geom::new_polygon(&Poly::new(&rings[0], &holes))
+}
+
+fn defects(geom: &Geom) -> Vec<ValidityError> {
+    let errors = geom.validate().unwrap_err();
+    assert!(!geom.is_valid());
+    errors
+}
+
+fn kinds(errors: &[ValidityError]) -> Vec<ValidityErrorKind> {
+    errors.iter().map(|e| e.kind).collect()
+}
+
+const SQUARE: &[(f64, f64)] = &[
+    (0.0, 0.0),
+    (10.0, 0.0),
+    (10.0, 10.0),
+    (0.0, 10.0),
+    (0.0, 0.0),
+];
+
+#[test]
+fn valid_geometries() {
+    for wkt in [
+        "POINT(1 2)",
+        "LINESTRING(0 0,1 1,2 0)",
+        "POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,2 4,4 4,4 2,2 2),(6 6,6 8,8 8,8 6,6 6))",
+        // Holes may touch the exterior, and each other, at a point.
+        "POLYGON((0 0,10 0,10 10,0 10,0 0),(0 5,5 2,5 8,0 5),(5 5,8 2,8 8,5 5))",
+        "MULTIPOLYGON(((0 0,1 0,1 1,0 0)),((0 0,-1 0,-1 -1,0 0)))",
+        "GEOMETRYCOLLECTION(POINT(0 0),LINESTRING(0 0,1 1))",
+        "POLYGON EMPTY",
+    ] {
+        let geom = Geom::parse_wkt(wkt).unwrap();
+        assert_eq!(geom.validate(), Ok(()), "{}", wkt);
+        assert!(geom.is_valid());
+    }
+}
+
+#[test]
+fn non_finite() {
+    let errors = defects(&Geom::new_point(pt(f64::NAN, 0.0)));
+    assert_eq!(kinds(&errors), [ValidityErrorKind::NonFinite]);
+    assert_eq!(errors[0].ring, None);
+
+    let line = Line::new(&[pt(0.0, 0.0), pt(1.0, f64::INFINITY), pt(2.0, 0.0)]);
+    let errors = defects(&Geom::new_linestring(&line));
+    assert_eq!(kinds(&errors), [ValidityErrorKind::NonFinite]);
+    assert_eq!(errors[0].point, pt(1.0, f64::INFINITY));
+}
+
+#[test]
+fn duplicate_point() {
+    let geom = Geom::parse_wkt("LINESTRING(0 0,1 1,1 1,2 0)").unwrap();
+    let errors = defects(&geom);
+    assert_eq!(kinds(&errors), [ValidityErrorKind::DuplicatePoint]);
+    assert_eq!(errors[0].point, pt(1.0, 1.0));
+    assert_eq!(errors[0].part, 0);
+    assert_eq!(errors[0].ring, None);
+}
+
+#[test]
+fn too_few_points() {
+    let line = Line::new(&[pt(1.0, 1.0)]);
+    let errors = defects(&Geom::new_linestring(&line));
+    assert_eq!(kinds(&errors), [ValidityErrorKind::TooFewPoints]);
+
+    // A ring that goes out and straight back has only two distinct points.
+    let errors = defects(&polygon(&[&[(0.0, 0.0), (1.0, 1.0), (0.0, 0.0)]]));
+    assert_eq!(kinds(&errors), [ValidityErrorKind::TooFewPoints]);
+    assert_eq!(errors[0].ring, Some(0));
+
+    // Three distinct points on one line enclose no area.
+    let errors = defects(&polygon(&[&[
+        (0.0, 0.0),
+        (1.0, 0.0),
+        (2.0, 0.0),
+        (0.0, 0.0),
+    ]]));
+    assert_eq!(kinds(&errors), [ValidityErrorKind::TooFewPoints]);
+    assert_eq!(errors[0].ring, Some(0));
+    assert_eq!(errors[0].point, pt(0.0, 0.0));
+
+    // A collapsed hole is reported against its own ring, and the rest of
+    // the polygon is still checked.
+    let errors = defects(&polygon(&[SQUARE, &[(2.0, 2.0), (3.0, 3.0), (2.0, 2.0)]]));
+    assert_eq!(kinds(&errors), [ValidityErrorKind::TooFewPoints]);
+    assert_eq!(errors[0].ring, Some(1));
+}
+
+#[test]
+fn unclosed() {
+    // The ring is closed as it's built, so there's nothing to report.
+    let geom = polygon(&[&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]]);
+    let ring = geom.poly().unwrap().exterior().points();
+    assert_eq!(ring.first(), ring.last());
+    assert_eq!(geom.validate(), Ok(()));
+    assert_eq!(ValidityErrorKind::Unclosed.to_string(), "unclosed ring");
+}
+
+#[test]
+fn bow_tie() {
+    let geom = Geom::parse_wkt("POLYGON((0 0,2 2,2 0,0 2,0 0))").unwrap();
+    let errors = defects(&geom);
+    // The crossing is found from both segments, but reported once.
+    assert_eq!(kinds(&errors), [ValidityErrorKind::SelfIntersection]);
+    assert_eq!(errors[0].point, pt(1.0, 1.0));
+    assert_eq!(errors[0].ring, Some(0));
+    assert_eq!(
+        errors[0].to_string(),
+        "self-intersection at (1 1) in part 0, ring 0"
+    );
+}
+
+#[test]
+fn spike() {
+    // The ring runs out to (6 4) and straight back, so the segments either
+    // side of the spike touch at its base.
+    let geom = Geom::parse_wkt("POLYGON((0 0,4 0,4 4,6 4,4 4,0 4,0 0))").unwrap();
+    let errors = defects(&geom);
+    for error in &errors {
+        assert_eq!(error.kind, ValidityErrorKind::SelfIntersection);
+        assert_eq!(error.point, pt(4.0, 4.0));
+    }
+    assert!(!errors.is_empty());
+}
+
+#[test]
+fn fold_back() {
+    // The second segment runs back along the first from (10 0) to (5 0),
+    // although the two are neighbours.
+    let geom = Geom::parse_wkt("POLYGON((0 0,10 0,5 0,5 5,0 0))").unwrap();
+    let errors = defects(&geom);
+    assert!(!errors.is_empty());
+    for error in &errors {
+        assert_eq!(error.kind, ValidityErrorKind::SelfIntersection);
+        assert_eq!(error.point, pt(5.0, 0.0));
+        assert_eq!(error.ring, Some(0));
+    }
+
+    // Neighbours that carry on along the same line only share a point.
+    let geom = Geom::parse_wkt("POLYGON((0 0,5 0,10 0,10 10,0 10,0 0))").unwrap();
+    assert_eq!(geom.validate(), Ok(()));
+}
+
+#[test]
+fn hole_outside() {
+    let hole: &[(f64, f64)] = &[(20.0, 2.0), (22.0, 2.0), (22.0, 4.0), (20.0, 2.0)];
+    let errors = defects(&polygon(&[SQUARE, hole]));
+    assert_eq!(kinds(&errors), [ValidityErrorKind::HoleOutside]);
+    assert_eq!(errors[0].ring, Some(1));
+    assert_eq!(errors[0].point, pt(20.0, 2.0));
+}
+
+#[test]
+fn hole_crosses_exterior() {
+    let hole: &[(f64, f64)] = &[(8.0, 2.0), (12.0, 2.0), (12.0, 4.0), (8.0, 4.0), (8.0, 2.0)];
+    let errors = defects(&polygon(&[SQUARE, hole]));
+    assert_eq!(kinds(&errors), [ValidityErrorKind::HoleCrossesExterior]);
+    assert_eq!(errors[0].ring, Some(1));
+    assert_eq!(errors[0].point.x, 10.0);
+}
+
+#[test]
+fn nested_holes() {
+    let outer: &[(f64, f64)] = &[(1.0, 1.0), (1.0, 9.0), (9.0, 9.0), (9.0, 1.0), (1.0, 1.0)];
+    let inner: &[(f64, f64)] = &[(3.0, 3.0), (3.0, 5.0), (5.0, 5.0), (5.0, 3.0), (3.0, 3.0)];
+    // Whichever order the holes come in, the inner one is reported.
+    let errors = defects(&polygon(&[SQUARE, outer, inner]));
+    assert_eq!(kinds(&errors), [ValidityErrorKind::NestedHoles]);
+    assert_eq!(errors[0].ring, Some(2));
+    assert_eq!(errors[0].point, pt(3.0, 3.0));
+
+    let errors = defects(&polygon(&[SQUARE, inner, outer]));
+    assert_eq!(kinds(&errors), [ValidityErrorKind::NestedHoles]);
+    assert_eq!(errors[0].ring, Some(1));
+}
+
+#[test]
+fn overlapping_holes() {
+    let a: &[(f64, f64)] = &[(1.0, 1.0), (1.0, 5.0), (5.0, 5.0), (5.0, 1.0), (1.0, 1.0)];
+    let b: &[(f64, f64)] = &[(3.0, 3.0), (3.0, 7.0), (7.0, 7.0), (7.0, 3.0), (3.0, 3.0)];
+    let errors = defects(&polygon(&[SQUARE, a, b]));
+    assert_eq!(kinds(&errors), [ValidityErrorKind::OverlappingHoles]);
+    assert_eq!(errors[0].ring, Some(2));
+}
+
+#[test]
+fn parts_are_counted_depth_first() {
+    let geom = Geom::parse_wkt(
+        "GEOMETRYCOLLECTION(POINT(0 0),MULTILINESTRING((0 0,1 1),(0 0,1 1,1 1)),\
+         POLYGON((0 0,2 2,2 0,0 2,0 0)))",
+    )
+    .unwrap();
+    let errors = defects(&geom);
+    assert_eq!(
+        kinds(&errors),
+        [
+            ValidityErrorKind::DuplicatePoint,
+            ValidityErrorKind::SelfIntersection
+        ]
+    );
+    assert_eq!(errors[0].part, 2);
+    assert_eq!(errors[1].part, 3);
+}
+
+/// Repairs the geometry, checking that the result passes `validate`.
+fn repair(wkt: &str) -> Geom {
+    let repaired = Geom::parse_wkt(wkt).unwrap().make_valid();
+    assert_eq!(repaired.validate(), Ok(()), "{}", repaired.to_wkt());
+    repaired
+}
+
+#[test]
+fn make_valid_splits_a_bow_tie() {
+    let geom = repair("POLYGON((0 0,2 2,2 0,0 2,0 0))");
+    assert_eq!(geom.geom_type(), tg_geom_type::TG_MULTIPOLYGON);
+    assert_eq!(geom.polys().len(), 2);
+    for poly in geom.polys() {
+        assert_eq!(poly.exterior().points().len(), 4);
+        assert_eq!(poly.as_geom().area(), 1.0);
+    }
+    assert!(geom.intersects_xy(1.5, 1.0));
+    assert!(geom.intersects_xy(0.5, 1.0));
+    assert!(!geom.intersects_xy(1.0, 1.5));
+}
+
+#[test]
+fn make_valid_drops_redundant_points() {
+    // A repeated point, and a collinear one.
+    let geom = repair("POLYGON((0 0,5 0,10 0,10 0,10 10,0 10,0 0))");
+    assert_eq!(geom.geom_type(), tg_geom_type::TG_POLYGON);
+    assert_eq!(geom.poly().unwrap().exterior().points().len(), 5);
+    assert_eq!(geom.area(), 100.0);
+
+    // A spike out to (6 4) and back.
+    let geom = repair("POLYGON((0 0,4 0,4 4,6 4,4 4,0 4,0 0))");
+    let exterior = geom.poly().unwrap().exterior();
+    assert_eq!(exterior.points().len(), 5);
+    assert!(!exterior.points().contains(&pt(6.0, 4.0)));
+    assert_eq!(geom.area(), 16.0);
+
+    let geom = repair("LINESTRING(0 0,1 1,1 1,2 0)");
+    assert_eq!(geom.to_wkt(), "LINESTRING(0 0,1 1,2 0)");
+    let geom = repair("MULTILINESTRING((0 0,0 0),(0 0,1 1))");
+    assert_eq!(geom.to_wkt(), "MULTILINESTRING((0 0,1 1))");
+}
+
+#[test]
+fn make_valid_normalises_winding() {
+    for wkt in [
+        "POLYGON((0 0,0 10,10 10,10 0,0 0),(2 2,4 2,4 4,2 4,2 2))",
+        "POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,2 4,4 4,4 2,2 2))",
+    ] {
+        let geom = repair(wkt);
+        let poly = geom.poly().unwrap();
+        assert!(!poly.exterior().clockwise(), "{}", wkt);
+        assert_eq!(poly.holes().len(), 1);
+        assert!(poly.holes().next().unwrap().clockwise(), "{}", wkt);
+        assert_eq!(geom.area(), 96.0);
+    }
+}
+
+#[test]
+fn make_valid_drops_degenerate_holes() {
+    let geom = repair(
+        "POLYGON((0 0,10 0,10 10,0 10,0 0),\
+         (2 2,3 3,2 2),(2 2,4 4,6 6,2 2),(20 2,22 2,22 4,20 2),(6 6,6 8,8 8,6 6))",
+    );
+    let poly = geom.poly().unwrap();
+    // Only the last hole, a proper triangle, survives.
+    assert_eq!(poly.holes().len(), 1);
+    assert!(poly
+        .holes()
+        .next()
+        .unwrap()
+        .points()
+        .contains(&pt(6.0, 8.0)));
+    assert_eq!(geom.area(), 98.0);
+
+    // A polygon with no area is left empty.
+    let geom = repair("POLYGON((0 0,1 1,2 2,0 0))");
+    assert!(geom.is_empty());
+    let geom = Geom::new_point(pt(f64::NAN, 0.0)).make_valid();
+    assert!(geom.is_empty());
+    assert_eq!(geom.validate(), Ok(()));
+}